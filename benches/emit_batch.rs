@@ -0,0 +1,97 @@
+//! Compares `emit_to_js` calls one by one with one `emit_batch_to_js` call
+//! for a burst of progress events.
+//!
+//! Run with `cargo bench --bench emit_batch`. Each case reports the average
+//! time for the Rust side of a burst: building each event's
+//! `window.__emit(...)` script and handing the scripts to a thread standing
+//! in for the event loop, one message per event or one per batch, where the
+//! event loop thread joins them as `RuntimeMessage::EvalBatch` does. Evaluating
+//! the scripts in a WebView is not measured.
+use std::hint::black_box;
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+/// Mirrors `js::emit_script` for an inlined payload.
+fn emit_script(event: &str, payload: &serde_json::Value) -> String {
+    format!(
+        "window.__emit({}, {});",
+        serde_json::to_string(event).unwrap(),
+        serde_json::to_string(payload).unwrap()
+    )
+}
+
+/// A progress update as pushed by long-running tasks.
+fn progress(i: usize) -> serde_json::Value {
+    serde_json::json!({ "done": i, "total": 1000, "label": format!("step {}", i) })
+}
+
+/// Stands in for `RuntimeMessage::Eval` and `RuntimeMessage::EvalBatch`.
+enum Message {
+    Eval(String),
+    EvalBatch(Vec<String>),
+    Done(mpsc::Sender<()>),
+}
+
+/// Runs the stand-in event loop, which prepares each message's script for
+/// evaluation once per message, like the real one.
+fn event_loop() -> mpsc::Sender<Message> {
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        for message in rx {
+            match message {
+                Message::Eval(script) => {
+                    black_box(script);
+                }
+                Message::EvalBatch(scripts) => {
+                    black_box(scripts.join("\n"));
+                }
+                Message::Done(done) => {
+                    let _ = done.send(());
+                }
+            }
+        }
+    });
+    tx
+}
+
+/// Waits until the event loop handled everything sent before.
+fn flush(tx: &mpsc::Sender<Message>) {
+    let (done, finished) = mpsc::channel();
+    tx.send(Message::Done(done)).unwrap();
+    finished.recv().unwrap();
+}
+
+fn time<F: FnMut()>(iterations: u32, mut f: F) -> Duration {
+    let start = Instant::now();
+    for _ in 0..iterations {
+        f();
+    }
+    start.elapsed() / iterations
+}
+
+fn bench(events: usize, iterations: u32) {
+    let payloads: Vec<_> = (0..events).map(progress).collect();
+    let tx = event_loop();
+
+    let one_by_one = time(iterations, || {
+        for payload in &payloads {
+            tx.send(Message::Eval(emit_script("progress", payload))).unwrap();
+        }
+        flush(&tx);
+    });
+    let batched = time(iterations, || {
+        let scripts = payloads.iter().map(|payload| emit_script("progress", payload)).collect();
+        tx.send(Message::EvalBatch(scripts)).unwrap();
+        flush(&tx);
+    });
+
+    println!("{} events:", events);
+    println!("  one by one  {:>10.2?}  ({} messages)", one_by_one, events);
+    println!("  batched     {:>10.2?}  (1 message)", batched);
+}
+
+fn main() {
+    bench(10, 10_000);
+    bench(100, 2_000);
+    bench(1000, 200);
+}
@@ -0,0 +1,122 @@
+//! Compares JSON and MessagePack for representative IPC payloads.
+//!
+//! Run with `cargo bench --bench ipc_format`. Each case reports the encoded
+//! size and the average time to encode and decode it with `serde_json` and
+//! `rmp-serde`, which are the codecs behind `ipc_format="json"` and
+//! `ipc_format="msgpack"`.
+use serde::{Deserialize, Serialize};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+/// Mirrors the shape of `ipc_req::SerdeRequest` as handed to Python.
+#[derive(Serialize, Deserialize)]
+struct Request {
+    id: u64,
+    method: String,
+    uri: String,
+    version: String,
+    headers: Vec<(String, String)>,
+    raw_headers: Vec<(String, String)>,
+    body: Invoke,
+    json: Option<serde_json::Value>,
+    binary: bool,
+}
+
+/// Mirrors a `window.invoke` message.
+#[derive(Serialize, Deserialize)]
+struct Invoke {
+    cmd: String,
+    invoke_id: String,
+    payload: Vec<serde_json::Value>,
+}
+
+fn request(payload: Vec<serde_json::Value>) -> Request {
+    Request {
+        id: 1,
+        method: "POST".to_string(),
+        uri: "pyframe://localhost/".to_string(),
+        version: "HTTP/1.1".to_string(),
+        headers: vec![("content-type".to_string(), "text/plain".to_string())],
+        raw_headers: Vec::new(),
+        body: Invoke {
+            cmd: "update".to_string(),
+            invoke_id: "1234567890".to_string(),
+            payload,
+        },
+        json: None,
+        binary: false,
+    }
+}
+
+/// A small command call such as `invoke("add", [1, 2])`.
+fn small() -> Request {
+    request(vec![1.into(), 2.into()])
+}
+
+/// A table of 1000 rows, as pushed by data-heavy UIs.
+fn table() -> Request {
+    let rows = (0..1000)
+        .map(|i| {
+            serde_json::json!({
+                "id": i,
+                "name": format!("row {}", i),
+                "value": i as f64 * 0.5,
+                "tags": ["a", "b", "c"],
+                "active": i % 2 == 0,
+            })
+        })
+        .collect();
+    request(vec![serde_json::Value::Array(rows)])
+}
+
+/// 100k samples of numeric series data.
+fn series() -> Request {
+    let samples = (0..100_000).map(|i| (i as f64).sin().into()).collect();
+    request(vec![serde_json::Value::Array(samples)])
+}
+
+fn time<F: FnMut()>(iterations: u32, mut f: F) -> Duration {
+    let start = Instant::now();
+    for _ in 0..iterations {
+        f();
+    }
+    start.elapsed() / iterations
+}
+
+fn bench(name: &str, iterations: u32, req: &Request) {
+    let json = serde_json::to_vec(req).unwrap();
+    let msgpack = rmp_serde::to_vec_named(req).unwrap();
+
+    let json_encode = time(iterations, || {
+        black_box(serde_json::to_vec(black_box(req)).unwrap());
+    });
+    let json_decode = time(iterations, || {
+        black_box(serde_json::from_slice::<Request>(black_box(&json)).unwrap());
+    });
+    let msgpack_encode = time(iterations, || {
+        black_box(rmp_serde::to_vec_named(black_box(req)).unwrap());
+    });
+    let msgpack_decode = time(iterations, || {
+        black_box(rmp_serde::from_slice::<Request>(black_box(&msgpack)).unwrap());
+    });
+
+    println!("{}:", name);
+    println!(
+        "  json     {:>9} bytes  encode {:>10.2?}  decode {:>10.2?}",
+        json.len(),
+        json_encode,
+        json_decode
+    );
+    println!(
+        "  msgpack  {:>9} bytes  encode {:>10.2?}  decode {:>10.2?}",
+        msgpack.len(),
+        msgpack_encode,
+        msgpack_decode
+    );
+}
+
+fn main() {
+    bench("small invoke", 100_000, &small());
+    bench("1000-row table", 200, &table());
+    bench("100k-sample series", 20, &series());
+}
@@ -1,9 +1,360 @@
-from typing import Callable
+import os
+from typing import Any, Awaitable, Callable, Optional, Union
 
 
 def create_webframe(
-    handler: Callable, 
-    html: str,
-    event_sender:Callable,
-    handle_event_result: Callable
-    ):...
\ No newline at end of file
+    handler: Callable,
+    html: Optional[str] = None,
+    *,
+    url: Optional[str] = None,
+    window_config: Optional[dict[str, Any]] = None,
+    window_state: Optional[Union[str, "os.PathLike[str]"]] = None,
+    watch_path: Optional[Union[str, "os.PathLike[str]"]] = None,
+    base_url: Optional[str] = None,
+    asset_root: Optional[Union[str, "os.PathLike[str]", Callable[[str], Optional[Union[bytes, str]]]]] = None,
+    icon: Optional[Union[str, "os.PathLike[str]", bytes, tuple[bytes, int, int]]] = None,
+    splash: Optional[str] = None,
+    devtools: bool = False,
+    incognito: bool = False,
+    accept_invalid_certs: bool = False,
+    proxy: Optional[str] = None,
+    data_directory: Optional[Union[str, "os.PathLike[str]"]] = None,
+    zoom: Optional[float] = None,
+    theme: str = "system",
+    auto_title: bool = False,
+    background_color: Optional[Union[str, tuple[int, ...]]] = None,
+    context_menu: bool = True,
+    text_selection: bool = True,
+    user_agent: Optional[str] = None,
+    init_scripts: list[str] = [],
+    transparent: bool = False,
+    vibrancy: Optional[str] = None,
+    quit_on_last_window_closed: bool = True,
+    handle_sigint: bool = True,
+    runtime_threads: Optional[int] = None,
+    on_occlusion_change: Optional[Callable[[bool], None]] = None,
+    on_focus: Optional[Callable[[bool], None]] = None,
+    on_title_changed: Optional[Callable[[str], None]] = None,
+    on_window_event: Optional[Callable[[str], None]] = None,
+    on_key: Optional[Callable[[str], Optional[bool]]] = None,
+    on_close: Optional[Callable[[], Optional[bool]]] = None,
+    on_error: Optional[Callable[[str], None]] = None,
+    ipc_format: str = "json",
+    binary_ipc: bool = False,
+    max_ipc_body_size: Optional[int] = 64 * 1024 * 1024,
+    large_payload_threshold: Optional[int] = 1024 * 1024,
+    message_capacity: Optional[int] = None,
+    message_validation: str = "off",
+    auto_timestamp: bool = True,
+    py_event_loop: Optional[Any] = None,
+    pyevent_to_rust_queue: Optional[Callable[[Any], Awaitable[None]]] = None,
+    rust_to_py_ipc: Optional[Callable[[Any], Awaitable[None]]] = None,
+    python_loop_policy: str = "restart",
+    on_python_crash: Optional[Callable[[str, bool], None]] = None,
+    heartbeat: Optional[float] = None,
+    on_stall: Optional[Callable[[float], None]] = None,
+    on_new_window: Optional[Callable[[str], Optional[str]]] = None,
+    on_navigation: Optional[Callable[[str], Optional[bool]]] = None,
+    on_file_drop: Optional[Callable[[list[str]], None]] = None,
+    on_file_hover: Optional[Callable[[Optional[list[str]]], None]] = None,
+    menu: Optional[list[dict[str, Any]]] = None,
+    on_menu: Optional[Callable[[str], None]] = None,
+    tray: Optional[dict[str, Any]] = None,
+    on_tray_click: Optional[Callable[[str], None]] = None,
+    blocking: bool = True,
+    ) -> Optional[FrameHandle]:...
+
+def create_webframe_blank(handler: Callable, **kwargs: Any) -> Optional[FrameHandle]:
+    """create_webframe on about:blank; push content later with load_html/load_url."""
+    ...
+
+MAIN_WINDOW_ID: int
+"""Id of the window created by create_webframe."""
+
+class FrameError(RuntimeError):
+    """Base class of the errors raised by frame, e.g. when no webframe is running."""
+
+class WindowError(FrameError): ...
+class WebViewError(FrameError): ...
+class IpcError(FrameError): ...
+class ChannelError(FrameError): ...
+
+def emit_str(json: Union[str, bytes], window_id: Optional[int] = None) -> None:
+    """Send a message to Rust as a JSON str or MessagePack bytes.
+
+    Goes to the channel of `window_id`, or of the first open window.
+
+    Fields: message (str), timestamp (str) and payload (any JSON value),
+    all optional.
+    """
+    ...
+
+async def emit_async(json: Union[str, bytes], window_id: Optional[int] = None) -> None:
+    """Awaitable variant of emit_str; waits while a bounded channel is full."""
+    ...
+
+def emit_sync(
+    json: Union[str, bytes],
+    timeout: Optional[float] = None,
+    window_id: Optional[int] = None,
+) -> str:
+    """Like emit_str, but block until Rust acknowledges the message and return its reply.
+
+    Raises TimeoutError if no acknowledgment arrives within `timeout` seconds.
+    """
+    ...
+
+def emit_to_js(event: str, payload: Any = None, window_id: Optional[int] = None) -> None:
+    """Push `payload` to the page's window.ipcOn(event, callback) subscribers."""
+    ...
+
+def emit_batch_to_js(events: list[tuple[str, Any]], window_id: Optional[int] = None) -> None:
+    """Push several (event, payload) pairs in order, in one script evaluation."""
+    ...
+
+def register_method(name: str, handler: Optional[Callable[..., Any]]) -> None:
+    """Serve `handler` as the JSON-RPC method `name` (window.rpc); None removes it."""
+    ...
+
+def register_route(pattern: str, handler: Optional[Callable], *, method: Optional[str] = None) -> None:
+    """Route `window.ipcRoute` requests matching `pattern` (e.g. "/users/{id}") to `handler`; None removes it."""
+    ...
+
+async def eval_js(script: str) -> Any:
+    """Evaluate `script` in the WebView and return its JSON-parsed result.
+
+    Resolves to None for values JSON cannot represent, and always on
+    backends without evaluation callbacks (Android).
+    """
+    ...
+
+eval = eval_js
+
+
+def spawn_window(
+    handler: Callable,
+    html: Optional[str] = None,
+    *,
+    url: Optional[str] = None,
+    base_url: Optional[str] = None,
+    window_config: Optional[dict[str, Any]] = None,
+    message_capacity: Optional[int] = None,
+) -> int:
+    """Open another window on the running event loop and return its window id.
+
+    The event loop is owned and run by the first create_webframe call.
+    """
+    ...
+
+def close_window() -> None:
+    """Close the main window; exits if it was the last one and quit_on_last_window_closed is set."""
+    ...
+
+def open_devtools(window_id: Optional[int] = None) -> None:
+    """Open the web inspector; needs create_webframe(devtools=True)."""
+    ...
+
+def set_window_title(title: str, window_id: Optional[int] = None) -> None:
+    """Change the title of a window, the main window by default."""
+    ...
+
+def reload(window_id: Optional[int] = None) -> None: ...
+
+def set_zoom(factor: float, window_id: Optional[int] = None) -> None:
+    """Zoom the page; 1.0 is the normal size, 0.25 to 5.0 allowed."""
+    ...
+
+def set_position(x: float, y: float, window_id: Optional[int] = None) -> None:
+    """Move the window's outer frame to x, y in logical pixels."""
+    ...
+
+def center_window(window_id: Optional[int] = None) -> None:
+    """Center the window on the monitor showing it."""
+    ...
+
+def set_opacity(value: float, window_id: Optional[int] = None) -> None:
+    """Set the whole window's opacity, clamped to 0.0-1.0."""
+    ...
+
+async def clear_browsing_data(window_id: Optional[int] = None) -> None:
+    """Delete cookies, cache, localStorage, sessionStorage and other site data."""
+    ...
+
+def load_html(html: str, window_id: Optional[int] = None) -> None:
+    """Replace the entire document with `html`."""
+    ...
+
+def load_url(url: str, window_id: Optional[int] = None) -> None: ...
+
+async def set_fullscreen(enabled: bool, window_id: Optional[int] = None) -> bool:
+    """Enter or leave borderless fullscreen; resolves to the resulting mode."""
+    ...
+
+async def get_scale_factor(window_id: Optional[int] = None) -> float:
+    """Physical pixels per logical pixel of the window's monitor."""
+    ...
+
+async def list_monitors() -> list[dict[str, Any]]:
+    """The connected displays: name, x, y, width, height, scale_factor, primary."""
+    ...
+
+async def current_monitor(window_id: Optional[int] = None) -> Optional[dict[str, Any]]:
+    """The display showing the window, like list_monitors' entries."""
+    ...
+
+async def get_theme(window_id: Optional[int] = None) -> str:
+    """The window's theme, "light" or "dark"."""
+    ...
+
+async def set_always_on_top(enabled: bool, window_id: Optional[int] = None) -> bool:
+    """Keep the window above others, or stop; resolves to the state the window reports."""
+    ...
+
+async def hide_window(window_id: Optional[int] = None) -> bool:
+    """Hide the window; resolves to whether it is visible afterwards, like the next three."""
+    ...
+
+async def show_window(window_id: Optional[int] = None) -> bool: ...
+
+async def minimize_window(window_id: Optional[int] = None) -> bool: ...
+
+async def maximize_window(window_id: Optional[int] = None) -> bool: ...
+
+async def capture_webview(path: Optional[Union[str, "os.PathLike[str]"]] = None, window_id: Optional[int] = None) -> bytes:
+    """Screenshot of the window content as PNG, also written to `path` if given."""
+    ...
+
+async def print_to_pdf(path: Union[str, "os.PathLike[str]"], window_id: Optional[int] = None) -> None:
+    """Print the page to a PDF file; Windows (WebView2) only."""
+    ...
+
+async def get_cookies(url: Optional[str] = None, window_id: Optional[int] = None) -> list[dict[str, Any]]:
+    """Cookies sent to `url` (all without it), as dicts with name, value, domain, path, expires, ..."""
+    ...
+
+async def set_cookie(
+    url: str,
+    name: str,
+    value: str,
+    *,
+    path: str = "/",
+    expires: Optional[float] = None,
+    secure: Optional[bool] = None,
+    http_only: bool = False,
+    same_site: Optional[str] = None,
+    window_id: Optional[int] = None,
+) -> None:
+    """Store a cookie for the host of `url`; Windows (WebView2) only."""
+    ...
+
+async def clear_cookies(window_id: Optional[int] = None) -> None:
+    """Delete every cookie; Windows (WebView2) only."""
+    ...
+
+def clipboard_set(text: str) -> None: ...
+
+def clipboard_get() -> Optional[str]:
+    """Text on the clipboard; None if it is empty or not text."""
+    ...
+
+def register_shortcut(accelerator: str, callback: Callable[[], None]) -> None:
+    """Call `callback` when `accelerator` (e.g. "CmdOrCtrl+Shift+K") is pressed, even unfocused."""
+    ...
+
+def unregister_shortcut(accelerator: str) -> None: ...
+
+def list_shortcuts() -> list[str]: ...
+
+async def open_file_dialog(
+    filters: Optional[list[tuple[str, list[str]]]] = None,
+    multiple: bool = False,
+    *,
+    window_id: Optional[int] = None,
+) -> list[str]:
+    """Pick files with the native dialog; an empty list on cancel."""
+    ...
+
+async def save_file_dialog(
+    filters: Optional[list[tuple[str, list[str]]]] = None,
+    default_name: Optional[str] = None,
+    *,
+    window_id: Optional[int] = None,
+) -> Optional[str]:
+    """Choose a path to save to with the native dialog; None on cancel."""
+    ...
+
+async def message_box(title: str, body: str, kind: str = "info", *, window_id: Optional[int] = None) -> None:
+    """Show a native message box; kind is "info", "warning" or "error"."""
+    ...
+
+async def confirm(title: str, body: str, *, window_id: Optional[int] = None) -> bool:
+    """Ask with native OK/Cancel buttons; True for OK."""
+    ...
+
+def init_logging(level: str = "info") -> None:
+    """Print the extension's log records to stderr; RUST_LOG overrides `level`."""
+    ...
+
+def quit_app() -> None:
+    """Close the window and exit the application."""
+    ...
+
+def shutdown(timeout: float = 5.0) -> None:
+    """Quit, abort background tasks and stop the Python loop threads, waiting up to `timeout` seconds for them."""
+    ...
+
+def python_loop_state() -> str:
+    """State of the Python event loop thread: "running", "stopped" or "crashed"."""
+    ...
+
+def channel_stats() -> dict[str, dict[str, int]]:
+    """Sent, received, pending and rejected counts of the "emit", "from_python" and "to_python" channels."""
+    ...
+
+def add_child_view(
+    html: str, x: float, y: float, width: float, height: float, *, relative: bool = False
+) -> int:
+    """Add a child WebView and return its view id.
+
+    With relative=True the bounds are fractions of the window size and
+    follow window resizes.
+    """
+    ...
+
+def set_view_bounds(
+    view_id: int, x: float, y: float, width: float, height: float, *, relative: bool = False
+) -> None:
+    """Move and resize a child view. Raises ValueError for unknown ids."""
+    ...
+
+class SenderHandle:
+    """Sending half of a channel into Rust (or of a create_channel pair)."""
+    def send(self, msg: Any) -> None: ...
+    def send_and_wait(self, msg: Any, timeout: Optional[float] = None) -> Any:
+        """Send and block until the message is replied to; raises TimeoutError."""
+        ...
+
+class ReceiverHandle:
+    """Receiving half of a channel out of Rust (or of a create_channel pair)."""
+    def recv(self) -> Optional[Any]: ...
+    def recv_blocking(self) -> Optional[Any]: ...
+    def recv_timeout(self, seconds: float) -> Optional[Any]:
+        """Wait up to `seconds`; None on timeout or once the channel is closed."""
+        ...
+    def on_message(self, callback: Optional[Callable[[Any], None]]) -> None:
+        """Call `callback(msg)` on a Rust thread for every message; None stops delivery."""
+        ...
+
+def create_channel() -> tuple[SenderHandle, ReceiverHandle]:
+    """Create a connected sender/receiver pair over an unbounded channel."""
+    ...
+
+class FrameHandle:
+    """Event loop started by create_webframe(blocking=False) on a background thread."""
+    @property
+    def window_id(self) -> int: ...
+    def is_running(self) -> bool: ...
+    def join(self) -> None:
+        """Wait until the event loop exits."""
+        ...
+    def quit(self) -> None: ...
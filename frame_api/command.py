@@ -51,43 +51,25 @@ async def dispatch(name: str, args: List[Any]) -> Any:
 
 
 
-async def handle_ipc_message(raw: str) -> str:
+async def handle_ipc_message(raw: str) -> Any:
     """
-    Handle an IPC message from window.ipc.postMessage (JS).
+    Handle an IPC message from window.invoke (JS).
     
     raw: JSON string from JS
-    Returns: JS code string (to be eval'd by Rust/Wry)
+    Returns: the command result, which Rust uses to resolve the JS promise.
+    Raises: on any failure, which Rust uses to reject the JS promise.
     """
-    try:
-        msg = json.loads(raw)
-        print(f"From IPC frontend: {msg})")
-        if not isinstance(msg, dict):
-            raise ValueError("Invalid IPC message format (not an object)")
-        body: str = json.loads(msg.get("body", ""))
-        if not isinstance(body, dict):
-            raise ValueError("Invalid IPC message body (not an object)")
-        msg = body
-        cmd: str = msg["cmd"]
-        # print(f"IPC command received: {cmd}({args})")
-        result_id: str = msg["result_id"]
-        error_id: str = msg["error_id"]
-        args: list[Any] = msg.get("payload", [])
-        try:
-            result = await dispatch(cmd, args)
-            # Erfolgreich → Callback aufrufen
-            print("ipc cmd result:", result)
-            # raise NotImplementedError("currently not implemented")
-            # js_code = f"window._{result_id}({json.dumps(result)});"
-            js_code = f"""console.log('{cmd} executed');"""
-        except Exception as e:
-            # Fehler → Error-Callback
-            js_code = f"""console.log('error occurred while executing {cmd}: {str(e)}');"""
-            print(js_code)
-            # js_code = f"window._{error_id}({json.dumps(str(e))});"
-
-        return js_code
-
-    except Exception as e:
-        print(f"IPC handling error: {e}")
-        # Top-Level Fehler → ebenfalls als String zurückgeben
-        return f"""console.error("IPC error: {str(e)}");"""
\ No newline at end of file
+    msg = json.loads(raw)
+    print(f"From IPC frontend: {msg})")
+    if not isinstance(msg, dict):
+        raise ValueError("Invalid IPC message format (not an object)")
+    body: str = json.loads(msg.get("body", ""))
+    if not isinstance(body, dict):
+        raise ValueError("Invalid IPC message body (not an object)")
+    msg = body
+    cmd: str = msg["cmd"]
+    # print(f"IPC command received: {cmd}({args})")
+    args: list[Any] = msg.get("payload", [])
+    result = await dispatch(cmd, args)
+    print("ipc cmd result:", result)
+    return result
@@ -287,7 +287,8 @@ async def eventloop_event_register_typed(
     _pending.register(req_id, future)
 
     data = {
-        "message": str(request.to_json_array()),
+        "message": method,
+        "payload": request.to_json_array(),
         # "future": future,
     }
 
@@ -36,13 +36,14 @@ async def set_title(title: str) -> bool:
  
 
 
-def on_ipc(data):
-    return asyncio.run(handle_ipc_message(data))
+async def on_ipc(data):
+    return await handle_ipc_message(data)
 
 if __name__ == "__main__":
     """
     Launch the webframe with:
-    - handler: the async function above to process messages from JS
+    - handler: the async function above to process messages from JS;
+      its return value resolves the matching JS promise
     - html: the HTML template string defined earlier
     """
     create_webframe(
@@ -0,0 +1,37 @@
+(function () {
+  // Key combinations `on_key` handled on their last press. A page cannot
+  // wait for Python, so its answer applies from the next press on.
+  const handled = new Set();
+
+  function combo(message) {
+    const { alt, ctrl, meta, shift } = message.modifiers;
+    return [message.type, message.code, alt, ctrl, meta, shift].join(":");
+  }
+
+  // Forwards key events to `on_key` before any listener of the page runs,
+  // and hides the combinations Python handled from the page.
+  function forward(event) {
+    if (event.isComposing) return;
+    const message = {
+      type: event.type,
+      key: event.key,
+      code: event.code,
+      modifiers: { alt: event.altKey, ctrl: event.ctrlKey, meta: event.metaKey, shift: event.shiftKey },
+      repeat: event.repeat,
+    };
+    if (handled.has(combo(message))) {
+      event.preventDefault();
+      event.stopImmediatePropagation();
+    }
+    window.ipc.postMessage("pyframe-key:" + JSON.stringify(message));
+  }
+
+  window.addEventListener("keydown", forward, true);
+  window.addEventListener("keyup", forward, true);
+
+  // Evaluated by Rust with the event and whether `on_key` returned True.
+  window.__keyHandled = (message, isHandled) => {
+    if (isHandled) handled.add(combo(message));
+    else handled.delete(combo(message));
+  };
+})();
@@ -1 +1,49 @@
+/// Initialization script injected into every WebView.
+///
+/// It defines the JS side of the IPC bridge:
+/// - `window.invoke(cmd, args)` posts `{cmd, payload: args, invoke_id}`
+///   and returns a promise for the Python command's result.
+/// - `window.ipcInvoke(payload)` posts `{payload, invoke_id}` for handlers
+///   that do not use `frame_api`; also a promise.
+/// - `window.ipcRoute(path, payload, method)` posts `{route: path, method,
+///   payload, invoke_id}` for the handler of the matching route; also a
+///   promise.
+/// - `window.ipcPostBinary(data)` posts an `ArrayBuffer` or typed array as
+///   `pyframe-binary:<base64>`, decoded by Rust with `binary_ipc=True`.
+/// - `window.start_dragging()` moves the window with the mouse; elements
+///   marked `data-pyframe-drag-region` call it on `mousedown`, which makes
+///   custom title bars of undecorated windows draggable.
+/// - `window.ipcReady()` posts `pyframe-command:ready`, which dismisses the
+///   `splash` window.
+/// - `window.ipcOn(event, callback)` subscribes to the events Python pushes
+///   with `emit_to_js`, which Rust delivers by evaluating
+///   `window.__emit(event, payload)`, or `window.__emitFetched(event, url)`
+///   for payloads too large to inline.
+/// - `window.__resolve(invoke_id, result)` and
+///   `window.__reject(invoke_id, message)` settle those promises. Rust
+///   evaluates them with the handler's return value or exception message.
 pub static INITIALIZEPY_SCRIPT: &str = include_str!("./py.js");
+
+/// Initialization script injected into every WebView with `on_key`.
+///
+/// It posts every `keydown` and `keyup` as `pyframe-key:<json>` from a
+/// capturing listener on `window`, ahead of the page's own listeners, and
+/// suppresses the combinations `on_key` claimed through
+/// `window.__keyHandled(event, handled)`.
+pub static KEYS_SCRIPT: &str = include_str!("./keys.js");
+
+/// Initialization script for `context_menu=False`: swallows right clicks
+/// before the page or the WebView sees them.
+pub static NO_CONTEXT_MENU_SCRIPT: &str =
+    r#"window.addEventListener("contextmenu", (event) => event.preventDefault(), true);"#;
+
+/// Initialization script for `text_selection=False`. Form fields and
+/// editable elements stay selectable so they can still be edited.
+pub static NO_TEXT_SELECTION_SCRIPT: &str = r#"(function () {
+  const style = document.createElement("style");
+  style.textContent = ":root { -webkit-user-select: none; user-select: none; }"
+    + " input, textarea, [contenteditable], [contenteditable] * { -webkit-user-select: text; user-select: text; }";
+  const insert = () => document.documentElement.appendChild(style);
+  if (document.documentElement) insert();
+  else document.addEventListener("DOMContentLoaded", insert);
+})();"#;
@@ -3,49 +3,426 @@
     return window.crypto.getRandomValues(new Uint32Array(1))[0].toString();
   }
 
-  function transformCallback(callback, once = true) {
-    const identifier = uid();
-    const prop = `_${identifier}`;
-
-    Object.defineProperty(window, prop, {
-      value: (result) => {
-        if (once) Reflect.deleteProperty(window, prop);
-        if (callback) callback(result);
+  // Minimal MessagePack encoder for `ipc_format="msgpack"`. Replies still
+  // arrive as evaluated scripts, so no decoder is needed.
+  function encodeMsgpack(value) {
+    const bytes = [];
+    const utf8 = new TextEncoder();
+    const view = new DataView(new ArrayBuffer(8));
+
+    function pushUint(n, size) {
+      for (let i = size - 1; i >= 0; i--) bytes.push((n / 2 ** (8 * i)) & 0xff);
+    }
+
+    function header(fix, fixMax, code16, code32, length) {
+      if (length <= fixMax) bytes.push(fix | length);
+      else if (length < 0x10000) { bytes.push(code16); pushUint(length, 2); }
+      else { bytes.push(code32); pushUint(length, 4); }
+    }
+
+    function write(v) {
+      if (v === null || v === undefined) {
+        bytes.push(0xc0);
+      } else if (typeof v === "boolean") {
+        bytes.push(v ? 0xc3 : 0xc2);
+      } else if (typeof v === "number") {
+        if (Number.isInteger(v) && v >= 0 && v < 2 ** 32) {
+          if (v < 0x80) bytes.push(v);
+          else if (v < 0x100) bytes.push(0xcc, v);
+          else if (v < 0x10000) { bytes.push(0xcd); pushUint(v, 2); }
+          else { bytes.push(0xce); pushUint(v, 4); }
+        } else if (Number.isInteger(v) && v < 0 && v >= -(2 ** 31)) {
+          if (v >= -32) bytes.push(v & 0xff);
+          else { bytes.push(0xd2); pushUint(v >>> 0, 4); }
+        } else {
+          bytes.push(0xcb);
+          view.setFloat64(0, v);
+          for (let i = 0; i < 8; i++) bytes.push(view.getUint8(i));
+        }
+      } else if (typeof v === "string") {
+        const data = utf8.encode(v);
+        if (data.length < 32) bytes.push(0xa0 | data.length);
+        else if (data.length < 0x100) bytes.push(0xd9, data.length);
+        else if (data.length < 0x10000) { bytes.push(0xda); pushUint(data.length, 2); }
+        else { bytes.push(0xdb); pushUint(data.length, 4); }
+        for (const b of data) bytes.push(b);
+      } else if (v instanceof Uint8Array) {
+        if (v.length < 0x100) bytes.push(0xc4, v.length);
+        else if (v.length < 0x10000) { bytes.push(0xc5); pushUint(v.length, 2); }
+        else { bytes.push(0xc6); pushUint(v.length, 4); }
+        for (const b of v) bytes.push(b);
+      } else if (Array.isArray(v)) {
+        header(0x90, 15, 0xdc, 0xdd, v.length);
+        v.forEach(write);
+      } else if (typeof v === "object") {
+        const entries = Object.entries(v);
+        header(0x80, 15, 0xde, 0xdf, entries.length);
+        for (const [key, item] of entries) {
+          write(key);
+          write(item);
+        }
+      } else {
+        bytes.push(0xc0);
+      }
+    }
+
+    write(value);
+    return Uint8Array.from(bytes);
+  }
+
+  // Encodes a message in the format negotiated by Rust at startup.
+  // `postMessage` only carries strings, so MessagePack is sent as base64.
+  function encodeMessage(message) {
+    if (window.__PYFRAME_IPC_FORMAT__ !== "msgpack") {
+      return JSON.stringify(message);
+    }
+    let binary = "";
+    for (const b of encodeMsgpack(message)) binary += String.fromCharCode(b);
+    return btoa(binary);
+  }
+
+  // Throws for bodies Rust would drop for exceeding `max_ipc_body_size`,
+  // before posting them. A UTF-16 unit takes at most 3 UTF-8 bytes, so
+  // short bodies skip the encoding.
+  function checkSize(body) {
+    const max = window.__PYFRAME_MAX_BODY_SIZE__;
+    if (max === undefined || body.length * 3 <= max) return;
+    const size = new TextEncoder().encode(body).length;
+    if (size > max) {
+      throw new Error(`IPC message of ${size} bytes exceeds max_ipc_body_size (${max} bytes)`);
+    }
+  }
+
+  // Calls waiting for their reply, keyed by invoke id.
+  const pending = new Map();
+
+  // Posts `message` tagged with a fresh `invoke_id`. The returned promise
+  // settles when Rust evaluates `__resolve`/`__reject` with that id.
+  function post(message) {
+    return new Promise((resolve, reject) => {
+      if (!window.ipc || typeof window.ipc.postMessage !== "function") {
+        reject(new Error("IPC bridge is not available!"));
+        return;
+      }
+
+      const invoke_id = uid();
+      // The id goes first, so Rust can read it from the start of a body
+      // too large to parse and reject this promise.
+      const body = encodeMessage({ invoke_id, ...message });
+      checkSize(body);
+      pending.set(invoke_id, { resolve, reject });
+      window.ipc.postMessage(body);
+    });
+  }
+
+  // Streams read through `ipcStream`/`invokeStream`, keyed by invoke id.
+  const streams = new Map();
+
+  // Posts `message` like `post`, for a handler returning an async
+  // iterator. Returns an async iterator over the items Rust passes to
+  // `__streamChunk`, which ends at `__streamEnd` and throws at
+  // `__streamError`. Leaving a `for await` loop early ignores the rest.
+  function postStream(message) {
+    if (!window.ipc || typeof window.ipc.postMessage !== "function") {
+      throw new Error("IPC bridge is not available!");
+    }
+    const invoke_id = uid();
+    const body = encodeMessage({ invoke_id, ...message });
+    checkSize(body);
+
+    // Items not yet asked for, and `next()` calls waiting for an item, in
+    // call order.
+    const items = [];
+    const waiting = [];
+    let done = false;
+    let error = null;
+    function settle(result) {
+      const waiter = waiting.shift();
+      if (!waiter) return false;
+      if (result instanceof Error) waiter.reject(result);
+      else waiter.resolve(result);
+      return true;
+    }
+    function settleAll(result) {
+      while (settle(result));
+    }
+    streams.set(invoke_id, {
+      push(item) {
+        if (!settle({ value: item, done: false })) items.push(item);
+      },
+      end() {
+        streams.delete(invoke_id);
+        done = true;
+        settleAll({ value: undefined, done: true });
+      },
+      fail(message) {
+        streams.delete(invoke_id);
+        error = new Error(message);
+        settleAll(error);
       },
-      writable: false,
-      configurable: true,
     });
+    window.ipc.postMessage(body);
+
+    return {
+      [Symbol.asyncIterator]() {
+        return this;
+      },
+      next() {
+        if (items.length) return Promise.resolve({ value: items.shift(), done: false });
+        if (error) return Promise.reject(error);
+        if (done) return Promise.resolve({ value: undefined, done: true });
+        return new Promise((resolve, reject) => {
+          waiting.push({ resolve, reject });
+        });
+      },
+      return() {
+        streams.delete(invoke_id);
+        items.length = 0;
+        done = true;
+        settleAll({ value: undefined, done: true });
+        return Promise.resolve({ value: undefined, done: true });
+      },
+    };
+  }
 
-    return identifier;
+  // Evaluated by Rust for each item of a streaming handler. A plain
+  // `invoke` of such a handler resolves with all items at the end.
+  function streamChunk(id, item) {
+    const stream = streams.get(id);
+    if (stream) {
+      stream.push(item);
+      return;
+    }
+    const call = pending.get(id);
+    if (call) (call.items ??= []).push(item);
   }
 
-  async function invoke(cmd, args = []) {
+  function streamEnd(id) {
+    const stream = streams.get(id);
+    if (stream) stream.end();
+    else resolveCall(id, pending.get(id)?.items ?? []);
+  }
+
+  function streamError(id, message) {
+    const stream = streams.get(id);
+    if (stream) stream.fail(message);
+    else rejectCall(id, message);
+  }
+
+  // Resolves the call `id` with the handler's JSON return value.
+  function resolveCall(id, result) {
+    const call = pending.get(id);
+    if (!call) return;
+    pending.delete(id);
+    call.resolve(result);
+  }
+
+  // Rejects the call `id` with the message of the exception it raised.
+  function rejectCall(id, message) {
+    const call = pending.get(id);
+    if (!call) return;
+    pending.delete(id);
+    call.reject(new Error(message));
+  }
+
+  // JSON-RPC calls waiting for their response, keyed by id.
+  const rpcPending = new Map();
+  let rpcNextId = 1;
+
+  // `window.rpc(method, params)`: calls the Python method registered with
+  // `register_method` over JSON-RPC 2.0, `params` being an array or an
+  // object. Resolves with the result, or rejects with an Error carrying
+  // the response's `code` and `data`.
+  function rpc(method, params) {
     return new Promise((resolve, reject) => {
       if (!window.ipc || typeof window.ipc.postMessage !== "function") {
         reject(new Error("IPC bridge is not available!"));
         return;
       }
+      const id = rpcNextId++;
+      const body = JSON.stringify({ jsonrpc: "2.0", method, params, id });
+      checkSize(body);
+      rpcPending.set(id, { resolve, reject });
+      window.ipc.postMessage(body);
+    });
+  }
 
-      const result_id = transformCallback((result) => resolve(result), true);
-      const error_id = transformCallback((error) => reject(error), true);
+  // `window.rpcNotify(method, params)`: like `rpc`, as a notification
+  // that gets no response. Fire and forget.
+  function rpcNotify(method, params) {
+    if (!window.ipc || typeof window.ipc.postMessage !== "function") {
+      throw new Error("IPC bridge is not available!");
+    }
+    const body = JSON.stringify({ jsonrpc: "2.0", method, params });
+    checkSize(body);
+    window.ipc.postMessage(body);
+  }
+
+  // Evaluated by Rust with each JSON-RPC response. Responses to requests
+  // not sent by `rpc` are dispatched as a `jsonrpc` event instead, for
+  // other clients.
+  function rpcReply(response) {
+    const call = rpcPending.get(response.id);
+    if (!call) {
+      window.dispatchEvent(new CustomEvent("jsonrpc", { detail: response }));
+      return;
+    }
+    rpcPending.delete(response.id);
+    if ("error" in response) {
+      const error = new Error(response.error.message);
+      error.code = response.error.code;
+      error.data = response.error.data;
+      call.reject(error);
+    } else {
+      call.resolve(response.result);
+    }
+  }
+
+  // `window.ipcPostBinary(data)`: posts an ArrayBuffer or typed array to
+  // the handler unchanged (requires `binary_ipc=True`). Fire and forget.
+  function ipcPostBinary(data) {
+    const bytes = data instanceof ArrayBuffer
+      ? new Uint8Array(data)
+      : new Uint8Array(data.buffer, data.byteOffset, data.byteLength);
+    let binary = "";
+    for (const b of bytes) binary += String.fromCharCode(b);
+    const body = "pyframe-binary:" + btoa(binary);
+    checkSize(body);
+    window.ipc.postMessage(body);
+  }
+
+  // `window.start_dragging()`: moves the window with the mouse while the
+  // button is held. Call it from a `mousedown` handler.
+  function startDragging() {
+    window.ipc.postMessage("pyframe-command:start_dragging");
+  }
+
+  // `window.ipcReady()`: tells Rust the page is rendered, which replaces
+  // the `splash` window with the main window. Harmless without a splash.
+  function ipcReady() {
+    window.ipc.postMessage("pyframe-command:ready");
+  }
+
+  // Elements marked `data-pyframe-drag-region` drag the window, e.g. the
+  // title bar of an undecorated window. Their buttons, inputs and links,
+  // and anything marked `data-pyframe-no-drag`, still get their clicks.
+  document.addEventListener("mousedown", (event) => {
+    if (event.button !== 0 || event.detail > 1) return;
+    const target = event.target;
+    if (!(target instanceof Element)) return;
+    if (!target.closest("[data-pyframe-drag-region]")) return;
+    if (target.closest("button, input, select, textarea, a, [data-pyframe-no-drag]")) return;
+    event.preventDefault();
+    startDragging();
+  });
+
+  // `window.ipcOn(event, callback)`: calls `callback(payload)` for every
+  // `emit_to_js(event, payload)` from Python. Returns an unsubscribe
+  // function. (`window.ipc` itself is frozen.)
+  function ipcOn(event, callback) {
+    const listener = (e) => callback(e.detail);
+    window.addEventListener(event, listener);
+    return () => window.removeEventListener(event, listener);
+  }
+
+  // Evaluated by Rust for `emit_to_js`.
+  function emit(event, payload) {
+    window.dispatchEvent(new CustomEvent(event, { detail: payload }));
+  }
 
-      const message = {
-        cmd,
-        result_id,
-        error_id,
-        payload: args,
-      };
-      console.log(message)
+  // Dispatch of the last payload still being fetched, if any. Events
+  // emitted meanwhile queue behind it, so they arrive in emit order.
+  let fetching = null;
 
-      let result = window.ipc.postMessage(JSON.stringify(message));
-      console.log("ipc postMessage result:", result);
+  function enqueue(step) {
+    const done = (fetching || Promise.resolve()).then(step);
+    fetching = done;
+    done.then(() => {
+      if (fetching === done) {
+        fetching = null;
+      }
     });
   }
 
+  function emitInOrder(event, payload) {
+    if (fetching) {
+      enqueue(() => emit(event, payload));
+    } else {
+      emit(event, payload);
+    }
+  }
+
+  // Evaluated by Rust for payloads above `large_payload_threshold`: the
+  // payload is fetched from `url` right away, in parallel with earlier
+  // fetches, and dispatched once the events before it are.
+  function emitFetched(event, url) {
+    const payload = fetch(url).then((response) => {
+      if (!response.ok) {
+        throw new Error(`HTTP ${response.status}`);
+      }
+      return response.json();
+    });
+    enqueue(() =>
+      payload.then(
+        (payload) => emit(event, payload),
+        (error) => console.error(`Failed to fetch the payload of ${event}:`, error),
+      ),
+    );
+  }
+
+  // `window.invoke(cmd, args)`: calls the Python command `cmd`, see
+  // `frame_api.ipc_command`.
+  async function invoke(cmd, args = []) {
+    return post({ cmd, payload: args });
+  }
+
+  // `window.ipcInvoke(payload)`: sends `payload` to the handler as is and
+  // resolves with its return value. (`window.ipc` itself is frozen.)
+  async function ipcInvoke(payload) {
+    return post({ payload });
+  }
+
+  // `window.ipcRoute(path, payload, method)`: sends `payload` to the
+  // handler of the route matching `path` (see `register_route`), the
+  // window's handler without one, and resolves with its return value.
+  async function ipcRoute(path, payload, method = "POST") {
+    return post({ route: path, method, payload });
+  }
+
+  // `window.invokeStream(cmd, args)` and `window.ipcStream(payload)`: like
+  // `invoke` and `ipcInvoke`, for handlers returning an async iterator.
+  // Returns an async iterator over its items: `for await (const item of
+  // window.ipcStream(payload)) ...`.
+  function invokeStream(cmd, args = []) {
+    return postStream({ cmd, payload: args });
+  }
+
+  function ipcStream(payload) {
+    return postStream({ payload });
+  }
+
 
   if (!window) {
     window= {};
   }
 
   window.invoke = invoke;
+  window.ipcPostBinary = ipcPostBinary;
+  window.ipcInvoke = ipcInvoke;
+  window.ipcRoute = ipcRoute;
+  window.invokeStream = invokeStream;
+  window.ipcStream = ipcStream;
+  window.start_dragging = startDragging;
+  window.ipcReady = ipcReady;
+  window.ipcOn = ipcOn;
+  window.rpc = rpc;
+  window.rpcNotify = rpcNotify;
+  window.__emit = emitInOrder;
+  window.__emitFetched = emitFetched;
+  window.__resolve = resolveCall;
+  window.__reject = rejectCall;
+  window.__rpcReply = rpcReply;
+  window.__streamChunk = streamChunk;
+  window.__streamEnd = streamEnd;
+  window.__streamError = streamError;
 })();
@@ -0,0 +1,62 @@
+use std::io::Cursor;
+
+/// Screen area showing the content of a window, in the coordinates the
+/// platform captures in: points on macOS, physical pixels elsewhere.
+#[derive(Debug, Clone, Copy)]
+pub struct Region {
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+}
+
+impl Region {
+    /// Reads the content area of `window`; `None` while it is hidden or
+    /// minimized, when there is nothing on screen to capture.
+    pub fn of(window: &tao::window::Window) -> Option<Self> {
+        if !window.is_visible() || window.is_minimized() {
+            return None;
+        }
+        let position = window.inner_position().ok()?;
+        let size = window.inner_size();
+        #[cfg(target_os = "macos")]
+        let (position, size) = {
+            let scale = window.scale_factor();
+            (position.to_logical::<i32>(scale), size.to_logical::<u32>(scale))
+        };
+        Some(Region {
+            x: position.x,
+            y: position.y,
+            width: size.width,
+            height: size.height,
+        })
+    }
+}
+
+/// Takes a screenshot of `region`, clipped to the monitor showing its
+/// center, and encodes it as PNG. Windows in front of it are captured too.
+pub fn capture_png(region: Region) -> Result<Vec<u8>, String> {
+    let center_x = region.x + (region.width / 2) as i32;
+    let center_y = region.y + (region.height / 2) as i32;
+    let monitor = xcap::Monitor::from_point(center_x, center_y).map_err(|e| e.to_string())?;
+    let (monitor_x, monitor_y) = (monitor.x().map_err(|e| e.to_string())?, monitor.y().map_err(|e| e.to_string())?);
+    let (monitor_width, monitor_height) = (
+        monitor.width().map_err(|e| e.to_string())? as i32,
+        monitor.height().map_err(|e| e.to_string())? as i32,
+    );
+    let left = (region.x - monitor_x).clamp(0, monitor_width);
+    let top = (region.y - monitor_y).clamp(0, monitor_height);
+    let right = (region.x - monitor_x + region.width as i32).clamp(0, monitor_width);
+    let bottom = (region.y - monitor_y + region.height as i32).clamp(0, monitor_height);
+    if right <= left || bottom <= top {
+        return Err("window is off screen".to_string());
+    }
+    let image = monitor
+        .capture_region(left as u32, top as u32, (right - left) as u32, (bottom - top) as u32)
+        .map_err(|e| e.to_string())?;
+    let mut png = Cursor::new(Vec::new());
+    image
+        .write_to(&mut png, image::ImageFormat::Png)
+        .map_err(|e| e.to_string())?;
+    Ok(png.into_inner())
+}
@@ -0,0 +1,64 @@
+/// Arguments WebView2 starts with unless told otherwise, which wry drops
+/// once any are given.
+#[cfg(target_os = "windows")]
+const WEBVIEW2_DEFAULT_ARGS: &str = "--disable-features=msWebOOUI,msPdfOOUI,msSmartScreenProtection";
+
+/// WebView2 arguments accepting any server certificate, keeping `proxy`
+/// which wry would have passed otherwise.
+#[cfg(target_os = "windows")]
+pub fn webview2_args(proxy: Option<&wry::ProxyConfig>) -> String {
+    let mut args = format!("{} --ignore-certificate-errors", WEBVIEW2_DEFAULT_ARGS);
+    if let Some(proxy) = proxy {
+        args.push(' ');
+        args.push_str(&crate::proxy_config::webview2_arg(proxy));
+    }
+    args
+}
+
+/// Warns, once per webframe, that certificates go unchecked, or that the
+/// platform cannot skip the checks.
+pub fn warn_insecure() {
+    if cfg!(any(
+        target_os = "windows",
+        target_os = "linux",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd"
+    )) {
+        log::warn!(
+            "accept_invalid_certs=True: TLS certificates are NOT verified, so any server can impersonate \
+             an https site. Only use this for local development, never in a shipped app"
+        );
+    } else {
+        log::warn!("accept_invalid_certs is not supported on this platform, certificate errors still fail");
+    }
+}
+
+/// Makes `webview` accept invalid certificates where the setting lives on
+/// the built WebView: WebKitGTK's website data manager, shared by the
+/// WebViews of a context. WebView2 gets it from its arguments instead.
+#[cfg(any(
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+pub fn accept_invalid(webview: &wry::WebView) {
+    use webkit2gtk::{TLSErrorsPolicy, WebViewExt, WebsiteDataManagerExt};
+    use wry::WebViewExtUnix;
+    match webview.webview().website_data_manager() {
+        Some(manager) => manager.set_tls_errors_policy(TLSErrorsPolicy::Ignore),
+        None => log::warn!("WebView has no website data manager, certificate errors still fail"),
+    }
+}
+
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd"
+)))]
+pub fn accept_invalid(_webview: &wry::WebView) {}
@@ -0,0 +1,99 @@
+use pyo3::prelude::*;
+use tokio::sync::mpsc;
+
+use crate::stats;
+
+/// Sending half of the Python → Rust message channel, bounded or not.
+pub enum MessageSender<T> {
+    Unbounded(mpsc::UnboundedSender<T>),
+    Bounded(mpsc::Sender<T>),
+}
+
+impl<T> Clone for MessageSender<T> {
+    fn clone(&self) -> Self {
+        match self {
+            MessageSender::Unbounded(tx) => MessageSender::Unbounded(tx.clone()),
+            MessageSender::Bounded(tx) => MessageSender::Bounded(tx.clone()),
+        }
+    }
+}
+
+/// Receiving half matching [`MessageSender`].
+pub enum MessageReceiver<T> {
+    Unbounded(mpsc::UnboundedReceiver<T>),
+    Bounded(mpsc::Receiver<T>),
+}
+
+/// Creates the message channel: unbounded for `None`, otherwise holding at
+/// most `capacity` messages the consumer has not taken yet.
+///
+/// # Errors
+/// Returns `PyValueError` for a capacity of zero.
+pub fn message_channel<T>(capacity: Option<usize>) -> PyResult<(MessageSender<T>, MessageReceiver<T>)> {
+    match capacity {
+        None => {
+            let (tx, rx) = mpsc::unbounded_channel();
+            Ok((MessageSender::Unbounded(tx), MessageReceiver::Unbounded(rx)))
+        }
+        Some(0) => Err(pyo3::exceptions::PyValueError::new_err(
+            "message_capacity must be at least 1",
+        )),
+        Some(capacity) => {
+            let (tx, rx) = mpsc::channel(capacity);
+            Ok((MessageSender::Bounded(tx), MessageReceiver::Bounded(rx)))
+        }
+    }
+}
+
+impl<T> MessageSender<T> {
+    /// Sends without waiting.
+    ///
+    /// # Errors
+    /// Returns `ChannelError` if a bounded channel is full or the
+    /// consumer is gone.
+    pub fn try_send(&self, message: T) -> PyResult<()> {
+        let sent = match self {
+            MessageSender::Unbounded(tx) => tx.send(message).map_err(|e| {
+                crate::errors::ChannelError::new_err(format!("Failed to send message: {}", e))
+            }),
+            MessageSender::Bounded(tx) => tx.try_send(message).map_err(|e| match e {
+                mpsc::error::TrySendError::Full(_) => {
+                    crate::errors::ChannelError::new_err("Message channel is full")
+                }
+                mpsc::error::TrySendError::Closed(_) => {
+                    crate::errors::ChannelError::new_err("Failed to send message: channel closed")
+                }
+            }),
+        };
+        stats::EMITS.count(sent)
+    }
+
+    /// Sends, waiting for room in a bounded channel.
+    ///
+    /// # Errors
+    /// Returns `ChannelError` if the consumer is gone.
+    pub async fn send(&self, message: T) -> PyResult<()> {
+        let sent = match self {
+            MessageSender::Unbounded(tx) => tx.send(message).map_err(|e| {
+                crate::errors::ChannelError::new_err(format!("Failed to send message: {}", e))
+            }),
+            MessageSender::Bounded(tx) => tx.send(message).await.map_err(|e| {
+                crate::errors::ChannelError::new_err(format!("Failed to send message: {}", e))
+            }),
+        };
+        stats::EMITS.count(sent)
+    }
+}
+
+impl<T> MessageReceiver<T> {
+    pub async fn recv(&mut self) -> Option<T> {
+        let message = match self {
+            MessageReceiver::Unbounded(rx) => rx.recv().await,
+            MessageReceiver::Bounded(rx) => rx.recv().await,
+        };
+        if message.is_some() {
+            stats::EMITS.received();
+        }
+        message
+    }
+}
@@ -0,0 +1,52 @@
+use once_cell::sync::Lazy;
+use pyo3::prelude::*;
+use std::sync::{Mutex, PoisonError};
+
+/// The system clipboard, opened on first use. Kept open because on X11
+/// and Wayland the text set by this process only stays available while
+/// its clipboard handle is alive.
+static CLIPBOARD: Lazy<Mutex<Option<arboard::Clipboard>>> = Lazy::new(|| Mutex::new(None));
+
+fn with_clipboard<T>(f: impl FnOnce(&mut arboard::Clipboard) -> PyResult<T>) -> PyResult<T> {
+    let mut clipboard = CLIPBOARD.lock().unwrap_or_else(PoisonError::into_inner);
+    if clipboard.is_none() {
+        *clipboard = Some(arboard::Clipboard::new().map_err(|e| {
+            pyo3::exceptions::PyRuntimeError::new_err(format!("Clipboard unavailable: {}", e))
+        })?);
+    }
+    f(clipboard.as_mut().unwrap())
+}
+
+/// Replaces the clipboard contents with `text`.
+///
+/// # Errors
+/// Raises `PyRuntimeError` if the clipboard cannot be opened or written.
+#[pyfunction]
+pub fn clipboard_set(py: Python<'_>, text: String) -> PyResult<()> {
+    py.allow_threads(|| {
+        with_clipboard(|clipboard| {
+            clipboard.set_text(text).map_err(|e| {
+                pyo3::exceptions::PyRuntimeError::new_err(format!("Failed to set clipboard: {}", e))
+            })
+        })
+    })
+}
+
+/// Returns the text on the clipboard, or `None` if it is empty or holds
+/// something else, such as an image.
+///
+/// # Errors
+/// Raises `PyRuntimeError` if the clipboard cannot be opened or read.
+#[pyfunction]
+pub fn clipboard_get(py: Python<'_>) -> PyResult<Option<String>> {
+    py.allow_threads(|| {
+        with_clipboard(|clipboard| match clipboard.get_text() {
+            Ok(text) => Ok(Some(text)),
+            Err(arboard::Error::ContentNotAvailable) => Ok(None),
+            Err(e) => Err(pyo3::exceptions::PyRuntimeError::new_err(format!(
+                "Failed to read clipboard: {}",
+                e
+            ))),
+        })
+    })
+}
@@ -0,0 +1,114 @@
+use base64::Engine;
+use pyo3::{prelude::*, types::PyBytes, types::PyString};
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Wire format of IPC payloads between the WebView, Rust and Python.
+///
+/// JSON is the default. With MessagePack, `window.invoke` encodes its
+/// message as base64 MessagePack (the only way to pass binary data through
+/// `postMessage`), the Python handler receives the request as MessagePack
+/// `bytes` whose `body` is the raw encoded message, and `emit_*` accept
+/// MessagePack `bytes` in addition to JSON strings.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum IpcFormat {
+    #[default]
+    Json,
+    MessagePack,
+}
+
+impl IpcFormat {
+    /// Parses a format name as passed from Python (`"json"` or `"msgpack"`).
+    ///
+    /// # Errors
+    /// Returns `PyValueError` for unknown format names.
+    pub fn parse(name: &str) -> PyResult<Self> {
+        match name {
+            "json" => Ok(IpcFormat::Json),
+            "msgpack" => Ok(IpcFormat::MessagePack),
+            other => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Unknown IPC format: {:?} (expected \"json\" or \"msgpack\")",
+                other
+            ))),
+        }
+    }
+
+    /// Name of the format as seen by the JS side.
+    pub fn name(self) -> &'static str {
+        match self {
+            IpcFormat::Json => "json",
+            IpcFormat::MessagePack => "msgpack",
+        }
+    }
+
+    /// Initialization script telling `assets/py.js` which format to encode with.
+    pub fn init_script(self) -> String {
+        format!("window.__PYFRAME_IPC_FORMAT__ = {};", crate::js::string_literal(self.name()))
+    }
+
+    /// Serializes `value` into the Python object handed to the handler:
+    /// a JSON `str` or MessagePack `bytes`.
+    pub fn to_python<T: Serialize>(self, py: Python<'_>, value: &T) -> PyResult<Py<PyAny>> {
+        match self {
+            IpcFormat::Json => {
+                let json = serde_json::to_string_pretty(value)
+                    .map_err(|e| crate::errors::IpcError::new_err(e.to_string()))?;
+                Ok(PyString::new(py, &json).into_any().unbind())
+            }
+            IpcFormat::MessagePack => {
+                let bytes = rmp_serde::to_vec_named(value)
+                    .map_err(|e| crate::errors::IpcError::new_err(e.to_string()))?;
+                Ok(PyBytes::new(py, &bytes).into_any().unbind())
+            }
+        }
+    }
+}
+
+/// Prefix marking a `postMessage` body as base64 binary data posted by
+/// `window.ipcPostBinary` (honored with `binary_ipc=True`).
+pub const BINARY_PREFIX: &str = "pyframe-binary:";
+
+/// Prefix of `postMessage` bodies carrying a key event for `on_key`, as
+/// JSON (see [`crate::events::KeyEventPayload`]).
+pub const KEY_PREFIX: &str = "pyframe-key:";
+
+/// Prefix of `postMessage` bodies that are commands for Rust itself rather
+/// than requests for the handler, e.g. `pyframe-command:start_dragging`.
+pub const COMMAND_PREFIX: &str = "pyframe-command:";
+
+/// Encodes binary data that has to travel as text, e.g. inside JSON.
+pub fn encode_base64(bytes: &[u8]) -> String {
+    base64::engine::general_purpose::STANDARD.encode(bytes)
+}
+
+/// Decodes a base64 `postMessage` body sent in MessagePack mode.
+///
+/// # Errors
+/// Returns `IpcError` if `body` is not valid base64.
+pub fn decode_base64(body: &str) -> PyResult<Vec<u8>> {
+    base64::engine::general_purpose::STANDARD
+        .decode(body.trim())
+        .map_err(|e| crate::errors::IpcError::new_err(format!("Invalid base64 IPC body: {}", e)))
+}
+
+/// A message passed to `emit_*`: JSON text, or MessagePack bytes.
+#[derive(FromPyObject)]
+pub enum EncodedMessage {
+    Json(String),
+    MessagePack(Vec<u8>),
+}
+
+impl EncodedMessage {
+    /// Deserializes the message.
+    ///
+    /// # Errors
+    /// Returns `PyValueError` if the payload does not match `T`.
+    pub fn decode<T: DeserializeOwned>(&self) -> PyResult<T> {
+        match self {
+            EncodedMessage::Json(json) => serde_json::from_str(json)
+                .map_err(|e| pyo3::exceptions::PyValueError::new_err(format!("Invalid JSON: {}", e))),
+            EncodedMessage::MessagePack(bytes) => rmp_serde::from_slice(bytes).map_err(|e| {
+                pyo3::exceptions::PyValueError::new_err(format!("Invalid MessagePack: {}", e))
+            }),
+        }
+    }
+}
@@ -0,0 +1,148 @@
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use wry::cookie::{Cookie, SameSite};
+
+use crate::errors;
+
+/// A cookie to store with `set_cookie`.
+#[derive(Debug)]
+pub struct CookieSpec {
+    pub name: String,
+    pub value: String,
+    pub domain: String,
+    pub path: String,
+    /// Expiry as seconds since the Unix epoch; a session cookie without.
+    pub expires: Option<f64>,
+    pub secure: bool,
+    pub http_only: bool,
+    pub same_site: Option<SameSite>,
+}
+
+/// Parses a `same_site` argument: `"strict"`, `"lax"` or `"none"`.
+///
+/// # Errors
+/// Returns `PyValueError` for other values.
+pub fn parse_same_site(name: &str) -> PyResult<SameSite> {
+    match name.to_ascii_lowercase().as_str() {
+        "strict" => Ok(SameSite::Strict),
+        "lax" => Ok(SameSite::Lax),
+        "none" => Ok(SameSite::None),
+        _ => Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "Unknown same_site {:?} (expected \"strict\", \"lax\" or \"none\")",
+            name
+        ))),
+    }
+}
+
+/// A cookie operation, run in the event loop against one WebView.
+#[derive(Debug)]
+pub enum CookieOp {
+    /// Read the cookies sent to this URL, or all of them.
+    Get(Option<String>),
+    Set(CookieSpec),
+    Clear,
+}
+
+impl CookieOp {
+    /// Runs the operation; only [`CookieOp::Get`] returns cookies.
+    ///
+    /// # Errors
+    /// Returns `WebViewError` if the WebView fails the operation and
+    /// `PyNotImplementedError` for setting and clearing cookies outside
+    /// Windows.
+    pub fn run(self, webview: &wry::WebView) -> PyResult<Vec<Cookie<'static>>> {
+        match self {
+            CookieOp::Get(url) => match url {
+                Some(url) => webview.cookies_for_url(&url),
+                None => webview.cookies(),
+            }
+            .map_err(|e| errors::WebViewError::new_err(format!("Failed to read cookies: {}", e))),
+            CookieOp::Set(spec) => set(webview, spec).map(|()| Vec::new()),
+            CookieOp::Clear => clear(webview).map(|()| Vec::new()),
+        }
+    }
+}
+
+/// Converts `cookie` into the dict returned by `get_cookies`.
+pub fn to_dict<'py>(py: Python<'py>, cookie: &Cookie<'_>) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new(py);
+    dict.set_item("name", cookie.name())?;
+    dict.set_item("value", cookie.value())?;
+    dict.set_item("domain", cookie.domain())?;
+    dict.set_item("path", cookie.path())?;
+    dict.set_item("expires", cookie.expires_datetime().map(|expires| expires.unix_timestamp()))?;
+    dict.set_item("secure", cookie.secure().unwrap_or(false))?;
+    dict.set_item("http_only", cookie.http_only().unwrap_or(false))?;
+    let same_site = cookie.same_site().map(|same_site| match same_site {
+        SameSite::Strict => "strict",
+        SameSite::Lax => "lax",
+        SameSite::None => "none",
+    });
+    dict.set_item("same_site", same_site)?;
+    Ok(dict)
+}
+
+#[cfg(target_os = "windows")]
+fn cookie_manager(
+    webview: &wry::WebView,
+) -> windows::core::Result<webview2_com::Microsoft::Web::WebView2::Win32::ICoreWebView2CookieManager> {
+    use webview2_com::Microsoft::Web::WebView2::Win32::ICoreWebView2_2;
+    use windows::core::Interface;
+    use wry::WebViewExtWindows;
+    // SAFETY: WebView2 is called on the thread owning the WebView.
+    unsafe { webview.controller().CoreWebView2()?.cast::<ICoreWebView2_2>()?.CookieManager() }
+}
+
+#[cfg(target_os = "windows")]
+fn set(webview: &wry::WebView, spec: CookieSpec) -> PyResult<()> {
+    use webview2_com::Microsoft::Web::WebView2::Win32::{
+        COREWEBVIEW2_COOKIE_SAME_SITE_KIND_LAX, COREWEBVIEW2_COOKIE_SAME_SITE_KIND_NONE,
+        COREWEBVIEW2_COOKIE_SAME_SITE_KIND_STRICT,
+    };
+    use windows::core::HSTRING;
+    // SAFETY: WebView2 is called on the thread owning the WebView.
+    let stored = cookie_manager(webview).and_then(|manager| unsafe {
+        let cookie = manager.CreateCookie(
+            &HSTRING::from(spec.name.as_str()),
+            &HSTRING::from(spec.value.as_str()),
+            &HSTRING::from(spec.domain.as_str()),
+            &HSTRING::from(spec.path.as_str()),
+        )?;
+        if let Some(expires) = spec.expires {
+            cookie.SetExpires(expires)?;
+        }
+        cookie.SetIsSecure(spec.secure)?;
+        cookie.SetIsHttpOnly(spec.http_only)?;
+        if let Some(same_site) = spec.same_site {
+            cookie.SetSameSite(match same_site {
+                SameSite::Strict => COREWEBVIEW2_COOKIE_SAME_SITE_KIND_STRICT,
+                SameSite::Lax => COREWEBVIEW2_COOKIE_SAME_SITE_KIND_LAX,
+                SameSite::None => COREWEBVIEW2_COOKIE_SAME_SITE_KIND_NONE,
+            })?;
+        }
+        manager.AddOrUpdateCookie(&cookie)
+    });
+    stored.map_err(|e| errors::WebViewError::new_err(format!("Failed to set cookie {:?}: {}", spec.name, e)))
+}
+
+#[cfg(target_os = "windows")]
+fn clear(webview: &wry::WebView) -> PyResult<()> {
+    // SAFETY: WebView2 is called on the thread owning the WebView.
+    cookie_manager(webview)
+        .and_then(|manager| unsafe { manager.DeleteAllCookies() })
+        .map_err(|e| errors::WebViewError::new_err(format!("Failed to clear cookies: {}", e)))
+}
+
+#[cfg(not(target_os = "windows"))]
+fn set(_webview: &wry::WebView, _spec: CookieSpec) -> PyResult<()> {
+    Err(pyo3::exceptions::PyNotImplementedError::new_err(
+        "set_cookie is only supported with WebView2 on Windows",
+    ))
+}
+
+#[cfg(not(target_os = "windows"))]
+fn clear(_webview: &wry::WebView) -> PyResult<()> {
+    Err(pyo3::exceptions::PyNotImplementedError::new_err(
+        "clear_cookies is only supported with WebView2 on Windows",
+    ))
+}
@@ -0,0 +1,243 @@
+use pyo3::prelude::*;
+use std::path::PathBuf;
+use tokio::sync::oneshot;
+
+use crate::{errors, send_runtime_message, windows, RuntimeMessage};
+
+/// A file filter as passed from Python: a label and the extensions it
+/// matches, e.g. `("Images", ["png", "jpg"])`.
+type Filter = (String, Vec<String>);
+
+/// A native file dialog, shown by the event loop as a modal of a window.
+#[derive(Debug)]
+pub struct FileDialog {
+    kind: FileDialogKind,
+    filters: Vec<Filter>,
+}
+
+#[derive(Debug)]
+enum FileDialogKind {
+    Open { multiple: bool },
+    Save { default_name: Option<String> },
+}
+
+impl FileDialog {
+    /// Shows the dialog over `parent` and blocks until it is closed,
+    /// returning the chosen paths, or `None` if it was cancelled.
+    pub fn show(self, parent: &tao::window::Window) -> Option<Vec<PathBuf>> {
+        let mut dialog = rfd::FileDialog::new().set_parent(parent);
+        for (label, extensions) in &self.filters {
+            dialog = dialog.add_filter(label, extensions);
+        }
+        match self.kind {
+            FileDialogKind::Open { multiple: false } => dialog.pick_file().map(|path| vec![path]),
+            FileDialogKind::Open { multiple: true } => dialog.pick_files(),
+            FileDialogKind::Save { default_name } => match default_name {
+                Some(name) => dialog.set_file_name(name),
+                None => dialog,
+            }
+            .save_file()
+            .map(|path| vec![path]),
+        }
+    }
+}
+
+/// A native message box, shown by the event loop.
+#[derive(Debug)]
+pub struct MessageDialog {
+    title: String,
+    body: String,
+    level: rfd::MessageLevel,
+    /// Offer OK and Cancel rather than just OK.
+    confirm: bool,
+}
+
+impl MessageDialog {
+    /// Shows the message box, as a modal of `parent` if given, and blocks
+    /// until it is closed, returning whether OK was chosen.
+    pub fn show(self, parent: Option<&tao::window::Window>) -> bool {
+        let dialog = rfd::MessageDialog::new()
+            .set_title(self.title)
+            .set_description(self.body)
+            .set_level(self.level)
+            .set_buttons(if self.confirm { rfd::MessageButtons::OkCancel } else { rfd::MessageButtons::Ok });
+        let dialog = match parent {
+            Some(parent) => dialog.set_parent(parent),
+            None => dialog,
+        };
+        dialog.show() == rfd::MessageDialogResult::Ok
+    }
+}
+
+/// Parses a `message_box` kind: `"info"`, `"warning"` or `"error"`.
+///
+/// # Errors
+/// Returns `PyValueError` for other kinds.
+fn parse_level(kind: &str) -> PyResult<rfd::MessageLevel> {
+    match kind {
+        "info" => Ok(rfd::MessageLevel::Info),
+        "warning" => Ok(rfd::MessageLevel::Warning),
+        "error" => Ok(rfd::MessageLevel::Error),
+        _ => Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "Unknown message box kind {:?} (expected \"info\", \"warning\" or \"error\")",
+            kind
+        ))),
+    }
+}
+
+/// Checks `filters` and strips leading dots from their extensions, so
+/// `".png"` and `"png"` both work.
+///
+/// # Errors
+/// Returns `PyValueError` for filters without extensions.
+fn parse_filters(filters: Option<Vec<Filter>>) -> PyResult<Vec<Filter>> {
+    filters
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(label, extensions)| {
+            if extensions.is_empty() {
+                return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                    "File filter {:?} has no extensions",
+                    label
+                )));
+            }
+            let extensions = extensions.iter().map(|ext| ext.trim_start_matches('.').to_string()).collect();
+            Ok((label, extensions))
+        })
+        .collect()
+}
+
+/// Sends `dialog` to the event loop, returning the receiver of its paths.
+fn show(dialog: FileDialog, window_id: Option<u64>) -> PyResult<(u64, oneshot::Receiver<Option<Vec<PathBuf>>>)> {
+    let id = window_id.unwrap_or(windows::MAIN_WINDOW_ID);
+    windows::check_id(id)?;
+    let (tx, reply) = oneshot::channel();
+    send_runtime_message(RuntimeMessage::FileDialog { id, dialog, reply: tx })?;
+    Ok((id, reply))
+}
+
+/// Opens the native file picker as a modal of the window `window_id` (the
+/// main window by default). `filters` restrict the selectable files, as
+/// `(label, extensions)` tuples such as `("Images", ["png", "jpg"])`; with
+/// `multiple`, several files can be selected.
+///
+/// Returns an awaitable resolving to the selected paths as `str`, or an
+/// empty list if the dialog was cancelled.
+///
+/// # Errors
+/// - Raises `PyValueError` for an unknown window id and filters without
+///   extensions.
+/// - Raises `FrameError` if no webframe is running.
+/// - Raises `WindowError` if the window closed before the dialog opened.
+#[pyfunction]
+#[pyo3(signature = (filters=None, multiple=false, *, window_id=None))]
+pub fn open_file_dialog(
+    py: Python<'_>,
+    filters: Option<Vec<Filter>>,
+    multiple: bool,
+    window_id: Option<u64>,
+) -> PyResult<Bound<'_, PyAny>> {
+    let dialog = FileDialog { kind: FileDialogKind::Open { multiple }, filters: parse_filters(filters)? };
+    let (id, reply) = show(dialog, window_id)?;
+
+    pyo3_async_runtimes::tokio::future_into_py(py, async move {
+        let paths = reply
+            .await
+            .map_err(|_| errors::WindowError::new_err(format!("Window {} is closed", id)))?;
+        Ok(paths.unwrap_or_default().into_iter().map(PathBuf::into_os_string).collect::<Vec<_>>())
+    })
+}
+
+/// Opens the native save dialog as a modal of the window `window_id` (the
+/// main window by default), suggesting `default_name`. `filters` work as
+/// in [`open_file_dialog`]. The dialog asks before overwriting an existing
+/// file, but does not create the file.
+///
+/// Returns an awaitable resolving to the chosen path, or `None` if the
+/// dialog was cancelled.
+///
+/// # Errors
+/// - Raises `PyValueError` for an unknown window id and filters without
+///   extensions.
+/// - Raises `FrameError` if no webframe is running.
+/// - Raises `WindowError` if the window closed before the dialog opened.
+#[pyfunction]
+#[pyo3(signature = (filters=None, default_name=None, *, window_id=None))]
+pub fn save_file_dialog(
+    py: Python<'_>,
+    filters: Option<Vec<Filter>>,
+    default_name: Option<String>,
+    window_id: Option<u64>,
+) -> PyResult<Bound<'_, PyAny>> {
+    let dialog = FileDialog { kind: FileDialogKind::Save { default_name }, filters: parse_filters(filters)? };
+    let (id, reply) = show(dialog, window_id)?;
+
+    pyo3_async_runtimes::tokio::future_into_py(py, async move {
+        let paths = reply
+            .await
+            .map_err(|_| errors::WindowError::new_err(format!("Window {} is closed", id)))?;
+        Ok(paths.and_then(|paths| paths.into_iter().next()).map(PathBuf::into_os_string))
+    })
+}
+
+/// Sends `dialog` to the event loop, returning the receiver of its result.
+fn show_message(dialog: MessageDialog, window_id: Option<u64>) -> PyResult<oneshot::Receiver<bool>> {
+    let id = window_id.unwrap_or(windows::MAIN_WINDOW_ID);
+    windows::check_id(id)?;
+    let (tx, reply) = oneshot::channel();
+    send_runtime_message(RuntimeMessage::MessageDialog { id, dialog, reply: tx })?;
+    Ok(reply)
+}
+
+/// Shows a native message box with an OK button, as a modal of the window
+/// `window_id` (the main window by default). `kind` selects the icon:
+/// `"info"` (default), `"warning"` or `"error"`. Being native, it shows
+/// even when the page is unresponsive, and without a parent once the
+/// window is closed.
+///
+/// Returns an awaitable resolving to `None` once the box is dismissed.
+///
+/// # Errors
+/// - Raises `PyValueError` for an unknown window id and `kind`.
+/// - Raises `FrameError` if no webframe is running.
+#[pyfunction]
+#[pyo3(signature = (title, body, kind="info", *, window_id=None))]
+pub fn message_box<'py>(
+    py: Python<'py>,
+    title: String,
+    body: String,
+    kind: &str,
+    window_id: Option<u64>,
+) -> PyResult<Bound<'py, PyAny>> {
+    let dialog = MessageDialog { title, body, level: parse_level(kind)?, confirm: false };
+    let reply = show_message(dialog, window_id)?;
+
+    pyo3_async_runtimes::tokio::future_into_py(py, async move {
+        reply
+            .await
+            .map_err(|_| errors::FrameError::new_err("Event loop exited before the message box closed"))?;
+        Ok(())
+    })
+}
+
+/// Asks a question in a native box with OK and Cancel buttons, like
+/// [`message_box`] with the `"warning"` icon.
+///
+/// Returns an awaitable resolving to `True` for OK, and `False` for Cancel
+/// or closing the box.
+///
+/// # Errors
+/// - Raises `PyValueError` for an unknown window id.
+/// - Raises `FrameError` if no webframe is running.
+#[pyfunction]
+#[pyo3(signature = (title, body, *, window_id=None))]
+pub fn confirm(py: Python<'_>, title: String, body: String, window_id: Option<u64>) -> PyResult<Bound<'_, PyAny>> {
+    let dialog = MessageDialog { title, body, level: rfd::MessageLevel::Warning, confirm: true };
+    let reply = show_message(dialog, window_id)?;
+
+    pyo3_async_runtimes::tokio::future_into_py(py, async move {
+        reply
+            .await
+            .map_err(|_| errors::FrameError::new_err("Event loop exited before the confirmation closed"))
+    })
+}
@@ -0,0 +1,47 @@
+use pyo3::create_exception;
+use pyo3::exceptions::PyRuntimeError;
+use pyo3::prelude::*;
+
+// Derived from `RuntimeError`, which these failures raised before, so
+// existing `except RuntimeError` handlers keep working.
+create_exception!(
+    frame,
+    FrameError,
+    PyRuntimeError,
+    "Base class of the errors raised by frame, e.g. when no webframe is running."
+);
+create_exception!(
+    frame,
+    WindowError,
+    FrameError,
+    "A native window could not be created, or is closed or not on screen."
+);
+create_exception!(
+    frame,
+    WebViewError,
+    FrameError,
+    "A WebView could not be created, or failed to evaluate a script or print."
+);
+create_exception!(
+    frame,
+    IpcError,
+    FrameError,
+    "An IPC request could not be decoded, encoded for the handler, or awaited."
+);
+create_exception!(
+    frame,
+    ChannelError,
+    FrameError,
+    "A message channel between Python and Rust is full, closed or not set up."
+);
+
+/// Adds the exception classes to the `frame` module.
+pub fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    let py = m.py();
+    m.add("FrameError", py.get_type::<FrameError>())?;
+    m.add("WindowError", py.get_type::<WindowError>())?;
+    m.add("WebViewError", py.get_type::<WebViewError>())?;
+    m.add("IpcError", py.get_type::<IpcError>())?;
+    m.add("ChannelError", py.get_type::<ChannelError>())?;
+    Ok(())
+}
@@ -0,0 +1,219 @@
+use pyo3::{prelude::*, types::PyTuple};
+use serde::{Deserialize, Serialize};
+
+/// Calls `callback` with `args`, logging (rather than propagating) any
+/// exception it raises, since there is no Python caller to hand it to
+/// inside the event loop.
+pub fn notify<A>(callback: &Py<PyAny>, args: A)
+where
+    A: for<'py> IntoPyObject<'py, Target = PyTuple>,
+{
+    Python::with_gil(|py| {
+        if let Err(error) = callback.call1(py, args) {
+            log::error!("Callback error: {:?}", error);
+        }
+    });
+}
+
+/// Calls `on_error` with the formatted traceback of `error`, logging
+/// failures of the callback itself.
+pub fn report_error(py: Python<'_>, on_error: &Py<PyAny>, error: &PyErr) {
+    let traceback = format_traceback(py, error).unwrap_or_else(|_| error.to_string());
+    if let Err(error) = on_error.call1(py, (traceback,)) {
+        log::error!("on_error error: {:?}", error);
+    }
+}
+
+/// Formats `error` like Python prints uncaught exceptions.
+pub fn format_traceback(py: Python<'_>, error: &PyErr) -> PyResult<String> {
+    let lines = py.import("traceback")?.call_method1(
+        "format_exception",
+        (error.get_type(py), error.value(py), error.traceback(py)),
+    )?;
+    "".into_pyobject(py)?.call_method1("join", (lines,))?.extract()
+}
+
+/// Creates a handler for [`wry::WebViewBuilder::with_drag_drop_handler`].
+///
+/// Dropped files are passed to `on_file_drop(paths)` in one call per drop.
+/// `on_file_hover(paths)` is called when files are dragged over the
+/// WebView, and with `None` once the drag leaves it or is cancelled. While
+/// `on_file_drop` is set, the WebView does not get to handle drops itself
+/// (e.g. by navigating to the file).
+pub fn handle_drag_drop(
+    on_file_drop: Option<Py<PyAny>>,
+    on_file_hover: Option<Py<PyAny>>,
+) -> impl Fn(wry::DragDropEvent) -> bool + 'static {
+    fn to_strings(paths: Vec<std::path::PathBuf>) -> Vec<String> {
+        paths.into_iter().map(|path| path.to_string_lossy().into_owned()).collect()
+    }
+    move |event| {
+        match event {
+            wry::DragDropEvent::Enter { paths, .. } => {
+                if let Some(on_file_hover) = &on_file_hover {
+                    notify(on_file_hover, (Some(to_strings(paths)),));
+                }
+            }
+            wry::DragDropEvent::Drop { paths, .. } => {
+                if let Some(on_file_hover) = &on_file_hover {
+                    notify(on_file_hover, (None::<Vec<String>>,));
+                }
+                if let Some(on_file_drop) = &on_file_drop {
+                    notify(on_file_drop, (to_strings(paths),));
+                }
+            }
+            wry::DragDropEvent::Leave => {
+                if let Some(on_file_hover) = &on_file_hover {
+                    notify(on_file_hover, (None::<Vec<String>>,));
+                }
+            }
+            _ => {}
+        }
+        on_file_drop.is_some()
+    }
+}
+
+/// Asks `on_close` whether the main window may close.
+///
+/// Only an explicit `False` keeps the window open; any other return value,
+/// and an exception (which is logged), lets it close.
+pub fn allow_close(on_close: &Py<PyAny>) -> bool {
+    Python::with_gil(|py| match on_close.call0(py) {
+        Ok(res) => !matches!(res.extract::<Option<bool>>(py), Ok(Some(false))),
+        Err(error) => {
+            log::error!("on_close error: {:?}", error);
+            true
+        }
+    })
+}
+
+/// Reports changes of the window's visibility to `on_occlusion_change`.
+///
+/// Tao has no occlusion events, so visibility is derived from the window
+/// being shown and not minimized. A window fully covered by other windows
+/// therefore still counts as visible on every platform.
+pub struct VisibilityTracker {
+    callback: Py<PyAny>,
+    visible: bool,
+}
+
+impl VisibilityTracker {
+    pub fn new(callback: Py<PyAny>) -> Self {
+        VisibilityTracker {
+            callback,
+            visible: true,
+        }
+    }
+
+    /// Re-reads the window state and fires the callback if it changed.
+    pub fn update(&mut self, window: &tao::window::Window) {
+        let visible = window.is_visible() && !window.is_minimized();
+        if visible != self.visible {
+            self.visible = visible;
+            notify(&self.callback, (visible,));
+        }
+    }
+}
+
+/// A window event reported to `on_window_event` as JSON, e.g.
+/// `{"type": "resized", "width": 800.0, "height": 600.0}`.
+///
+/// Sizes and positions are logical pixels, like `window_config`.
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum WindowEventPayload {
+    /// The inner size changed.
+    Resized { width: f64, height: f64 },
+    /// The outer position changed.
+    Moved { x: f64, y: f64 },
+    /// The window is about to close.
+    CloseRequested,
+    /// The window entered or left fullscreen through [`set_fullscreen`].
+    ///
+    /// [`set_fullscreen`]: crate::set_fullscreen
+    FullscreenChanged { fullscreen: bool },
+    /// The window moved to a monitor with another scale factor; the
+    /// inner size is the new one.
+    ScaleFactorChanged { scale_factor: f64, width: f64, height: f64 },
+    /// The window's theme changed, `"light"` or `"dark"`, e.g. because the
+    /// OS appearance changed while following it.
+    ThemeChanged { theme: &'static str },
+    /// The window gained or lost keyboard focus.
+    Focused { focused: bool },
+}
+
+/// A key event posted by [`crate::assets::KEYS_SCRIPT`], passed to
+/// `on_key` as JSON, e.g. `{"type": "keydown", "key": "k", "code": "KeyK",
+/// "modifiers": {"alt": false, "ctrl": true, "meta": false, "shift": false},
+/// "repeat": false}`.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct KeyEventPayload {
+    #[serde(rename = "type")]
+    kind: KeyEventKind,
+    /// The produced character or key name, e.g. `"K"` or `"Enter"`.
+    key: String,
+    /// The physical key regardless of layout, e.g. `"KeyK"`.
+    code: String,
+    modifiers: Modifiers,
+    /// Whether the key is held down and auto-repeating.
+    repeat: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum KeyEventKind {
+    Keydown,
+    Keyup,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct Modifiers {
+    alt: bool,
+    ctrl: bool,
+    meta: bool,
+    shift: bool,
+}
+
+/// Calls `on_key` with the key event posted as `event`, returning the
+/// script telling the page whether Python handled it (returned `True`).
+/// Malformed events are logged and ignored.
+pub fn handle_key(on_key: &Py<PyAny>, event: &str) -> Option<String> {
+    let event: KeyEventPayload = match serde_json::from_str(event) {
+        Ok(event) => event,
+        Err(e) => {
+            log::warn!("Invalid key event {:?}: {}", event, e);
+            return None;
+        }
+    };
+    let json = serde_json::to_string(&event).unwrap();
+    let handled = Python::with_gil(|py| match on_key.call1(py, (json.as_str(),)) {
+        Ok(res) => matches!(res.extract::<Option<bool>>(py), Ok(Some(true))),
+        Err(error) => {
+            log::error!("on_key error: {:?}", error);
+            false
+        }
+    });
+    Some(format!("window.__keyHandled({}, {})", json, handled))
+}
+
+impl WindowEventPayload {
+    pub fn resized(window: &tao::window::Window, size: tao::dpi::PhysicalSize<u32>) -> Self {
+        let size = size.to_logical::<f64>(window.scale_factor());
+        WindowEventPayload::Resized { width: size.width, height: size.height }
+    }
+
+    pub fn moved(window: &tao::window::Window, position: tao::dpi::PhysicalPosition<i32>) -> Self {
+        let position = position.to_logical::<f64>(window.scale_factor());
+        WindowEventPayload::Moved { x: position.x, y: position.y }
+    }
+
+    pub fn scale_factor_changed(scale_factor: f64, size: tao::dpi::PhysicalSize<u32>) -> Self {
+        let size = size.to_logical::<f64>(scale_factor);
+        WindowEventPayload::ScaleFactorChanged { scale_factor, width: size.width, height: size.height }
+    }
+
+    /// Calls `callback` with the event serialized as JSON.
+    pub fn send(&self, callback: &Py<PyAny>) {
+        notify(callback, (serde_json::to_string(self).unwrap(),));
+    }
+}
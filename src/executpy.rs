@@ -1,4 +1,8 @@
-use pyo3::{types::PyAnyMethods, Py, PyAny, PyResult, Python};
+use pyo3::{types::PyAnyMethods, Bound, Py, PyAny, PyResult, Python};
+use pyo3_async_runtimes::TaskLocals;
+use std::future::Future;
+use std::sync::{Arc, Condvar, Mutex, PoisonError};
+use std::time::Duration;
 
 /// Schedule `handler` on the asyncio loop.
 ///
@@ -19,3 +23,119 @@ pub fn _executer(py: Python, handler: Py<PyAny>, args: String) -> PyResult<Py<Py
     let result = loop_obj.call_method1("run_until_complete", (task,))?;
     Ok(result.into())
 }
+
+/// The asyncio loop that IPC handler coroutines are scheduled on.
+///
+/// Either the loop of [`spawn_asyncio_loop`] or, when `create_webframe`
+/// runs a supervised Python thread, that thread's current loop. The slot is
+/// empty while no loop is running, e.g. between a crash and the restart.
+#[derive(Clone, Default)]
+pub struct HandlerLoop {
+    locals: Arc<Mutex<Option<TaskLocals>>>,
+    /// Set by [`HandlerLoop::stop`] when the webframe shuts down, which
+    /// wakes [`HandlerLoop::wait_stopped`].
+    stopped: Arc<(Mutex<bool>, Condvar)>,
+}
+
+impl HandlerLoop {
+    /// Makes `loop_obj` the loop coroutines are scheduled on.
+    pub fn set(&self, py: Python, loop_obj: &Bound<PyAny>) -> PyResult<()> {
+        let locals = TaskLocals::new(loop_obj.clone()).copy_context(py)?;
+        *self.locals.lock().unwrap_or_else(PoisonError::into_inner) = Some(locals);
+        Ok(())
+    }
+
+    /// Forgets the loop once it stopped running.
+    pub fn clear(&self) {
+        *self.locals.lock().unwrap_or_else(PoisonError::into_inner) = None;
+    }
+
+    /// Stops the running loop, if any, for good: the thread running it
+    /// returns, and loops set afterwards must not run (see
+    /// [`HandlerLoop::is_stopped`]).
+    pub fn stop(&self, py: Python) {
+        let mut guard = self.locals.lock().unwrap_or_else(PoisonError::into_inner);
+        let (stopped, woken) = &*self.stopped;
+        *stopped.lock().unwrap_or_else(PoisonError::into_inner) = true;
+        woken.notify_all();
+        let Some(locals) = guard.take() else {
+            return;
+        };
+        let loop_obj = locals.event_loop(py);
+        let stopped = loop_obj
+            .getattr("stop")
+            .and_then(|stop| loop_obj.call_method1("call_soon_threadsafe", (stop,)));
+        if let Err(e) = stopped {
+            log::error!("Failed to stop asyncio loop: {:?}", e);
+        }
+    }
+
+    /// Whether [`HandlerLoop::stop`] was called. Checked by threads after
+    /// setting their loop and before running it, so a stop in between is
+    /// not missed.
+    pub fn is_stopped(&self) -> bool {
+        *self.stopped.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Waits up to `timeout` for [`HandlerLoop::stop`], returning whether
+    /// it was called.
+    pub fn wait_stopped(&self, timeout: Duration) -> bool {
+        let (stopped, woken) = &*self.stopped;
+        let guard = stopped.lock().unwrap_or_else(PoisonError::into_inner);
+        let (guard, _) = woken
+            .wait_timeout_while(guard, timeout, |stopped| !*stopped)
+            .unwrap_or_else(PoisonError::into_inner);
+        *guard
+    }
+
+    /// Schedules `awaitable` on the loop and returns a future resolving
+    /// with its result.
+    ///
+    /// # Errors
+    /// Returns `IpcError` if no loop is running. The awaitable is
+    /// closed so Python does not warn about a coroutine never awaited.
+    pub fn schedule(
+        &self,
+        awaitable: Bound<PyAny>,
+    ) -> PyResult<impl Future<Output = PyResult<Py<PyAny>>> + Send + 'static> {
+        let guard = self.locals.lock().unwrap_or_else(PoisonError::into_inner);
+        match guard.as_ref() {
+            Some(locals) => pyo3_async_runtimes::into_future_with_locals(locals, awaitable),
+            None => {
+                if awaitable.hasattr("close").unwrap_or(false) {
+                    let _ = awaitable.call_method0("close");
+                }
+                Err(crate::errors::IpcError::new_err(
+                    "No asyncio event loop is running to await the IPC handler's coroutine",
+                ))
+            }
+        }
+    }
+}
+
+/// Starts a fresh asyncio event loop on a dedicated thread.
+///
+/// The loop runs `run_forever` until [`HandlerLoop::stop`] and is
+/// installed as `handler_loop` while it runs.
+pub fn spawn_asyncio_loop(py: Python, handler_loop: &HandlerLoop) -> PyResult<std::thread::JoinHandle<()>> {
+    let asyncio = py.import("asyncio")?;
+    let loop_obj = asyncio.call_method0("new_event_loop")?;
+    handler_loop.set(py, &loop_obj)?;
+
+    let loop_obj: Py<PyAny> = loop_obj.unbind();
+    let handler_loop = handler_loop.clone();
+    Ok(std::thread::spawn(move || {
+        Python::with_gil(|py| -> PyResult<()> {
+            let loop_obj = loop_obj.bind(py);
+            py.import("asyncio")?
+                .call_method1("set_event_loop", (loop_obj.clone(),))?;
+            if handler_loop.is_stopped() {
+                return Ok(());
+            }
+            loop_obj.call_method0("run_forever")?;
+            Ok(())
+        })
+        .unwrap_or_else(|e| log::error!("asyncio loop error: {:?}", e));
+        handler_loop.clear();
+    }))
+}
@@ -0,0 +1,160 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::Duration;
+use tao::dpi::{LogicalPosition, PhysicalPosition};
+use tokio::sync::watch;
+
+use crate::window_config::WindowConfig;
+
+/// Time without further moves or resizes before the geometry is written,
+/// so dragging a window does not write the file on every event.
+const SAVE_DELAY: Duration = Duration::from_millis(500);
+
+/// Window size and position in logical pixels, as stored in the state file.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Geometry {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl Geometry {
+    /// Reads the current geometry of `window`; `None` while it is
+    /// minimized, when the platform reports placeholder positions.
+    pub fn of(window: &tao::window::Window) -> Option<Self> {
+        if window.is_minimized() {
+            return None;
+        }
+        let scale = window.scale_factor();
+        let size = window.inner_size().to_logical::<f64>(scale);
+        let position = window.outer_position().ok()?.to_logical::<f64>(scale);
+        Some(Geometry {
+            x: position.x,
+            y: position.y,
+            width: size.width,
+            height: size.height,
+        })
+    }
+
+    /// Overrides the size and position of `config`.
+    pub fn apply(self, config: &mut WindowConfig) {
+        config.x = Some(self.x);
+        config.y = Some(self.y);
+        config.width = self.width;
+        config.height = self.height;
+    }
+}
+
+/// Moves the outer frame of `window` to `position`, in logical pixels,
+/// warning if no monitor would show any of it there.
+pub fn move_to(window: &tao::window::Window, position: LogicalPosition<f64>) {
+    let origin = position.to_physical::<f64>(window.scale_factor());
+    let size = window.outer_size();
+    let on_screen = window.available_monitors().any(|monitor| {
+        let corner = monitor.position();
+        let extent = monitor.size();
+        origin.x < corner.x as f64 + extent.width as f64
+            && origin.x + size.width as f64 > corner.x as f64
+            && origin.y < corner.y as f64 + extent.height as f64
+            && origin.y + size.height as f64 > corner.y as f64
+    });
+    if !on_screen {
+        log::warn!("Moving window off-screen to ({}, {})", position.x, position.y);
+    }
+    window.set_outer_position(position);
+}
+
+/// Centers `window` on the monitor showing it, or the primary one.
+pub fn center(window: &tao::window::Window) {
+    let Some(monitor) = window.current_monitor().or_else(|| window.primary_monitor()) else {
+        log::warn!("No monitor to center the window on");
+        return;
+    };
+    let size = window.outer_size();
+    let origin = monitor.position();
+    let x = origin.x as f64 + (monitor.size().width as f64 - size.width as f64) / 2.0;
+    let y = origin.y as f64 + (monitor.size().height as f64 - size.height as f64) / 2.0;
+    window.set_outer_position(PhysicalPosition::new(x, y));
+}
+
+/// Reads the geometry saved at `path`. A missing, unreadable or corrupt
+/// file gives `None` (logged unless missing), so the window falls back to
+/// its configured geometry.
+pub fn load(path: &std::path::Path) -> Option<Geometry> {
+    let data = match std::fs::read_to_string(path) {
+        Ok(data) => data,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return None,
+        Err(e) => {
+            log::warn!("Failed to read window state {}: {}", path.display(), e);
+            return None;
+        }
+    };
+    match serde_json::from_str::<Geometry>(&data) {
+        Ok(geometry) if geometry.width > 0.0 && geometry.height > 0.0 => Some(geometry),
+        Ok(_) => {
+            log::warn!("Ignoring window state {} with an empty size", path.display());
+            None
+        }
+        Err(e) => {
+            log::warn!("Ignoring corrupt window state {}: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+/// Writes geometry updates to a state file from a background task.
+pub struct GeometryStore {
+    path: PathBuf,
+    tx: watch::Sender<Option<Geometry>>,
+}
+
+impl GeometryStore {
+    /// Starts the task writing to `path` on `runtime`.
+    pub fn spawn(runtime: &tokio::runtime::Handle, path: PathBuf) -> Self {
+        let (tx, mut rx) = watch::channel(None::<Geometry>);
+        let store = GeometryStore { path: path.clone(), tx };
+        runtime.spawn(async move {
+            while rx.changed().await.is_ok() {
+                // Restart the delay on every change until the window rests.
+                while let Ok(Ok(())) = tokio::time::timeout(SAVE_DELAY, rx.changed()).await {}
+                let Some(geometry) = *rx.borrow_and_update() else {
+                    continue;
+                };
+                let path = path.clone();
+                let _ = tokio::task::spawn_blocking(move || save(&path, geometry)).await;
+            }
+        });
+        store
+    }
+
+    /// Schedules saving the current geometry of `window`.
+    pub fn update(&self, window: &tao::window::Window) {
+        if let Some(geometry) = Geometry::of(window) {
+            self.tx.send_if_modified(|current| {
+                let changed = *current != Some(geometry);
+                *current = Some(geometry);
+                changed
+            });
+        }
+    }
+
+    /// Saves the geometry of `window` right away, e.g. before it closes.
+    pub fn flush(&self, window: &tao::window::Window) {
+        if let Some(geometry) = Geometry::of(window) {
+            // Recorded without waking the task, which would save it again.
+            self.tx.send_if_modified(|current| {
+                *current = Some(geometry);
+                false
+            });
+            save(&self.path, geometry);
+        }
+    }
+}
+
+fn save(path: &std::path::Path, geometry: Geometry) {
+    let data = serde_json::to_string(&geometry).expect("geometry serializes");
+    if let Err(e) = std::fs::write(path, data) {
+        log::warn!("Failed to save window state {}: {}", path.display(), e);
+    }
+}
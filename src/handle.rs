@@ -0,0 +1,106 @@
+use pyo3::prelude::*;
+use std::sync::{Mutex, PoisonError};
+use std::thread::JoinHandle;
+
+use crate::{errors, run_webframe, send_runtime_message, windows, RuntimeMessage, WebframeArgs};
+
+/// A webframe whose event loop runs on a background thread, returned by
+/// `create_webframe(blocking=False)`.
+#[pyclass]
+pub struct FrameHandle {
+    thread: Mutex<Option<JoinHandle<()>>>,
+}
+
+#[pymethods]
+impl FrameHandle {
+    /// Id of the main window, `MAIN_WINDOW_ID`.
+    #[getter]
+    fn window_id(&self) -> u64 {
+        windows::MAIN_WINDOW_ID
+    }
+
+    /// Whether the event loop is still running.
+    fn is_running(&self) -> bool {
+        self.thread
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .as_ref()
+            .is_some_and(|thread| !thread.is_finished())
+    }
+
+    /// Waits, with the GIL released, until the event loop exits, e.g.
+    /// after the last window closed or `quit()`.
+    fn join(&self, py: Python<'_>) {
+        let thread = self.thread.lock().unwrap_or_else(PoisonError::into_inner).take();
+        if let Some(thread) = thread {
+            if py.allow_threads(|| thread.join()).is_err() {
+                log::error!("Event loop thread panicked");
+            }
+        }
+    }
+
+    /// Closes every window and exits the event loop, like `quit_app()`.
+    ///
+    /// # Errors
+    /// Raises `FrameError` if the event loop has already exited.
+    fn quit(&self) -> PyResult<()> {
+        send_runtime_message(RuntimeMessage::Quit)
+    }
+}
+
+/// Runs [`run_webframe`] on a new thread, returning once the window is up.
+///
+/// # Errors
+/// Returns the error of building the window, `FrameError` if the thread
+/// stopped before, and `PyNotImplementedError` where the event loop must
+/// run on the main thread.
+pub fn spawn(py: Python<'_>, args: WebframeArgs) -> PyResult<FrameHandle> {
+    if cfg!(any(target_os = "macos", target_os = "android", target_os = "ios")) {
+        return Err(pyo3::exceptions::PyNotImplementedError::new_err(
+            "blocking=False is not supported on this platform, where the event loop must run on the main thread",
+        ));
+    }
+    let (ready_tx, ready_rx) = crossbeam_channel::bounded(1);
+    let thread = std::thread::Builder::new()
+        .name("pyframe-event-loop".to_string())
+        .spawn(move || {
+            Python::with_gil(|py| {
+                if let Err(e) = run_webframe(py, args, Some(&ready_tx)) {
+                    let _ = ready_tx.send(Err(e));
+                }
+            })
+        })
+        .map_err(|e| errors::FrameError::new_err(format!("Failed to start event loop thread: {}", e)))?;
+    py.allow_threads(|| ready_rx.recv())
+        .map_err(|_| errors::FrameError::new_err("Event loop thread stopped before the window was created"))??;
+    Ok(FrameHandle { thread: Mutex::new(Some(thread)) })
+}
+
+/// Lets `builder` create the event loop off the main thread.
+pub fn allow_any_thread(builder: &mut tao::event_loop::EventLoopBuilder<RuntimeMessage>) {
+    #[cfg(target_os = "windows")]
+    {
+        use tao::platform::windows::EventLoopBuilderExtWindows;
+        builder.with_any_thread(true);
+    }
+    #[cfg(any(
+        target_os = "linux",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd"
+    ))]
+    {
+        use tao::platform::unix::EventLoopBuilderExtUnix;
+        builder.with_any_thread(true);
+    }
+    #[cfg(not(any(
+        target_os = "windows",
+        target_os = "linux",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd"
+    )))]
+    let _ = builder;
+}
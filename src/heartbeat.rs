@@ -0,0 +1,86 @@
+use pyo3::prelude::*;
+use pyo3::types::PyString;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::{events, py_sender, stats, tasks};
+
+/// Prefix of the pings sent to the Python thread's `ReceiverHandle`, e.g.
+/// `pyframe-ping:3`. The thread answers by sending the ping back through
+/// its `SenderHandle`.
+pub const PING_PREFIX: &str = "pyframe-ping:";
+
+/// The last ping answered by the Python thread, shared by the heartbeat
+/// task and the consumer of the thread's messages.
+#[derive(Default)]
+pub struct Heartbeat {
+    answered: AtomicU64,
+}
+
+impl Heartbeat {
+    /// Records `msg`, sent by the Python thread, if it answers a ping;
+    /// returns whether it did.
+    pub fn record_pong(&self, msg: &Bound<'_, PyAny>) -> bool {
+        let Ok(text) = msg.downcast::<PyString>() else {
+            return false;
+        };
+        let ping = text.to_str().ok().and_then(|text| text.strip_prefix(PING_PREFIX)?.parse::<u64>().ok());
+        match ping {
+            Some(ping) => {
+                self.answered.fetch_max(ping, Ordering::Relaxed);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Pings the Python thread every `interval` through `to_py`. A ping still
+/// unanswered when the next one is due means the thread stalled:
+/// `on_stall(seconds)` is called with the time since the last answer, once
+/// per stall, or a warning is logged without it. While the ping waits in
+/// the channel no other is sent; once it was taken without an answer,
+/// e.g. by a loop that was restarted since, it is sent again.
+pub fn spawn(
+    tasks: &mut tasks::Tasks,
+    runtime: &tokio::runtime::Handle,
+    heartbeat: Arc<Heartbeat>,
+    interval: Duration,
+    to_py: crossbeam_channel::Sender<py_sender::Envelope>,
+    on_stall: Option<Py<PyAny>>,
+) {
+    tasks.spawn(runtime, "heartbeat".to_string(), async move {
+        let mut ticks = tokio::time::interval(interval);
+        ticks.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        let mut sent = 0;
+        let mut last_answer = Instant::now();
+        let mut stalled = false;
+        loop {
+            ticks.tick().await;
+            let answered = heartbeat.answered.load(Ordering::Relaxed);
+            if answered == sent {
+                if stalled {
+                    log::info!("Python event loop thread answers pings again");
+                    stalled = false;
+                }
+                last_answer = Instant::now();
+            } else if !stalled {
+                stalled = true;
+                let seconds = last_answer.elapsed().as_secs_f64();
+                match &on_stall {
+                    Some(on_stall) => events::notify(on_stall, (seconds,)),
+                    None => log::warn!("Python event loop thread did not answer pings for {:.1}s", seconds),
+                }
+            }
+            if answered == sent || to_py.is_empty() {
+                sent += 1;
+                let ping = format!("{}{}", PING_PREFIX, sent);
+                let msg = Python::with_gil(|py| PyString::new(py, &ping).into_any().unbind());
+                stats::TO_PYTHON
+                    .count(to_py.send(py_sender::Envelope { id: None, msg }))
+                    .map_err(|_| "Python receiver closed".to_string())?;
+            }
+        }
+    });
+}
@@ -0,0 +1,61 @@
+use notify::{RecursiveMode, Watcher};
+use pyo3::prelude::*;
+use std::path::Path;
+use std::time::Duration;
+use tao::event_loop::EventLoopProxy;
+use tokio::sync::mpsc::unbounded_channel;
+
+use crate::{windows, RuntimeMessage};
+
+/// Time without further changes before the page reloads, so saving many
+/// files at once (or an editor's write-rename dance) reloads once.
+const RELOAD_DELAY: Duration = Duration::from_millis(200);
+
+/// Reloads the main window when files under a directory change. Dropping
+/// it stops the watcher thread, which ends the debouncing task as well.
+pub struct HotReload {
+    _watcher: notify::RecommendedWatcher,
+}
+
+impl HotReload {
+    /// Starts watching `path` recursively, debouncing on `runtime`.
+    ///
+    /// # Errors
+    /// Returns `PyValueError` if `path` does not exist and `PyOSError` if
+    /// it cannot be watched.
+    pub fn spawn(
+        runtime: &tokio::runtime::Handle,
+        path: &Path,
+        proxy: EventLoopProxy<RuntimeMessage>,
+    ) -> PyResult<Self> {
+        if !path.exists() {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "watch_path does not exist: {}",
+                path.display()
+            )));
+        }
+        let (tx, mut rx) = unbounded_channel();
+        let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| match event {
+            Ok(event) if !event.kind.is_access() => {
+                let _ = tx.send(());
+            }
+            Ok(_) => {}
+            Err(e) => log::warn!("File watcher error: {}", e),
+        })
+        .map_err(|e| pyo3::exceptions::PyOSError::new_err(format!("Failed to start file watcher: {}", e)))?;
+        watcher
+            .watch(path, RecursiveMode::Recursive)
+            .map_err(|e| pyo3::exceptions::PyOSError::new_err(format!("Failed to watch {}: {}", path.display(), e)))?;
+        runtime.spawn(async move {
+            while rx.recv().await.is_some() {
+                // Restart the delay on every change until the files rest.
+                while let Ok(Some(())) = tokio::time::timeout(RELOAD_DELAY, rx.recv()).await {}
+                log::info!("Files changed, reloading");
+                if proxy.send_event(RuntimeMessage::Reload(windows::MAIN_WINDOW_ID)).is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(HotReload { _watcher: watcher })
+    }
+}
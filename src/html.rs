@@ -0,0 +1,43 @@
+/// Makes relative URLs in `html` resolve against `base_url`.
+///
+/// Wry does not expose `load_html_with_base_url` on any backend, so a
+/// `<base href>` element is inserted at the start of the document's
+/// `<head>` instead (creating one if needed), which WebKitGTK, WKWebView
+/// and WebView2 all honor.
+pub fn with_base_url(html: &str, base_url: &str) -> String {
+    let base = format!("<base href=\"{}\">", escape_attribute(base_url));
+    let lower = html.to_ascii_lowercase();
+
+    if let Some(end) = tag_end(&lower, "<head") {
+        return format!("{}{}{}", &html[..end], base, &html[end..]);
+    }
+    if let Some(end) = tag_end(&lower, "<html") {
+        return format!("{}<head>{}</head>{}", &html[..end], base, &html[end..]);
+    }
+    format!("{}{}", base, html)
+}
+
+/// Returns the byte offset just past the opening tag `tag` (e.g. `"<head"`).
+fn tag_end(lower_html: &str, tag: &str) -> Option<usize> {
+    let mut from = 0;
+    while let Some(pos) = lower_html[from..].find(tag) {
+        let start = from + pos;
+        let after = start + tag.len();
+        // Skip longer tag names such as `<header`.
+        match lower_html[after..].chars().next() {
+            Some(c) if c == '>' || c.is_ascii_whitespace() => {
+                return lower_html[after..].find('>').map(|i| after + i + 1);
+            }
+            _ => from = after,
+        }
+    }
+    None
+}
+
+fn escape_attribute(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
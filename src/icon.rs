@@ -0,0 +1,39 @@
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyTuple};
+use std::path::PathBuf;
+use tao::window::Icon;
+
+/// Reads the `icon` passed to `create_webframe`: a path to an image file,
+/// the encoded file contents as `bytes`, or a `(rgba, width, height)`
+/// tuple of raw pixels.
+///
+/// # Errors
+/// Returns `PyValueError` for unreadable files, formats other than PNG,
+/// ICO and JPEG, and RGBA data that does not match `width` x `height`.
+pub fn from_py(py: Python<'_>, value: Py<PyAny>) -> PyResult<Icon> {
+    let (rgba, width, height) = rgba_from_py(py, value)?;
+    Icon::from_rgba(rgba, width, height)
+        .map_err(|e| pyo3::exceptions::PyValueError::new_err(format!("Invalid icon: {}", e)))
+}
+
+/// Decodes an icon given like [`from_py`] into RGBA pixels, width and
+/// height, for consumers other than tao windows (e.g. the tray).
+///
+/// # Errors
+/// Returns `PyValueError` for unreadable files and unsupported formats.
+pub fn rgba_from_py(py: Python<'_>, value: Py<PyAny>) -> PyResult<(Vec<u8>, u32, u32)> {
+    let value = value.bind(py);
+    if let Ok(tuple) = value.downcast::<PyTuple>() {
+        return tuple.extract();
+    }
+    let image = if let Ok(bytes) = value.downcast::<PyBytes>() {
+        image::load_from_memory(bytes.as_bytes())
+    } else {
+        let path: PathBuf = py.import("os")?.call_method1("fspath", (value,))?.extract()?;
+        image::open(&path)
+    }
+    .map_err(|e| pyo3::exceptions::PyValueError::new_err(format!("Invalid icon: {}", e)))?;
+    let image = image.into_rgba8();
+    let (width, height) = image.dimensions();
+    Ok((image.into_raw(), width, height))
+}
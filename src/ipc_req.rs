@@ -1,22 +1,62 @@
 use pyo3::prelude::*;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 use wry::http::Request;
 
-use crate::RuntimeMessage;
+use crate::codec::{self, IpcFormat};
+use crate::errors;
+use crate::events;
+use crate::js;
+use crate::router;
+use crate::rpc;
+use crate::executpy::HandlerLoop;
+use crate::{RuntimeMessage, Target};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SerdeRequest<T> {
+    /// Number of the request, unique per process and assigned by
+    /// [`handle_ipc_req`] in arrival order.
+    pub id: u64,
     /// HTTP method of the request (e.g. `"POST"`).
     pub method: String,
     /// Target URI (e.g. `"/api/call"` or `"https://example.com"`).
     pub uri: String,
-    /// HTTP version, typically `"HTTP/1.1"`.
+    /// Path of `uri`, e.g. `"/api/call"` for `"https://example.com/api/call?x=1"`.
+    pub path: String,
+    /// Decoded query parameters of `uri`, each name mapped to all its
+    /// values in order, like Python's `urllib.parse.parse_qs`:
+    /// `{"tag": ["a", "b"]}` for `?tag=a&tag=b`.
+    pub query: BTreeMap<String, Vec<String>>,
+    /// Parameters of the route the request named and matched (see
+    /// [`router::register_route`]), e.g. `{"id": "7"}` for `/users/{id}`;
+    /// empty for requests reaching the window's handler.
+    pub route_params: BTreeMap<String, String>,
+    /// HTTP version: `"HTTP/0.9"`, `"HTTP/1.0"`, `"HTTP/1.1"` (typical),
+    /// `"HTTP/2.0"` or `"HTTP/3.0"`.
     pub version: String,
-    /// Request headers, collected into a `HashMap`.
-    pub headers: HashMap<String, String>,
-    /// Request body (generic type).
+    /// Request headers as `(name, value)` pairs, serialized as a list of
+    /// `[name, value]` arrays, e.g. `[["set-cookie", "a=1"], ["set-cookie",
+    /// "b=2"]]`. Repeated headers keep every value. Pairs follow the order
+    /// in which each name first appeared, with the values of a repeated
+    /// name kept together in their original order.
+    pub headers: Vec<(String, String)>,
+    /// Headers whose values are not valid UTF-8, as `[name, value]` pairs
+    /// with the value base64-encoded. Usually empty.
+    pub raw_headers: Vec<(String, String)>,
+    /// Request body (generic type), as sent.
     pub body: T,
+    /// `body` parsed, for JSON bodies: those of requests whose
+    /// `Content-Type` is JSON (`application/json` or `*/*+json`), and
+    /// messages posted by the pages, which carry no headers, whenever their
+    /// body is valid JSON, e.g. the envelope of `window.invoke`. `null` for
+    /// other content types, binary and MessagePack bodies and invalid JSON,
+    /// where `body` is the only form.
+    pub json: Option<serde_json::Value>,
+    /// Whether `body` is binary data from `window.ipcPostBinary`: a base64
+    /// `str` in JSON, `bytes` in MessagePack.
+    pub binary: bool,
 }
 
 impl<T: Serialize> From<Request<T>> for SerdeRequest<T> {
@@ -25,67 +65,745 @@ impl<T: Serialize> From<Request<T>> for SerdeRequest<T> {
     fn from(req: Request<T>) -> Self {
         let (parts, body) = req.into_parts();
 
-        // Convert `HeaderMap` into `HashMap<String, String>`
-        let headers = parts
-            .headers
-            .iter()
-            .map(|(k, v)| (k.as_str().to_string(), v.to_str().unwrap_or("").to_string()))
-            .collect::<HashMap<_, _>>();
+        // Convert `HeaderMap` into `(name, value)` pairs, one per value
+        let mut headers = Vec::new();
+        let mut raw_headers = Vec::new();
+        for (k, v) in &parts.headers {
+            match std::str::from_utf8(v.as_bytes()) {
+                Ok(value) => headers.push((k.as_str().to_string(), value.to_string())),
+                Err(_) => {
+                    log::warn!("Header {} is not valid UTF-8, passing it base64-encoded in raw_headers", k);
+                    raw_headers.push((k.as_str().to_string(), codec::encode_base64(v.as_bytes())));
+                }
+            }
+        }
 
         SerdeRequest {
+            id: 0,
             method: parts.method.to_string(),
             uri: parts.uri.to_string(),
-            version: format!("{:?}", parts.version),
+            path: parts.uri.path().to_string(),
+            query: parse_query(parts.uri.query()),
+            route_params: BTreeMap::new(),
+            version: version_str(parts.version).to_string(),
             headers,
+            raw_headers,
             body,
+            json: None,
+            binary: false,
+        }
+    }
+}
+
+/// Groups the decoded pairs of `query` by name.
+fn parse_query(query: Option<&str>) -> BTreeMap<String, Vec<String>> {
+    let mut params = BTreeMap::<String, Vec<String>>::new();
+    for (name, value) in url::form_urlencoded::parse(query.unwrap_or_default().as_bytes()) {
+        params.entry(name.into_owned()).or_default().push(value.into_owned());
+    }
+    params
+}
+
+/// Canonical name of `version`, independent of its `Debug` output.
+fn version_str(version: wry::http::Version) -> &'static str {
+    use wry::http::Version;
+    match version {
+        Version::HTTP_09 => "HTTP/0.9",
+        Version::HTTP_10 => "HTTP/1.0",
+        Version::HTTP_11 => "HTTP/1.1",
+        Version::HTTP_2 => "HTTP/2.0",
+        Version::HTTP_3 => "HTTP/3.0",
+        // `Version` is an opaque struct, so the match needs a catch-all.
+        other => {
+            log::warn!("Unknown HTTP version {:?}, reporting it as HTTP/1.1", other);
+            "HTTP/1.1"
+        }
+    }
+}
+
+impl<T> SerdeRequest<T> {
+    /// Sets the request number.
+    pub fn with_id(mut self, id: u64) -> Self {
+        self.id = id;
+        self
+    }
+
+    /// Sets the parameters of the matched route.
+    pub fn with_route_params(mut self, route_params: BTreeMap<String, String>) -> Self {
+        self.route_params = route_params;
+        self
+    }
+}
+
+impl SerdeRequest<String> {
+    /// Fills in [`SerdeRequest::json`] if the request declares a JSON body
+    /// or, declaring no content type, has one.
+    pub fn with_json_body(mut self) -> Self {
+        let content_type = self.headers.iter().find(|(name, _)| name == "content-type");
+        match content_type {
+            Some((_, value)) if is_json_content_type(value) => match serde_json::from_str(&self.body) {
+                Ok(value) => self.json = Some(value),
+                Err(e) => log::warn!("Request {} claims a JSON body but it does not parse: {}", self.id, e),
+            },
+            Some(_) => {}
+            None => self.json = serde_json::from_str(&self.body).ok(),
+        }
+        self
+    }
+}
+
+/// Whether the media type of a `Content-Type` value is JSON, ignoring
+/// parameters such as `charset`.
+fn is_json_content_type(value: &str) -> bool {
+    let media_type = value.split(';').next().unwrap_or_default().trim().to_ascii_lowercase();
+    media_type == "application/json" || (media_type.contains('/') && media_type.ends_with("+json"))
+}
+
+static NEXT_REQUEST_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Default of `create_webframe(max_ipc_body_size=...)`: 64 MiB.
+pub const DEFAULT_MAX_BODY_SIZE: usize = 64 * 1024 * 1024;
+
+/// Id attached to requests sent through `window.invoke`,
+/// `window.ipcInvoke` or `window.ipcRoute` (see `assets/py.js`). Its
+/// presence marks a request whose JS promise is settled with the handler's
+/// outcome.
+#[derive(Debug)]
+struct InvokeId {
+    invoke_id: String,
+}
+
+/// The fields of a posted body that tell how to answer and where to send
+/// it: the invoke id, and the route and method named by `window.ipcRoute`.
+/// Bodies without them, or that are not maps, have none.
+#[derive(Debug, Default, Deserialize)]
+struct Addressing {
+    invoke_id: Option<String>,
+    route: Option<String>,
+    method: Option<String>,
+}
+
+impl Addressing {
+    /// Finds the registered route matching the named route, ignoring any
+    /// query or fragment, and method, the request's HTTP method if none.
+    fn find_route(&self, http_method: &str) -> Option<router::Match> {
+        let path = self.route.as_deref()?.split(['?', '#']).next()?;
+        router::find(self.method.as_deref().unwrap_or(http_method), path)
+    }
+
+    fn invoke(self) -> Option<InvokeId> {
+        self.invoke_id.map(|invoke_id| InvokeId { invoke_id })
+    }
+}
+
+/// Bytes at the start of a body searched by [`leading_invoke_id`].
+const INVOKE_ID_SCAN: usize = 256;
+
+/// Reads the invoke id from the first [`INVOKE_ID_SCAN`] bytes of `body`,
+/// for bodies too large to parse whole. `assets/py.js` encodes it as the
+/// first field of the message, in either format.
+fn leading_invoke_id(format: IpcFormat, body: &str) -> Option<InvokeId> {
+    let end = (0..=INVOKE_ID_SCAN.min(body.len())).rev().find(|&end| body.is_char_boundary(end))?;
+    let head = &body[..end];
+    let invoke_id = match format {
+        IpcFormat::Json => {
+            let value = head.trim_start().strip_prefix('{')?.trim_start().strip_prefix("\"invoke_id\"")?;
+            let value = value.trim_start().strip_prefix(':')?;
+            String::deserialize(&mut serde_json::Deserializer::from_str(value)).ok()?
+        }
+        IpcFormat::MessagePack => {
+            // Whole base64 quanta only, so the prefix decodes.
+            let bytes = codec::decode_base64(&head[..head.len() / 4 * 4]).ok()?;
+            let fields = match bytes.first()? {
+                0x80..=0x8f => &bytes[1..],
+                0xde => bytes.get(3..)?,
+                0xdf => bytes.get(5..)?,
+                _ => return None,
+            };
+            let mut fields = rmp_serde::Deserializer::new(fields);
+            if String::deserialize(&mut fields).ok()? != "invoke_id" {
+                return None;
+            }
+            String::deserialize(&mut fields).ok()?
+        }
+    };
+    Some(InvokeId { invoke_id })
+}
+
+/// Settings shared by every IPC request, used by the [`IpcWorker`].
+///
+/// - `proxy`: Event loop proxy used to evaluate the reply script.
+/// - `format`: Wire format of the bodies posted by `window.invoke`.
+/// - `binary_ipc`: Accept binary bodies posted by `window.ipcPostBinary`.
+/// - `handler_loop`: Asyncio loop that awaitables are scheduled on.
+/// - `runtime`: Tokio runtime that waits for scheduled awaitables.
+/// - `on_error`: Called with the formatted traceback whenever a handler
+///   (or the awaitable it returned) raises.
+/// - `max_body_size`: Bodies longer than this many bytes skip the handler
+///   and are reported to `on_error` as an `IpcError`.
+pub struct IpcSettings {
+    pub proxy: tao::event_loop::EventLoopProxy<RuntimeMessage>,
+    pub format: IpcFormat,
+    pub binary_ipc: bool,
+    pub handler_loop: HandlerLoop,
+    pub runtime: tokio::runtime::Handle,
+    pub on_error: Option<Arc<Py<PyAny>>>,
+    pub max_body_size: Option<usize>,
+}
+
+/// A request posted by a WebView, waiting for the worker.
+struct Job {
+    handler: Arc<Py<PyAny>>,
+    target: Target,
+    req: Request<String>,
+}
+
+/// Calls the Python handlers on a dedicated thread rather than the event
+/// loop's, so a slow handler does not freeze the windows.
+///
+/// Requests are handled one at a time in the order they were posted,
+/// across all WebViews. Handlers therefore run in that order, but
+/// awaitables complete independently: with several requests in flight,
+/// promises are resolved in completion order, each one by its own
+/// callback id.
+#[derive(Clone)]
+pub struct IpcWorker(crossbeam_channel::Sender<Job>);
+
+impl IpcWorker {
+    /// Starts the worker thread, which exits once every handler created
+    /// with this worker is dropped.
+    ///
+    /// # Errors
+    /// Returns `FrameError` if the thread cannot be started.
+    pub fn spawn(settings: IpcSettings) -> PyResult<Self> {
+        let (jobs, queue) = crossbeam_channel::unbounded::<Job>();
+        std::thread::Builder::new()
+            .name("pyframe-ipc".to_string())
+            .spawn(move || {
+                for job in queue.iter() {
+                    settings.handle(job);
+                }
+            })
+            .map_err(|e| errors::FrameError::new_err(format!("Failed to start IPC thread: {}", e)))?;
+        Ok(IpcWorker(jobs))
+    }
+}
+
+impl IpcSettings {
+    /// Passes `job` to its handler:
+    /// - converts the request into [`SerdeRequest`],
+    /// - serializes it into `format` (a JSON `str` or MessagePack `bytes`),
+    /// - calls the handler with the encoded request,
+    /// - if the call returns an awaitable (e.g. the handler is `async def`),
+    ///   schedules it on `handler_loop` and waits for it on `runtime`
+    ///   without blocking the worker. If no loop is running, the request
+    ///   fails with an `IpcError` instead.
+    ///
+    /// For requests sent through `window.invoke`/`window.ipcInvoke`, the
+    /// (awaited) return value resolves the JS promise through
+    /// `window.__resolve(invoke_id, result)` and a raised exception rejects
+    /// it through `window.__reject(invoke_id, message)`. For any other
+    /// request, a returned string is evaluated as a script.
+    ///
+    /// A handler returning an async iterator (e.g. an async generator)
+    /// streams its items instead, see [`IpcSettings::stream`].
+    ///
+    /// A request that cannot be decoded, such as a MessagePack body that is
+    /// not valid base64, skips the handler: its error rejects the promise,
+    /// if the invoke id could still be read, and goes to `on_error`. So does
+    /// a body larger than `max_body_size`, whose invoke id is read from its
+    /// start only (see [`leading_invoke_id`]).
+    ///
+    /// JSON-RPC requests go to the method registered under their name
+    /// instead (see [`rpc::register_method`]), and requests naming a
+    /// matching route to the route's handler (see
+    /// [`router::register_route`]).
+    fn handle(&self, job: Job) {
+        let Job { handler, target, req } = job;
+        Python::with_gil(|py| {
+            if let Some(max) = self.max_body_size.filter(|&max| req.body().len() > max) {
+                let outcome = Err(errors::IpcError::new_err(format!(
+                    "IPC message of {} bytes exceeds max_ipc_body_size ({} bytes), not calling the handler",
+                    req.body().len(),
+                    max
+                )));
+                report_error(py, self.on_error.as_deref(), &outcome);
+                let invoke = leading_invoke_id(self.format, req.body());
+                send_reply(py, &self.proxy, target, invoke.as_ref(), outcome);
+                return;
+            }
+            if let Some(call) = rpc::parse(req.body()) {
+                self.handle_rpc(py, target, call);
+                return;
+            }
+            let id = NEXT_REQUEST_ID.fetch_add(1, Ordering::Relaxed);
+            let request = Encoding { format: self.format, binary_ipc: self.binary_ipc, id, handler };
+            let Encoded { handler, invoke, payload } = request.encode(py, req);
+            let payload = match payload {
+                Ok(payload) => payload,
+                Err(error) => {
+                    let outcome = Err(error);
+                    report_error(py, self.on_error.as_deref(), &outcome);
+                    send_reply(py, &self.proxy, target, invoke.as_ref(), outcome);
+                    return;
+                }
+            };
+            let outcome = handler.call1(py, (payload,));
+            if let Ok(items) = &outcome {
+                if items.bind(py).hasattr("__anext__").unwrap_or(false) {
+                    self.stream(target, invoke, items.clone_ref(py));
+                    return;
+                }
+            }
+            let proxy = self.proxy.clone();
+            self.settle(py, outcome, move |py, outcome| {
+                send_reply(py, &proxy, target, invoke.as_ref(), outcome)
+            });
+        });
+    }
+
+    /// Forwards the items of `items`, an async iterator returned by a
+    /// handler, to the page as they are produced. Each one is awaited on
+    /// `handler_loop`, then, for requests sent through `window.invoke`,
+    /// passed to `window.__streamChunk(invoke_id, item)` as JSON; the end
+    /// of the iteration calls `window.__streamEnd(invoke_id)` and an
+    /// exception `window.__streamError(invoke_id, message)` (and
+    /// `on_error`). For other requests, items that are strings are
+    /// evaluated as scripts.
+    fn stream(&self, target: Target, invoke: Option<InvokeId>, items: Py<PyAny>) {
+        let proxy = self.proxy.clone();
+        let handler_loop = self.handler_loop.clone();
+        let on_error = self.on_error.clone();
+        self.runtime.spawn(async move {
+            loop {
+                let next = Python::with_gil(|py| handler_loop.schedule(items.bind(py).call_method0("__anext__")?));
+                let outcome = match next {
+                    Ok(next) => next.await,
+                    Err(error) => Err(error),
+                };
+                let finished = Python::with_gil(|py| {
+                    let (script, finished) = match outcome.and_then(|item| item_script(py, invoke.as_ref(), item)) {
+                        Ok(script) => (script, false),
+                        Err(error) if error.is_instance_of::<pyo3::exceptions::PyStopAsyncIteration>(py) => {
+                            let script = invoke
+                                .as_ref()
+                                .map(|invoke| js::call("window.__streamEnd", &[invoke_id_literal(invoke)]));
+                            (script, true)
+                        }
+                        Err(error) => {
+                            log::error!("IPC stream error: {:?}", error);
+                            let script = invoke.as_ref().map(|invoke| {
+                                let message = js::string_literal(&error.value(py).to_string());
+                                js::call("window.__streamError", &[invoke_id_literal(invoke), message])
+                            });
+                            report_error(py, on_error.as_deref(), &Err(error));
+                            (script, true)
+                        }
+                    };
+                    if let Some(script) = script {
+                        let _ = proxy.send_event(RuntimeMessage::Eval(target, script));
+                    }
+                    finished
+                });
+                if finished {
+                    break;
+                }
+            }
+        });
+    }
+
+    /// Calls the method named by `call` and responds to the page unless it
+    /// is a notification.
+    fn handle_rpc(&self, py: Python<'_>, target: Target, call: rpc::Call) {
+        log::debug!("JSON-RPC call of {:?}", call.method);
+        let proxy = self.proxy.clone();
+        let respond = move |py: Python<'_>, id: Option<serde_json::Value>, outcome| {
+            if let Some(id) = id {
+                let _ = proxy.send_event(RuntimeMessage::Eval(target, rpc::response_script(py, &id, outcome)));
+            }
+        };
+        match rpc::invoke(py, &call) {
+            Ok(outcome) => {
+                let id = call.id;
+                self.settle(py, outcome, move |py, outcome| respond(py, id, Ok(outcome)));
+            }
+            Err(fault) => {
+                log::warn!("JSON-RPC call of {:?} failed: {}", call.method, fault.message());
+                respond(py, call.id, Err(fault));
+            }
+        }
+    }
+
+    /// Completes `outcome`, the result of calling a handler: awaits it if
+    /// it is an awaitable (see [`IpcSettings::handle`]), then reports a
+    /// failure to `on_error` and passes the final outcome to `reply`.
+    fn settle<F>(&self, py: Python<'_>, outcome: PyResult<Py<PyAny>>, reply: F)
+    where
+        F: FnOnce(Python<'_>, PyResult<Py<PyAny>>) + Send + 'static,
+    {
+        let awaitable = match &outcome {
+            Ok(res) => res.bind(py).hasattr("__await__").unwrap_or(false),
+            Err(_) => false,
+        };
+        if !awaitable {
+            report_error(py, self.on_error.as_deref(), &outcome);
+            reply(py, outcome);
+            return;
+        }
+
+        match self.handler_loop.schedule(outcome.unwrap().into_bound(py)) {
+            Ok(future) => {
+                let on_error = self.on_error.clone();
+                self.runtime.spawn(async move {
+                    let outcome = future.await;
+                    Python::with_gil(|py| {
+                        report_error(py, on_error.as_deref(), &outcome);
+                        reply(py, outcome)
+                    });
+                });
+            }
+            Err(error) => {
+                let outcome = Err(error);
+                report_error(py, self.on_error.as_deref(), &outcome);
+                reply(py, outcome)
+            }
         }
     }
 }
 
 /// Creates an IPC handler for [`wry::WebViewBuilder::with_ipc_handler`].
 ///
-/// The handler:
-/// - receives incoming [`Request<String>`] objects from the WebView,
-/// - converts them into [`SerdeRequest`],
-/// - serializes them into JSON,
-/// - calls the provided Python function with the JSON as an argument.
+/// The handler receives the [`Request<String>`] objects posted by the
+/// WebView and queues them on `worker`, which calls `handler` with them
+/// (see [`IpcSettings::handle`]); the event loop never waits for Python.
+/// Bodies starting with [`codec::COMMAND_PREFIX`] are handled in Rust, and
+/// those starting with [`codec::KEY_PREFIX`] go to `on_key`; neither
+/// reaches `handler`.
 ///
 /// # Parameters
 /// - `handler`: A Python callable (e.g. `def handler(msg: str): ...`)
 ///   that processes the incoming JSON request.
+/// - `target`: The WebView the handler serves, which replies are evaluated in.
+/// - `proxy`: Event loop proxy that commands are sent to.
+/// - `worker`: The thread calling `handler`.
 ///
 /// # Returns
 /// A closure that can be passed directly to Wry as an IPC handler.
- pub fn handle_ipc_req(
+pub fn handle_ipc_req(
     handler: Py<PyAny>,
+    target: Target,
     proxy: tao::event_loop::EventLoopProxy<RuntimeMessage>,
+    worker: IpcWorker,
 ) -> impl Fn(Request<String>) + 'static {
+    let handler = Arc::new(handler);
     move |_req: Request<String>| {
-        let p = proxy.clone();
-        Python::with_gil(|py| {
-            let req = SerdeRequest::from(_req);
-            let json = serde_json::to_string_pretty(&req).unwrap();
-            // match expression *without* a trailing semicolon, so it is returned
-            let handler = handler.clone_ref(py);
-            match handler.call1(py, (json,)) {
-                Ok(res) => {
-                    let proxy = p.clone();
-                    println!("IPC response: {}", res);
-                    if let Ok(Some(script)) = res.extract::<Option<String>>(py) {
-                        println!("ipc script: {}", res);
-                        let _ = proxy.send_event(RuntimeMessage::Eval(script));
-                    }
+        if let Some(command) = _req.body().strip_prefix(codec::COMMAND_PREFIX) {
+            match command {
+                "start_dragging" => {
+                    let _ = proxy.send_event(RuntimeMessage::StartDrag(target));
                 }
-                Err(error) => {
-                    eprintln!("Some Error: {:?}", error);
-                    // Nur eine Fehlermeldung ausgeben
+                "ready" => {
+                    let _ = proxy.send_event(RuntimeMessage::FrontendReady(target));
                 }
-            };
-        });
+                _ => log::warn!("Unknown IPC command: {:?}", command),
+            }
+            return;
+        }
+        if let Some(event) = _req.body().strip_prefix(codec::KEY_PREFIX) {
+            let _ = proxy.send_event(RuntimeMessage::Key(target, event.to_string()));
+            return;
+        }
+        let job = Job { handler: handler.clone(), target, req: _req };
+        if worker.0.send(job).is_err() {
+            log::error!("IPC thread stopped, dropping request");
+        }
     }
 }
 
+/// Passes the error of a failed handler call to `on_error`.
+fn report_error(py: Python<'_>, on_error: Option<&Py<PyAny>>, outcome: &PyResult<Py<PyAny>>) {
+    if let (Some(on_error), Err(error)) = (on_error, outcome) {
+        events::report_error(py, on_error, error);
+    }
+}
+
+/// How a request is turned into the handler argument numbered `id`.
+struct Encoding {
+    format: IpcFormat,
+    binary_ipc: bool,
+    id: u64,
+    /// The window's handler, for requests naming no matching route.
+    handler: Arc<Py<PyAny>>,
+}
+
+/// A request turned into a handler argument.
+struct Encoded {
+    /// The handler to call: the matched route's, else the window's.
+    handler: Arc<Py<PyAny>>,
+    invoke: Option<InvokeId>,
+    /// The handler argument, or why the request could not be encoded.
+    payload: PyResult<Py<PyAny>>,
+}
+
+impl Encoding {
+    /// Encodes `req`, extracting the invoke id from its body and choosing
+    /// the handler of the route it names. The invoke id is read first, so
+    /// a request failing later can still be answered.
+    fn encode(self, py: Python<'_>, req: Request<String>) -> Encoded {
+        if let Some(data) = req.body().strip_prefix(codec::BINARY_PREFIX).filter(|_| self.binary_ipc) {
+            let payload = codec::decode_base64(data).and_then(|bytes| self.encode_binary(py, req.map(|_| bytes)));
+            return Encoded { handler: self.handler, invoke: None, payload };
+        }
+        match self.format {
+            IpcFormat::Json => {
+                let req = SerdeRequest::from(req).with_id(self.id).with_json_body();
+                let addressing = req.json.as_ref().and_then(|json| Addressing::deserialize(json).ok());
+                self.route(py, addressing.unwrap_or_default(), req)
+            }
+            IpcFormat::MessagePack => {
+                let body = match codec::decode_base64(req.body()) {
+                    Ok(body) => body,
+                    Err(error) => return Encoded { handler: self.handler, invoke: None, payload: Err(error) },
+                };
+                let addressing = rmp_serde::from_slice::<Addressing>(&body).unwrap_or_default();
+                let req = SerdeRequest::from(req.map(|_| serde_bytes::ByteBuf::from(body))).with_id(self.id);
+                self.route(py, addressing, req)
+            }
+        }
+    }
+
+    /// Chooses the handler of the route `addressing` names, the window's if
+    /// none matches, and encodes `req` for it.
+    fn route<T: Serialize>(self, py: Python<'_>, addressing: Addressing, req: SerdeRequest<T>) -> Encoded {
+        let (handler, route_params) = match addressing.find_route(&req.method) {
+            Some(found) => found,
+            None => (self.handler, BTreeMap::new()),
+        };
+        let payload = self.format.to_python(py, &req.with_route_params(route_params));
+        Encoded { handler, invoke: addressing.invoke(), payload }
+    }
+
+    /// Encodes a binary request, keeping the body binary where the format
+    /// allows it.
+    fn encode_binary(&self, py: Python<'_>, req: Request<Vec<u8>>) -> PyResult<Py<PyAny>> {
+        match self.format {
+            IpcFormat::Json => {
+                let mut req = SerdeRequest::from(req.map(|body| codec::encode_base64(&body))).with_id(self.id);
+                req.binary = true;
+                self.format.to_python(py, &req)
+            }
+            IpcFormat::MessagePack => {
+                let mut req = SerdeRequest::from(req.map(serde_bytes::ByteBuf::from)).with_id(self.id);
+                req.binary = true;
+                self.format.to_python(py, &req)
+            }
+        }
+    }
+}
+
+/// Turns the outcome of a handler call into a script and sends it for
+/// evaluation.
+fn send_reply(
+    py: Python<'_>,
+    proxy: &tao::event_loop::EventLoopProxy<RuntimeMessage>,
+    target: Target,
+    invoke: Option<&InvokeId>,
+    outcome: PyResult<Py<PyAny>>,
+) {
+    let script = match (invoke, outcome) {
+        (Some(invoke), Ok(res)) => match to_json(py, res) {
+            Ok(value) => js::call("window.__resolve", &[invoke_id_literal(invoke), js::json_literal(value)]),
+            Err(error) => reject_script(py, invoke, &error),
+        },
+        (Some(invoke), Err(error)) => {
+            log::error!("IPC handler error: {:?}", error);
+            reject_script(py, invoke, &error)
+        }
+        (None, Ok(res)) => {
+            log::debug!("IPC response: {}", res);
+            match res.extract::<Option<String>>(py) {
+                Ok(Some(script)) => script,
+                _ => return,
+            }
+        }
+        (None, Err(error)) => {
+            log::error!("IPC handler error: {:?}", error);
+            return;
+        }
+    };
+
+    log::debug!("ipc script: {}", script);
+    let _ = proxy.send_event(RuntimeMessage::Eval(target, script));
+}
+
+/// Builds the script delivering one streamed `item`, see
+/// [`IpcSettings::stream`].
+fn item_script(py: Python<'_>, invoke: Option<&InvokeId>, item: Py<PyAny>) -> PyResult<Option<String>> {
+    match invoke {
+        Some(invoke) => {
+            let value = to_json(py, item)?;
+            Ok(Some(js::call("window.__streamChunk", &[invoke_id_literal(invoke), js::json_literal(value)])))
+        }
+        None => Ok(item.extract::<Option<String>>(py).ok().flatten()),
+    }
+}
+
+/// Serializes a handler's return value with Python's `json.dumps`.
+fn to_json(py: Python<'_>, value: Py<PyAny>) -> PyResult<String> {
+    py.import("json")?.call_method1("dumps", (value,))?.extract()
+}
+
+/// Builds the script rejecting an `invoke` promise with `error`'s message.
+fn reject_script(py: Python<'_>, invoke: &InvokeId, error: &PyErr) -> String {
+    let message = error.value(py).to_string();
+    js::call("window.__reject", &[invoke_id_literal(invoke), js::string_literal(&message)])
+}
+
+/// The invoke id as a JS string literal; it comes from the page and is
+/// never spliced into a script unquoted.
+fn invoke_id_literal(invoke: &InvokeId) -> String {
+    js::string_literal(&invoke.invoke_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(pairs: &[(&str, &[&str])]) -> BTreeMap<String, Vec<String>> {
+        pairs
+            .iter()
+            .map(|&(name, values)| (name.to_string(), values.iter().map(|value| value.to_string()).collect()))
+            .collect()
+    }
+
+    #[test]
+    fn names_every_http_version() {
+        use wry::http::Version;
+        let versions = [
+            (Version::HTTP_09, "HTTP/0.9"),
+            (Version::HTTP_10, "HTTP/1.0"),
+            (Version::HTTP_11, "HTTP/1.1"),
+            (Version::HTTP_2, "HTTP/2.0"),
+            (Version::HTTP_3, "HTTP/3.0"),
+        ];
+        for (version, name) in versions {
+            let req = Request::builder().version(version).body(String::new()).unwrap();
+            let encoded = serde_json::to_string(&SerdeRequest::from(req)).unwrap();
+            let decoded: SerdeRequest<String> = serde_json::from_str(&encoded).unwrap();
+            assert_eq!(decoded.version, name);
+        }
+    }
 
+    fn posted(content_type: Option<&str>, body: &str) -> SerdeRequest<String> {
+        let mut req = Request::builder();
+        if let Some(content_type) = content_type {
+            req = req.header("Content-Type", content_type);
+        }
+        SerdeRequest::from(req.body(body.to_string()).unwrap()).with_json_body()
+    }
+
+    #[test]
+    fn recognizes_json_content_types() {
+        assert!(is_json_content_type("application/json"));
+        assert!(is_json_content_type("application/json; charset=utf-8"));
+        assert!(is_json_content_type("Application/JSON;charset=UTF-8"));
+        assert!(is_json_content_type("application/ld+json"));
+        assert!(!is_json_content_type("text/plain"));
+        assert!(!is_json_content_type("text/plain; charset=utf-8"));
+        assert!(!is_json_content_type("+json"));
+        assert!(!is_json_content_type(""));
+    }
+
+    #[test]
+    fn parses_json_bodies() {
+        let req = posted(Some("application/json; charset=utf-8"), r#"{"n": 1}"#);
+        assert_eq!(req.json, Some(serde_json::json!({ "n": 1 })));
+        assert_eq!(req.body, r#"{"n": 1}"#);
+    }
+
+    #[test]
+    fn parses_json_bodies_without_a_content_type() {
+        assert_eq!(posted(None, r#"{"n": 1}"#).json, Some(serde_json::json!({ "n": 1 })));
+        assert_eq!(posted(None, "not json").json, None);
+    }
+
+    #[test]
+    fn leaves_other_bodies_unparsed() {
+        assert_eq!(posted(Some("text/plain"), r#"{"n": 1}"#).json, None);
+        assert_eq!(posted(Some("application/json"), "{not json").json, None);
+    }
+
+    /// Encodes `body` as posted by a page through wry, which passes it with
+    /// the page URL and no headers, for a handler taking JSON.
+    fn encoded(body: &str) -> (Option<String>, serde_json::Value) {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let encoding = Encoding { format: IpcFormat::Json, binary_ipc: false, id: 1, handler: Arc::new(py.None()) };
+            let req = Request::builder().uri("http://localhost/index.html").body(body.to_string()).unwrap();
+            let Encoded { invoke, payload, .. } = encoding.encode(py, req);
+            let payload: String = payload.unwrap().extract(py).unwrap();
+            (invoke.map(|invoke| invoke.invoke_id), serde_json::from_str(&payload).unwrap())
+        })
+    }
+
+    #[test]
+    fn parses_posted_invoke_envelopes() {
+        let (invoke_id, req) = encoded(r#"{"invoke_id": "7", "payload": {"n": 1}}"#);
+        assert_eq!(invoke_id.as_deref(), Some("7"));
+        assert_eq!(req["json"], serde_json::json!({ "invoke_id": "7", "payload": { "n": 1 } }));
+        assert_eq!(req["headers"], serde_json::json!([]));
+    }
+
+    #[test]
+    fn passes_posted_text_as_body_only() {
+        let (invoke_id, req) = encoded("hello");
+        assert_eq!(invoke_id, None);
+        assert_eq!(req["body"], "hello");
+        assert_eq!(req["json"], serde_json::Value::Null);
+    }
+
+    #[test]
+    fn reads_the_leading_invoke_id_of_json_bodies() {
+        let body = format!(r#"{{"invoke_id":"42","payload":"{}"}}"#, "x".repeat(1000));
+        assert_eq!(leading_invoke_id(IpcFormat::Json, &body).map(|id| id.invoke_id).as_deref(), Some("42"));
+        assert!(leading_invoke_id(IpcFormat::Json, r#"{"payload":1,"invoke_id":"42"}"#).is_none());
+        let cut = format!(r#"{{"invoke_id":"{}"}}"#, "4".repeat(INVOKE_ID_SCAN));
+        assert!(leading_invoke_id(IpcFormat::Json, &cut).is_none());
+        assert!(leading_invoke_id(IpcFormat::Json, "pyframe-binary:AAAA").is_none());
+    }
+
+    #[test]
+    fn reads_the_leading_invoke_id_of_msgpack_bodies() {
+        #[derive(Serialize)]
+        struct Message {
+            invoke_id: &'static str,
+            payload: serde_bytes::ByteBuf,
+        }
+        let message = Message { invoke_id: "42", payload: serde_bytes::ByteBuf::from(vec![7; 1000]) };
+        let body = codec::encode_base64(&rmp_serde::to_vec_named(&message).unwrap());
+        let invoke = leading_invoke_id(IpcFormat::MessagePack, &body);
+        assert_eq!(invoke.map(|id| id.invoke_id).as_deref(), Some("42"));
+        let unnamed = codec::encode_base64(&rmp_serde::to_vec(&message).unwrap());
+        assert!(leading_invoke_id(IpcFormat::MessagePack, &unnamed).is_none());
+    }
+
+    #[test]
+    fn parses_empty_queries() {
+        assert_eq!(parse_query(None), BTreeMap::new());
+        assert_eq!(parse_query(Some("")), BTreeMap::new());
+    }
+
+    #[test]
+    fn groups_repeated_query_names_in_order() {
+        assert_eq!(parse_query(Some("tag=a&x=1&tag=b")), query(&[("tag", &["a", "b"]), ("x", &["1"])]));
+    }
+
+    #[test]
+    fn decodes_query_pairs() {
+        assert_eq!(parse_query(Some("q=a+b%26c&k%C3%A9=%E2%9C%93")), query(&[("q", &["a b&c"]), ("ké", &["✓"])]));
+        assert_eq!(parse_query(Some("flag&empty=")), query(&[("empty", &[""]), ("flag", &[""])]));
+    }
+}
 
 
 
@@ -0,0 +1,93 @@
+/// `value` as a JavaScript string literal.
+///
+/// Every script that carries data goes through this module instead of
+/// formatting values into the source by hand: JSON string syntax escapes
+/// quotes, backslashes and control characters, so the data can neither end
+/// the literal early nor inject code.
+pub fn string_literal(value: &str) -> String {
+    escape_for_script(serde_json::to_string(value).unwrap())
+}
+
+/// `value` as a JavaScript expression.
+pub fn value_literal(value: &serde_json::Value) -> String {
+    json_literal(serde_json::to_string(value).unwrap())
+}
+
+/// Already serialized JSON, e.g. `json.dumps` output, as a JavaScript
+/// expression.
+pub fn json_literal(json: String) -> String {
+    escape_for_script(json)
+}
+
+/// Builds the statement `function(args...);` from already escaped
+/// arguments.
+pub fn call(function: &str, args: &[String]) -> String {
+    format!("{}({});", function, args.join(", "))
+}
+
+/// Script dispatching `event` with the JSON value `payload` to the
+/// listeners registered with `window.ipcOn` (see `assets/py.js`).
+pub fn emit_script(event: &str, payload: &serde_json::Value) -> String {
+    call("window.__emit", &[string_literal(event), value_literal(payload)])
+}
+
+/// Escapes what JSON leaves as is but scripts cannot hold: U+2028 and
+/// U+2029, which engines predating ES2019 treat as line terminators that
+/// cannot appear in string literals, and `</`, which ends a script inlined
+/// in HTML at `</script>`. Outside strings JSON has neither, so escaping
+/// them keeps the value.
+fn escape_for_script(json: String) -> String {
+    if json.contains(['\u{2028}', '\u{2029}']) || json.contains("</") {
+        json.replace('\u{2028}', "\\u2028").replace('\u{2029}', "\\u2029").replace("</", "<\\/")
+    } else {
+        json
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_quotes_and_backslashes() {
+        assert_eq!(string_literal(r#"say "hi" \o/"#), r#""say \"hi\" \\o/""#);
+        assert_eq!(string_literal("');alert(1);('"), r#""');alert(1);('""#);
+    }
+
+    #[test]
+    fn escapes_control_characters() {
+        assert_eq!(string_literal("a\nb\u{0}"), r#""a\nb\u0000""#);
+    }
+
+    #[test]
+    fn escapes_script_end_tags() {
+        assert_eq!(string_literal("</script><script>alert(1)"), r#""<\/script><script>alert(1)""#);
+        assert_eq!(
+            value_literal(&serde_json::json!({ "html": "</SCRIPT>" })),
+            r#"{"html":"<\/SCRIPT>"}"#
+        );
+    }
+
+    #[test]
+    fn escapes_line_separators() {
+        assert_eq!(string_literal("a\u{2028}b\u{2029}c"), r#""a\u2028b\u2029c""#);
+        assert_eq!(value_literal(&serde_json::json!(["\u{2028}"])), r#"["\u2028"]"#);
+    }
+
+    #[test]
+    fn escapes_serialized_json() {
+        assert_eq!(json_literal(r#"{"html": "</script>"}"#.to_string()), r#"{"html": "<\/script>"}"#);
+        assert_eq!(json_literal("[\"\u{2028}\"]".to_string()), r#"["\u2028"]"#);
+    }
+
+    #[test]
+    fn keeps_other_unicode() {
+        assert_eq!(string_literal("ké ✓ 😀"), "\"ké ✓ 😀\"");
+    }
+
+    #[test]
+    fn builds_calls() {
+        let script = emit_script("update", &serde_json::json!({ "n": 1 }));
+        assert_eq!(script, r#"window.__emit("update", {"n":1});"#);
+    }
+}
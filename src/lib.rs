@@ -11,8 +11,9 @@
 //!
 //! ## Overview
 //! - [`SerdeRequest`] serializes incoming HTTP-like requests (`wry::http::Request`).
-//! - [`handle_ipc_req`] creates an IPC handler that converts requests
-//!   to JSON and forwards them to Python.
+//! - [`handle_ipc_req`] creates an IPC handler that queues requests for
+//!   the IPC thread, which converts them to JSON and forwards them to
+//!   Python.
 //! - [`create_webframe`] creates a native window with a WebView, binds the IPC handler,
 //!   and starts the Tao event loop.
 //!
@@ -28,141 +29,2151 @@
 //! some test
 //! ```
 use once_cell::sync::Lazy;
-use std::sync::{Mutex};
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex, PoisonError};
 use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
+use tokio::sync::oneshot;
 use pyo3::prelude::*;
 use serde::{Serialize, Deserialize};
 use tao::{
     event::{Event, WindowEvent},
-    event_loop::{ControlFlow, EventLoopBuilder},
+    event_loop::{ControlFlow, EventLoopBuilder, EventLoopProxy},
 };
 
-/// Custom user-defined messages that can be dispatched
-/// through Tao’s event loop as `UserEvent`.
-///
-/// Currently empty, but can be extended later to
-/// support WebView ↔ Rust communication.
 mod assets;
+mod capture;
+mod certs;
+mod channel;
+mod clipboard;
+mod codec;
+mod cookies;
+mod dialogs;
+mod errors;
+mod events;
 mod executpy;
+mod geometry;
+mod handle;
+mod heartbeat;
+mod hot_reload;
+mod html;
+mod icon;
 mod ipc_req;
+mod js;
+mod menu;
+mod monitors;
+mod navigation;
+mod payloads;
+mod new_window;
+mod opacity;
+mod pdf;
+mod protocol;
+mod proxy_config;
+mod router;
+mod rpc;
+mod runtime;
+mod schema;
+mod py_sender;
+mod shortcuts;
+mod shutdown;
+mod signals;
+mod splash;
+mod stats;
+mod tasks;
+mod tray;
+mod vibrancy;
+mod views;
+mod window_config;
+mod windows;
 
 
 // Define the message structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct Message {
+    #[serde(default)]
     message: String,
     #[serde(default)]
     timestamp: Option<String>,
+    /// Structured data carried as is, e.g. `{"rows": [...]}`; `null` if
+    /// omitted.
+    #[serde(default)]
+    payload: serde_json::Value,
     // Add more fields as needed
 }
 
-static MESSAGE_CHANNEL: Lazy<Mutex<Option<UnboundedSender<Message>>>> = Lazy::new(|| {
+/// A [`Message`] on its way to the consumer, with the channel to
+/// acknowledge it on when the sender waits ([`emit_sync`]).
+struct Emitted {
+    message: Message,
+    ack: Option<crossbeam_channel::Sender<&'static str>>,
+}
+
+impl From<Message> for Emitted {
+    fn from(message: Message) -> Self {
+        Emitted { message, ack: None }
+    }
+}
+
+/// Proxy into the running Tao event loop, set once [`create_webframe`] has
+/// built the window. Used by pyfunctions that need to reach the WebView.
+static EVENT_LOOP_PROXY: Lazy<Mutex<Option<EventLoopProxy<RuntimeMessage>>>> = Lazy::new(|| {
     Mutex::new(None)
 });
 
+/// Custom user-defined messages that are dispatched
+/// through Tao’s event loop as `UserEvent`.
+pub(crate) enum RuntimeMessage {
+    /// Evaluate a script in the WebView `target`, discarding its result.
+    Eval(Target, String),
+    /// Evaluate these scripts one after another in the WebView `target`,
+    /// as a single script.
+    EvalBatch(Target, Vec<String>),
+    /// Evaluate a script in the WebView and send its result back.
+    EvalWithReply(String, oneshot::Sender<EvalResult>),
+    /// Close the main window as if the user had clicked its close button.
+    CloseWindow,
+    /// Close the window and exit the event loop.
+    Quit,
+    /// Ctrl+C: report the close like the user closing the main window,
+    /// then quit.
+    Interrupt,
+    /// Quit and stop the asyncio loops of the Python threads, see
+    /// [`shutdown::shutdown`].
+    Shutdown,
+    /// Create the child WebView `id` showing `html`.
+    AddChildView { id: u64, html: String, bounds: views::ViewBounds },
+    /// Move and resize the child WebView `id`.
+    SetViewBounds { id: u64, bounds: views::ViewBounds },
+    /// Open `url` in a new managed window (see [`new_window`]).
+    OpenWindow { url: String },
+    /// Open the inspector of the window with this id.
+    OpenDevtools(u64),
+    /// Change the title of the window with this id.
+    SetTitle(u64, String),
+    /// Set the zoom factor of the page of the window with this id.
+    SetZoom(u64, f64),
+    /// Move the window with this id to a logical position.
+    SetPosition(u64, tao::dpi::LogicalPosition<f64>),
+    /// Center the window with this id on its monitor.
+    CenterWindow(u64),
+    /// Set the opacity of the window with this id.
+    SetOpacity(u64, f64),
+    /// Reload the page of the window with this id.
+    Reload(u64),
+    /// Delete the cookies, storage and cache of the WebView of window `id`.
+    ClearBrowsingData { id: u64, reply: oneshot::Sender<PyResult<()>> },
+    /// Replace the page of the window with this id.
+    Load(u64, Content),
+    /// Show a file dialog as a modal of the window `id`; `reply` gets the
+    /// chosen paths, `None` if it was cancelled.
+    FileDialog {
+        id: u64,
+        dialog: dialogs::FileDialog,
+        reply: oneshot::Sender<Option<Vec<std::path::PathBuf>>>,
+    },
+    /// Show a message box, as a modal of the window `id` while it is open;
+    /// `reply` gets whether OK was chosen.
+    MessageDialog { id: u64, dialog: dialogs::MessageDialog, reply: oneshot::Sender<bool> },
+    /// Start moving the window hosting this WebView with the mouse, sent
+    /// by `window.start_dragging()`.
+    StartDrag(Target),
+    /// This WebView called `window.ipcReady()`.
+    FrontendReady(Target),
+    /// A key event (JSON) posted by this WebView, for `on_key`.
+    Key(Target, String),
+    /// A menu item with this id was clicked.
+    MenuClicked(String),
+    /// The tray icon was clicked with this button (`"left"`, `"right"` or
+    /// `"middle"`).
+    TrayClicked(&'static str),
+    /// Register a global shortcut added by `register_shortcut`.
+    RegisterShortcut(global_hotkey::hotkey::HotKey),
+    /// Unregister a global shortcut removed by `unregister_shortcut`.
+    UnregisterShortcut(global_hotkey::hotkey::HotKey),
+    /// The global shortcut with this hotkey id was pressed.
+    ShortcutPressed(u32),
+    /// Enter or leave borderless fullscreen on the window `id`; `reply`
+    /// gets the resulting mode.
+    SetFullscreen { id: u64, enabled: bool, reply: oneshot::Sender<bool> },
+    /// Keep the window `id` above other windows, or stop; `reply` gets the
+    /// state read back from the window.
+    SetAlwaysOnTop { id: u64, enabled: bool, reply: oneshot::Sender<bool> },
+    /// List the connected displays.
+    Monitors { reply: oneshot::Sender<Vec<monitors::Monitor>> },
+    /// Read the display showing the window `id`, if known.
+    CurrentMonitor { id: u64, reply: oneshot::Sender<Option<monitors::Monitor>> },
+    /// The document title of the window `id` changed.
+    TitleChanged(u64, String),
+    /// Read the theme of the window `id`.
+    Theme { id: u64, reply: oneshot::Sender<&'static str> },
+    /// Read the scale factor of the monitor showing the window `id`.
+    ScaleFactor { id: u64, reply: oneshot::Sender<f64> },
+    /// Hide, show, minimize or maximize the window `id`; `reply` gets
+    /// whether it is visible afterwards.
+    ControlWindow { id: u64, action: windows::WindowAction, reply: oneshot::Sender<bool> },
+    /// Send the screen area of the window `id` to `reply`, `None` while
+    /// it is not on screen.
+    CaptureRegion { id: u64, reply: oneshot::Sender<Option<capture::Region>> },
+    /// Read or change the cookies of the WebView of window `id`.
+    Cookies {
+        id: u64,
+        op: cookies::CookieOp,
+        reply: oneshot::Sender<PyResult<Vec<wry::cookie::Cookie<'static>>>>,
+    },
+    /// Print the page of the window `id` to a PDF file.
+    PrintToPdf { id: u64, path: std::path::PathBuf, reply: oneshot::Sender<PyResult<()>> },
+    /// Open the window `id` requested by [`spawn_window`].
+    CreateWindow {
+        id: u64,
+        handler: Py<PyAny>,
+        content: Content,
+        config: window_config::WindowConfig,
+        messages: channel::MessageReceiver<Emitted>,
+    },
+}
+
+/// A WebView that IPC replies are evaluated in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    /// The WebView of the window with this id (see [`windows`]).
+    Window(u64),
+    /// The child view with this id (see [`add_child_view`]).
+    View(u64),
+}
+
+/// Outcome of a script evaluation: the JSON-serialized JavaScript value,
+/// or a description of why the evaluation could not be started.
+type EvalResult = Result<String, String>;
+
+/// IPC handler of a single WebView.
+type IpcHandler = Rc<dyn Fn(wry::http::Request<String>)>;
+
+/// New-window handler shared by the main WebView and opened windows.
+type NewWindowHandler = Rc<dyn Fn(String) -> bool>;
+
+/// Navigation handler shared by every WebView.
+type NavigationHandler = Rc<dyn Fn(String) -> bool>;
+
+/// Drag-and-drop handler shared by every WebView.
+type DragDropHandler = Rc<dyn Fn(wry::DragDropEvent) -> bool>;
+
+/// Settings shared by every WebView of the app.
+struct ViewOptions {
+    ipc_format: codec::IpcFormat,
+    max_ipc_body_size: Option<usize>,
+    /// Register [`payloads::SCHEME`] for `large_payload_threshold`.
+    large_payloads: bool,
+    /// Theme of every window, `None` following the OS.
+    theme: Option<tao::window::Theme>,
+    /// Shown by every window and WebView until the page paints.
+    background_color: Option<(u8, u8, u8, u8)>,
+    devtools: bool,
+    incognito: bool,
+    /// Skip TLS certificate checks, see `accept_invalid_certs`.
+    accept_invalid_certs: bool,
+    proxy: Option<wry::ProxyConfig>,
+    /// Inject [`assets::KEYS_SCRIPT`] for `on_key`.
+    key_events: bool,
+    context_menu: bool,
+    text_selection: bool,
+    /// Shared by every WebView when `data_directory` is given.
+    web_context: Option<wry::WebContext>,
+    asset_source: Option<Rc<protocol::AssetSource>>,
+    navigation_handler: Option<NavigationHandler>,
+    drag_drop_handler: Option<DragDropHandler>,
+    user_agent: Option<String>,
+    init_scripts: Vec<String>,
+    /// Proxy reporting document titles, for `auto_title` and
+    /// `on_title_changed`.
+    title_events: Option<tao::event_loop::EventLoopProxy<RuntimeMessage>>,
+}
+
+impl ViewOptions {
+    /// Handler reporting the document titles of the window `id` as
+    /// [`RuntimeMessage::TitleChanged`], if titles are used.
+    fn title_handler(&self, id: u64) -> Option<impl Fn(String) + 'static> {
+        let proxy = self.title_events.clone()?;
+        Some(move |title| {
+            let _ = proxy.send_event(RuntimeMessage::TitleChanged(id, title));
+        })
+    }
+
+    /// Starts a WebView with the initialization scripts, `ipc_handler` and
+    /// the shared settings.
+    ///
+    /// The built-in scripts are registered first, so user scripts can use
+    /// `window.invoke` and friends.
+    fn builder(&mut self, ipc_handler: IpcHandler) -> wry::WebViewBuilder<'_> {
+        // WebKitGTK registers schemes on the shared context, so only the
+        // first WebView may register `app://`; its handler serves them all.
+        let asset_source = match &self.web_context {
+            Some(context) if context.is_custom_protocol_registered(protocol::SCHEME) => None,
+            _ => self.asset_source.as_ref(),
+        };
+        let payload_scheme = self.large_payloads
+            && !self
+                .web_context
+                .as_ref()
+                .is_some_and(|context| context.is_custom_protocol_registered(payloads::SCHEME));
+        let builder = match self.web_context.as_mut() {
+            Some(context) => wry::WebViewBuilder::new_with_web_context(context),
+            None => wry::WebViewBuilder::new(),
+        };
+        let mut builder = builder
+            .with_initialization_script(self.ipc_format.init_script())
+            .with_initialization_script(assets::INITIALIZEPY_SCRIPT);
+        if let Some(max) = self.max_ipc_body_size {
+            builder = builder.with_initialization_script(format!("window.__PYFRAME_MAX_BODY_SIZE__ = {};", max));
+        }
+        if self.key_events {
+            builder = builder.with_initialization_script(assets::KEYS_SCRIPT);
+        }
+        if !self.context_menu {
+            builder = builder.with_initialization_script(assets::NO_CONTEXT_MENU_SCRIPT);
+        }
+        if !self.text_selection {
+            builder = builder.with_initialization_script(assets::NO_TEXT_SELECTION_SCRIPT);
+        }
+        for script in &self.init_scripts {
+            builder = builder.with_initialization_script(script);
+        }
+        let builder = builder
+            .with_ipc_handler(move |req| ipc_handler(req))
+            .with_devtools(self.devtools)
+            .with_incognito(self.incognito);
+        let builder = match &self.proxy {
+            Some(proxy) => builder.with_proxy_config(proxy.clone()),
+            None => builder,
+        };
+        let builder = match self.background_color {
+            Some(color) => builder.with_background_color(color),
+            None => builder,
+        };
+        #[cfg(target_os = "windows")]
+        let builder = {
+            use wry::WebViewBuilderExtWindows;
+            let builder = match self.accept_invalid_certs {
+                true => builder.with_additional_browser_args(certs::webview2_args(self.proxy.as_ref())),
+                false => builder,
+            };
+            builder.with_theme(match self.theme {
+                Some(tao::window::Theme::Dark) => wry::Theme::Dark,
+                Some(_) => wry::Theme::Light,
+                None => wry::Theme::Auto,
+            })
+        };
+        let builder = match &self.user_agent {
+            Some(user_agent) => builder.with_user_agent(user_agent),
+            None => builder,
+        };
+        let builder = match self.navigation_handler.clone() {
+            Some(handler) => builder.with_navigation_handler(move |url| handler(url)),
+            None => builder,
+        };
+        let builder = match self.drag_drop_handler.clone() {
+            Some(handler) => builder.with_drag_drop_handler(move |event| handler(event)),
+            None => builder,
+        };
+        let builder = match payload_scheme {
+            true => payloads::register(builder),
+            false => builder,
+        };
+        protocol::register(builder, asset_source)
+    }
+}
+
+/// Builds a WebView as a child of `window` with the same initialization
+/// scripts as the main view.
+fn build_child_view(
+    window: &tao::window::Window,
+    html: &str,
+    bounds: views::ViewBounds,
+    options: &mut ViewOptions,
+    ipc_handler: IpcHandler,
+) -> wry::Result<wry::WebView> {
+    let webview = options
+        .builder(ipc_handler)
+        .with_html(html)
+        .with_bounds(bounds.to_rect(window.inner_size()))
+        .build_as_child(window)?;
+    if options.accept_invalid_certs {
+        certs::accept_invalid(&webview);
+    }
+    Ok(webview)
+}
+
+/// Opens a window showing `content` with the same initialization scripts
+/// and new-window handler as the main view.
+fn build_window(
+    window_target: &tao::event_loop::EventLoopWindowTarget<RuntimeMessage>,
+    id: u64,
+    config: &window_config::WindowConfig,
+    content: &Content,
+    options: &mut ViewOptions,
+    ipc_handler: IpcHandler,
+    new_window_handler: &NewWindowHandler,
+) -> Result<(wry::WebView, tao::window::Window), String> {
+    let transparent = config.transparent.unwrap_or(false);
+    window_config::warn_transparency_caveats(transparent);
+    let mut builder = config.apply(tao::window::WindowBuilder::new()).with_theme(options.theme);
+    if let Some(color) = options.background_color {
+        builder = builder.with_background_color(color);
+    }
+    let window = builder
+        .build(window_target)
+        .map_err(|e| e.to_string())?;
+    let new_window_handler = new_window_handler.clone();
+    let title_handler = options.title_handler(id);
+    let builder = options
+        .builder(ipc_handler)
+        .with_new_window_req_handler(move |url| new_window_handler(url))
+        .with_transparent(transparent);
+    let builder = match title_handler {
+        Some(handler) => builder.with_document_title_changed_handler(handler),
+        None => builder,
+    };
+    let builder = match content {
+        Content::Html(html) => builder.with_html(html),
+        Content::Url(url) => builder.with_url(url.as_str()),
+    };
+    let webview = builder.build(&window).map_err(|e| e.to_string())?;
+    if options.accept_invalid_certs {
+        certs::accept_invalid(&webview);
+    }
+    Ok((webview, window))
+}
+
+/// The WebView and window of the open window `id`, main or hosted.
+fn find_window<'a>(
+    frame: &'a Option<(wry::WebView, tao::window::Window)>,
+    opened_windows: &'a HashMap<tao::window::WindowId, windows::HostedWindow>,
+    id: u64,
+) -> Option<(&'a wry::WebView, &'a tao::window::Window)> {
+    if id == windows::MAIN_WINDOW_ID {
+        return frame.as_ref().map(|(webview, window)| (webview, window));
+    }
+    opened_windows
+        .values()
+        .find(|hosted| hosted.id == id)
+        .map(|hosted| (&hosted.webview, &hosted.window))
+}
+
+/// Consumes the messages `emit_*` sends to the window `window_id`, until
+/// the window's channel closes. Fails if the replies can no longer reach
+/// Python.
+fn spawn_message_consumer(
+    tasks: &mut tasks::Tasks,
+    runtime: &tokio::runtime::Handle,
+    window_id: u64,
+    mut messages: channel::MessageReceiver<Emitted>,
+    replies: UnboundedSender<&'static str>,
+) {
+    tasks.spawn(runtime, format!("message consumer of window {}", window_id), async move {
+        while let Some(Emitted { message: msg, ack }) = messages.recv().await {
+            log::debug!(
+                "Rust got from window {}: {} (payload: {})",
+                window_id,
+                msg.message,
+                msg.payload
+            );
+            let reply = "pong from rust";
+            if let Some(ack) = ack {
+                // The sender may have timed out already.
+                let _ = ack.send(reply);
+            }
+            // Sending resp back to python
+            replies.send(reply).map_err(|_| "reply channel closed".to_string())?;
+        }
+        Ok(())
+    });
+}
+
+/// What a window's WebView shows.
+enum Content {
+    Html(String),
+    Url(url::Url),
+}
 
+impl Content {
+    /// Picks the content from the `html`, `url` and `base_url` arguments.
+    ///
+    /// # Errors
+    /// Returns `PyValueError` unless exactly one of `html` and `url` is
+    /// given, for an invalid `url`, and for an empty `base_url` or one
+    /// combined with `url`.
+    fn from_args(html: Option<String>, url: Option<String>, base_url: Option<String>) -> PyResult<Self> {
+        match (html, url) {
+            (Some(html), None) => match base_url.as_deref().map(str::trim) {
+                Some("") => Err(pyo3::exceptions::PyValueError::new_err("base_url must not be empty")),
+                Some(base_url) => Ok(Content::Html(html::with_base_url(&html, base_url))),
+                None => Ok(Content::Html(html)),
+            },
+            (None, Some(url)) if base_url.is_none() => Ok(Content::Url(parse_url(&url)?)),
+            (None, Some(_)) => Err(pyo3::exceptions::PyValueError::new_err(
+                "base_url only applies to html, not url",
+            )),
+            _ => Err(pyo3::exceptions::PyValueError::new_err(
+                "Exactly one of html and url must be given",
+            )),
+        }
+    }
+}
+
+/// The empty page of [`create_webframe_blank`].
+const BLANK_URL: &str = "about:blank";
+
+/// Validates a URL passed as `create_webframe(url=...)`.
+///
+/// # Errors
+/// Returns `PyValueError` for malformed URLs and for schemes other than
+/// `http`, `https` and `file`, which also catches a missing scheme such as
+/// `"localhost:5173"`.
+fn parse_url(url: &str) -> PyResult<url::Url> {
+    let parsed = url::Url::parse(url)
+        .map_err(|e| pyo3::exceptions::PyValueError::new_err(format!("Invalid URL {:?}: {}", url, e)))?;
+    match parsed.scheme() {
+        "http" | "https" | "file" => Ok(parsed),
+        "about" if parsed.as_str() == BLANK_URL => Ok(parsed),
+        scheme => Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "Unsupported URL scheme {:?} in {:?} (expected http, https, file or about:blank)",
+            scheme, url
+        ))),
+    }
+}
+
+/// Smallest and largest zoom factors accepted by `set_zoom`.
+const ZOOM_RANGE: std::ops::RangeInclusive<f64> = 0.25..=5.0;
+
+/// Validates a zoom factor passed to `set_zoom` or `create_webframe`.
+///
+/// # Errors
+/// Returns `PyValueError` for factors outside [`ZOOM_RANGE`].
+fn check_zoom(factor: f64) -> PyResult<f64> {
+    if ZOOM_RANGE.contains(&factor) {
+        Ok(factor)
+    } else {
+        Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "Zoom factor {} is out of range ({} to {})",
+            factor,
+            ZOOM_RANGE.start(),
+            ZOOM_RANGE.end()
+        )))
+    }
+}
+
+/// Sends `message` to the running event loop.
+///
+/// # Errors
+/// Returns `FrameError` if no webframe is running or its event loop
+/// has already exited.
+fn send_runtime_message(message: RuntimeMessage) -> PyResult<()> {
+    let proxy = EVENT_LOOP_PROXY
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
+        .ok_or_else(|| errors::FrameError::new_err("Event loop not running"))?;
+    proxy
+        .send_event(message)
+        .map_err(|e| errors::FrameError::new_err(format!("Failed to send message: {}", e)))
+}
+
+/// Closes the main window of `frame`, if open, unless `on_close` vetoes
+/// it: reports the close to `on_window_event`, saves the window geometry
+/// and drops the window with its child views, channel, hot reload watcher
+/// and splash. Returns whether the window closed.
+fn close_main_window(
+    frame: &mut Option<(wry::WebView, tao::window::Window)>,
+    child_views: &mut views::ChildViews,
+    hot_reload: &mut Option<hot_reload::HotReload>,
+    splash: &mut Option<splash::Splash>,
+    on_close: Option<&Py<PyAny>>,
+    on_window_event: Option<&Py<PyAny>>,
+    geometry_store: Option<&geometry::GeometryStore>,
+) -> bool {
+    let Some((_, window)) = frame.as_ref() else {
+        return false;
+    };
+    if !on_close.is_none_or(events::allow_close) {
+        log::info!("Close vetoed by on_close");
+        return false;
+    }
+    if let Some(on_window_event) = on_window_event {
+        events::WindowEventPayload::CloseRequested.send(on_window_event);
+    }
+    if let Some(store) = geometry_store {
+        store.flush(window);
+    }
+    child_views.clear();
+    windows::unregister(windows::MAIN_WINDOW_ID);
+    *frame = None;
+    drop(hot_reload.take());
+    drop(splash.take());
+    true
+}
+
+/// Sends `script` to the WebView for evaluation.
+///
+/// Evaluation goes through `evaluate_script_with_callback`, so the result is
+/// always captured and sent to `reply` when present. Android does not
+/// implement the callback; there the result is reported as `null`.
+fn evaluate(webview: &wry::WebView, script: &str, reply: Option<oneshot::Sender<EvalResult>>) {
+    log::debug!("Evaluating script: {}", script);
+    // The callback may only be `Fn`, so the sender is taken out on first use.
+    let reply = Arc::new(Mutex::new(reply));
+    let send = move |result: EvalResult| {
+        if let Some(reply) = reply.lock().unwrap_or_else(PoisonError::into_inner).take() {
+            let _ = reply.send(result);
+        }
+    };
+
+    #[cfg(not(target_os = "android"))]
+    let result = webview.evaluate_script_with_callback(script, {
+        let send = send.clone();
+        move |value| {
+            log::debug!("Script evaluated successfully: {}", value);
+            send(Ok(value));
+        }
+    });
+
+    #[cfg(target_os = "android")]
+    let result = webview.evaluate_script(script).map(|()| send(Ok("null".to_string())));
+
+    if let Err(e) = result {
+        log::error!("Error evaluating script: {:?}", e);
+        send(Err(e.to_string()));
+    }
+}
+
+/// Lets the non-`Send` event loop and WebView cross into
+/// [`Python::allow_threads`]. The closure still runs on the calling thread,
+/// so nothing is actually moved to another thread.
+struct MainThreadBound<T>(T);
+
+unsafe impl<T> Send for MainThreadBound<T> {}
+
+impl<T> MainThreadBound<T> {
+    fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+/// The arguments of [`create_webframe`], moved to the thread running the
+/// event loop with `blocking=False`.
+struct WebframeArgs {
+    handler: Py<PyAny>,
+    html: Option<String>,
+    url: Option<String>,
+    window_config: Option<Py<PyAny>>,
+    window_state: Option<std::path::PathBuf>,
+    watch_path: Option<std::path::PathBuf>,
+    base_url: Option<String>,
+    asset_root: Option<Py<PyAny>>,
+    icon: Option<Py<PyAny>>,
+    splash: Option<String>,
+    devtools: bool,
+    incognito: bool,
+    accept_invalid_certs: bool,
+    proxy: Option<String>,
+    data_directory: Option<std::path::PathBuf>,
+    zoom: Option<f64>,
+    theme: String,
+    auto_title: bool,
+    background_color: Option<window_config::Color>,
+    context_menu: bool,
+    text_selection: bool,
+    user_agent: Option<String>,
+    init_scripts: Vec<String>,
+    transparent: bool,
+    vibrancy: Option<String>,
+    quit_on_last_window_closed: bool,
+    handle_sigint: bool,
+    runtime_threads: Option<usize>,
+    on_occlusion_change: Option<Py<PyAny>>,
+    on_focus: Option<Py<PyAny>>,
+    on_title_changed: Option<Py<PyAny>>,
+    on_window_event: Option<Py<PyAny>>,
+    on_key: Option<Py<PyAny>>,
+    on_close: Option<Py<PyAny>>,
+    on_error: Option<Py<PyAny>>,
+    ipc_format: String,
+    binary_ipc: bool,
+    max_ipc_body_size: Option<usize>,
+    large_payload_threshold: Option<usize>,
+    message_capacity: Option<usize>,
+    message_validation: String,
+    auto_timestamp: bool,
+    py_event_loop: Option<Py<PyAny>>,
+    pyevent_to_rust_queue: Option<Py<PyAny>>,
+    rust_to_py_ipc: Option<Py<PyAny>>,
+    python_loop_policy: String,
+    on_python_crash: Option<Py<PyAny>>,
+    heartbeat: Option<f64>,
+    on_stall: Option<Py<PyAny>>,
+    on_new_window: Option<Py<PyAny>>,
+    on_navigation: Option<Py<PyAny>>,
+    on_file_drop: Option<Py<PyAny>>,
+    on_file_hover: Option<Py<PyAny>>,
+    menu: Option<Py<PyAny>>,
+    on_menu: Option<Py<PyAny>>,
+    tray: Option<Py<PyAny>>,
+    on_tray_click: Option<Py<PyAny>>,
+}
 
 /// Creates a native window with an embedded WebView.
 ///
 /// This function:
 /// - Initializes a Tao event loop,
-/// - Builds a [`tao::window::Window`] titled `"PyFrame"`,
+/// - Builds a [`tao::window::Window`] from `window_config`,
 /// - Builds a [`wry::WebView`] with:
-///   - provided HTML content (`html` parameter),
+///   - provided HTML content (`html` parameter) or a page loaded from `url`,
 ///   - an IPC handler that forwards messages to Python,
-/// - Starts the event loop (`event_loop.run`).
+/// - Starts the event loop (`event_loop.run`) with the GIL released, so other
+///   Python threads keep running while the window is open.
+///
+/// Since the call does not return while the window is open, the window's
+/// id, as passed to [`emit_str`], is the constant `MAIN_WINDOW_ID`.
+///
+/// With `blocking=False`, the event loop runs on a new thread instead and
+/// the call returns a [`handle::FrameHandle`] once the window is up. This
+/// is not possible on macOS, where the event loop must run on the main
+/// thread, nor on Android and iOS.
 ///
 /// # Parameters
-/// - `handler`: A Python callable that receives IPC messages as JSON.
+/// - `handler`: A Python callable that receives IPC messages as JSON. It
+///   runs on a dedicated IPC thread, one request at a time in the order
+///   the pages posted them, so a slow handler delays later requests but
+///   never freezes the windows. Coroutines it returns run on the asyncio
+///   loop and complete independently. Async generators it returns stream
+///   their items to `window.invokeStream`/`window.ipcStream` iterators.
 /// - `html`: The HTML string to render inside the WebView.
+/// - `url`: A page to load instead of `html`, e.g. a dev server such as
+///   `http://localhost:5173`. Exactly one of `html` and `url` is required.
+/// - `window_config`: Dict of window options: `title` (`"PyFrame"`),
+///   `width`/`height` (800x600 logical pixels), `x`/`y` (placed by the
+///   platform), `resizable` (`True`), `closable`, `minimizable` and
+///   `maximizable` (`True`, the title bar buttons; Linux cannot hide the
+///   last two on their own), `decorations` (`True`, `False` for
+///   custom title bars), `always_on_top` (`False`), `transparent`
+///   (`False`, like the `transparent` argument, which it must not
+///   contradict) and `min_size`/`max_size`
+///   (`[width, height]` bounds for resizing, unbounded). Missing keys take
+///   these defaults.
+/// - `window_state`: Path of a JSON file remembering the main window's size
+///   and position. Read at startup, overriding `window_config`; rewritten a
+///   moment after the window stops moving or resizing, and when it closes.
+///   A missing or corrupt file is ignored.
+/// - `watch_path`: Directory (or file) to watch during development; the
+///   main window reloads shortly after anything under it changes. Reloading
+///   shows `url` or `asset_root` afresh, but not an edited `html` string.
+///   The watcher stops when the main window closes.
+/// - `base_url`: URL that relative URLs in `html` resolve against (e.g. a
+///   CDN root), applied by inserting a `<base href>` element. Without it
+///   they resolve against `about:blank`.
+/// - `asset_root`: Directory served under the `app://` scheme, or a
+///   callable `asset_root(path)` returning `bytes`, `str` or `None` (not
+///   found). The content type follows the file extension. Without `html`
+///   and `url`, the window loads `index.html` from it.
+/// - `icon`: Window and taskbar icon, as a path to a PNG, ICO or JPEG
+///   file, its contents as `bytes`, or an `(rgba, width, height)` tuple of
+///   raw pixels. No icon by default.
+/// - `splash`: HTML shown in a small borderless window while the main
+///   window loads hidden, for frontends with a slow start. The page
+///   dismisses it by calling `window.ipcReady()` once it is rendered, which
+///   also shows the main window; until then, the main window stays hidden.
+///   Closing the splash shows the main window as well.
+/// - `devtools`: Allow opening the web inspector, from the context menu or
+///   with [`open_devtools`]. Off by default so shipped apps do not expose it.
+/// - `incognito`: Keep cookies, storage and cache in memory only, so every
+///   run starts from a clean profile and leaves nothing on disk, e.g. for
+///   test isolation. Needs WebView2 Runtime 101 or newer on Windows, where
+///   older runtimes ignore it; WebKitGTK and WKWebView use an ephemeral
+///   data store.
+/// - `accept_invalid_certs`: INSECURE, for development only. Load https
+///   pages whose certificate is self-signed, expired or for another host,
+///   e.g. a local dev server, instead of failing navigation. This disables
+///   the protection https gives against impersonation, for every page of
+///   every window, so never ship it. Off by default; turning it on logs a
+///   warning. Supported by WebView2 and WebKitGTK; elsewhere it only warns.
+/// - `proxy`: Proxy every WebView's traffic goes through, as
+///   `http://host:port` (HTTP CONNECT) or `socks5://host:port`, e.g. to
+///   reach the network from behind a corporate firewall. Credentials are
+///   not supported. Honored by WebView2 and WebKitGTK; elsewhere a warning
+///   is logged and traffic goes out directly.
+/// - `data_directory`: Directory holding the profile of every WebView
+///   (cookies, storage, cache), created if missing, e.g. to keep a portable
+///   app's data next to it or to give each account its own profile. Without
+///   it, the backend's default location is used. Ignored with `incognito`.
+///   WKWebView (macOS, iOS) always uses the default data store.
+/// - `zoom`: Initial zoom factor of the main window's page, as with
+///   [`set_zoom`]. 1.0 by default.
+/// - `theme`: Appearance of the windows' title bars and native controls,
+///   and the page's `prefers-color-scheme` on Windows: `"light"`, `"dark"`
+///   or `"system"` (default; follows the OS, including later changes).
+///   See [`get_theme`].
+/// - `auto_title`: Mirror the page's `<title>` in the title bar of each
+///   window, like a browser (`False` by default, keeping `window_config`'s
+///   title and [`set_window_title`]). Empty titles are ignored.
+/// - `background_color`: Color shown by the windows and WebViews until the
+///   page paints, avoiding a white flash in dark apps: `"#rrggbb"`,
+///   `"#rrggbbaa"` or an `(r, g, b[, a])` tuple. By default the platform's
+///   (usually white). macOS does not support it for the WebView, and
+///   Windows ignores alpha for the window and only honors 0 (transparent)
+///   or 255 for the WebView. `transparent` takes precedence.
+/// - `context_menu`: Show the WebView's right-click menu (default). Pass
+///   `False` for kiosk and app-like UIs; pages still get `contextmenu`
+///   events to show their own menus.
+/// - `text_selection`: Let the user select text (default). With `False`,
+///   only form fields and editable elements stay selectable.
+/// - `user_agent`: User agent string of every WebView, replacing the
+///   backend's default.
+/// - `init_scripts`: JavaScript sources run in every WebView before each
+///   page loads, e.g. polyfills or config constants. They run after the
+///   built-in bridge script, so `window.invoke` is already defined, and in
+///   list order. Pages loaded by navigation run them again; iframes do not.
+/// - `transparent`: Makes both the window and the WebView background
+///   transparent. On Linux this needs a compositing window manager; without
+///   one the window stays opaque and a warning is logged.
+/// - `vibrancy`: Backdrop material drawn behind the window (`"sidebar"`,
+///   `"menu"`, `"hud_window"`, ... on macOS; `"blur"`, `"acrylic"`, `"mica"`,
+///   `"tabbed"` on Windows). Implies `transparent`. Materials the platform
+///   does not support are logged and ignored.
+/// - `quit_on_last_window_closed`: Exit once the last window (including
+///   windows opened through `on_new_window`) is closed (default). When
+///   `False`, closed windows are destroyed but the process stays alive until
+///   [`quit_app`] is called, following the macOS convention of apps
+///   outliving their windows.
+/// - `handle_sigint`: Exit the event loop on Ctrl+C (default), which would
+///   otherwise go unnoticed while the loop owns the thread: SIGINT on
+///   Unix, the console's Ctrl+C event on Windows. `on_close` and
+///   `on_window_event` are called as for closing the main window, but a
+///   veto is ignored. A second Ctrl+C ends the process with status 130.
+///   Python's own handler still runs, so `KeyboardInterrupt` may be raised
+///   once `create_webframe` returns. Pass `False` to handle signals in
+///   Python, e.g. with `blocking=False`.
+/// - `runtime_threads`: Worker threads of the tokio runtime running the
+///   webframe's background tasks (message consumers, reply forwarding,
+///   IPC replies). By default a runtime with one worker per CPU core is
+///   started for each webframe, more than a single window needs. A small
+///   number, e.g. `1`, saves threads and memory, at the cost of one slow
+///   task delaying the others. `0` starts none and shares the runtime
+///   behind the awaitables of `eval_js` and friends, which lives for the
+///   whole process; when embedding the frame in a larger service, this
+///   keeps one runtime per process.
+/// - `on_occlusion_change`: Called as `on_occlusion_change(visible)` when the
+///   window gets hidden or minimized and when it becomes visible again.
+///   No platform reports true occlusion through Tao, so a window covered by
+///   other windows still counts as visible.
+/// - `on_focus`: Called as `on_focus(focused)` when the main window gains
+///   or loses keyboard focus, e.g. to pause animations or polling while
+///   in the background.
+/// - `on_title_changed`: Called as `on_title_changed(title)` when the
+///   document title of the main window changes, e.g. to mirror it
+///   elsewhere than in the title bar (see `auto_title`).
+/// - `on_window_event`: Called with a JSON string when the main window is
+///   resized (`{"type": "resized", "width", "height"}`), moved
+///   (`{"type": "moved", "x", "y"}`, logical pixels) or is about to close
+///   (`{"type": "close_requested"}`, before the event loop exits, so
+///   cleanup code can run), after [`set_fullscreen`]
+///   (`{"type": "fullscreen_changed", "fullscreen"}`), and when it moves
+///   to a monitor with another DPI (`{"type": "scale_factor_changed",
+///   "scale_factor", "width", "height"}`, the new inner size), or its
+///   theme changes (`{"type": "theme_changed", "theme"}`) or focus
+///   (`{"type": "focused", "focused"}`).
+/// - `on_key`: Called with a JSON string for every key pressed
+///   (`"keydown"`) or released (`"keyup"`) in any WebView:
+///   `{"type", "key", "code", "modifiers": {"alt", "ctrl", "meta",
+///   "shift"}, "repeat"}`, with `key`/`code` as in the DOM `KeyboardEvent`.
+///   It sees keys before the page does, e.g. for command palettes.
+///   Returning `True` marks the combination (type, code and modifiers) as
+///   handled: the page cannot wait for Python, so it still gets the press
+///   being answered, but not the following ones until `on_key` stops
+///   returning `True` for them.
+/// - `on_close`: Called without arguments when the main window is about to
+///   close, by the user or [`close_window`]. Returning `False` keeps it
+///   open, e.g. to confirm unsaved changes; exceptions are logged and let
+///   the window close. Not consulted by [`quit_app`].
+/// - `on_error`: Called with the formatted traceback (a `str`) whenever
+///   `handler`, or a coroutine it returned, raises. The error is still logged
+///   and rejects the page's `invoke` promise.
+/// - `ipc_format`: Wire format of IPC payloads, `"json"` (default) or
+///   `"msgpack"`. With `"msgpack"`, `handler` receives the request as
+///   MessagePack `bytes` and `window.invoke` encodes its messages to match.
+/// - `binary_ipc`: Accept binary data posted with
+///   `window.ipcPostBinary(data)`. The handler receives it as a request with
+///   `binary` set and the raw bytes as `body` (base64 `str` in JSON mode).
+///   Off by default, so such messages otherwise arrive as plain text.
+/// - `max_ipc_body_size`: Largest body, in bytes, a page may post (64 MiB
+///   by default; `None` for no limit). Larger messages never reach
+///   `handler`: `window.invoke` and `window.ipcPostBinary` throw before
+///   posting them, and bodies posted otherwise are dropped and reported to
+///   `on_error`. Binary and MessagePack bodies count in base64.
+/// - `large_payload_threshold`: [`emit_to_js`] payloads of at least this
+///   many bytes of JSON (1 MiB by default) are not inlined into the script
+///   evaluated in the page: the page fetches them from the `pyframe://`
+///   scheme instead and parses them with `Response.json()`, off the
+///   script compiler, so chart and table data of several megabytes does
+///   not stall the page. Events are still dispatched in emit order. The
+///   default is a conservative starting point rather than a measured
+///   crossover, which depends on the engine and the data: time your own
+///   payloads and lower it if large emits stutter. `None` always inlines.
+/// - `message_capacity`: Number of [`emit_str`]/[`emit_async`] messages
+///   that may wait for the Rust consumer. Unbounded by default; once a
+///   bounded channel is full, `emit_str` raises and `emit_async` waits.
+/// - `message_validation`: How strictly `emit_*` check messages, to catch
+///   drift between what the front end sends and what Rust expects. `"off"`
+///   (default) accepts anything that decodes, dropping unknown fields;
+///   `"timestamps"` also requires `timestamp`, when given, to be RFC 3339
+///   (e.g. `"2024-05-01T12:30:00Z"`); `"strict"` also rejects fields other
+///   than `message`, `timestamp` and `payload`. Violations raise
+///   `PyValueError` from the emit. Applies to every window, until the next
+///   `create_webframe`.
+/// - `auto_timestamp`: Give messages emitted without a `timestamp` the
+///   time of the emit, as UTC RFC 3339 with milliseconds (e.g.
+///   `"2024-05-01T12:30:00.250Z"`), so every message can be logged and
+///   ordered by time (default). Pass `False` to keep them without one.
+/// - `pyevent_to_rust_queue`, `rust_to_py_ipc`: Coroutine functions run on a
+///   Python asyncio loop in a background thread. They are called with a
+///   `SenderHandle` (Python → Rust) and a `ReceiverHandle` (Rust → Python)
+///   respectively, and must be given together. The contract:
+///   - Each must return a coroutine; both are scheduled as tasks on the
+///     loop, once per run of the loop (again after a restart).
+///   - They are expected to run for the life of the loop. Returning or
+///     raising ends only that task; call `loop.stop()` to end the thread
+///     and apply `python_loop_policy`.
+///   - The handles block: `recv_blocking`, `recv_timeout` and
+///     `send_and_wait` stall every task on the loop while they wait, so
+///     call them through `loop.run_in_executor` or poll `recv()` between
+///     `await asyncio.sleep(...)`.
+///   - The `ReceiverHandle` yields the replies to [`emit_str`] messages as
+///     `str`; objects sent through the `SenderHandle` are consumed by Rust,
+///     which answers `send_and_wait` with the object itself.
+/// - `py_event_loop`: The asyncio loop to run them on; a new loop if omitted.
+///   Coroutines returned by `handler` are scheduled on this loop as well
+///   (on a private loop without these options), and fail with
+///   `RuntimeError` while it is not running.
+/// - `python_loop_policy`: What happens when that thread exits, cleanly or
+///   by raising: `"restart"` (default) starts it again on a new loop,
+///   `"shutdown"` quits the application. See [`python_loop_state`]. A
+///   thread exiting within 5 seconds of starting is restarted after a
+///   delay that doubles with each such exit, from 1 up to 30 seconds, so a
+///   loop crashing on startup does not spin; one that ran longer restarts
+///   at once.
+/// - `on_python_crash`: Called as `on_python_crash(error, restarting)` from
+///   a Rust thread when that thread dies by raising or panicking, with the
+///   formatted traceback and whether `python_loop_policy` restarts it, e.g.
+///   to alert or to save state. Runs before the restart delay. Without it,
+///   crashes are logged.
+/// - `heartbeat`: Interval in seconds at which that thread's
+///   `ReceiverHandle` gets a `"pyframe-ping:<n>"` string, to detect a
+///   stalled loop. The `rust_to_py_ipc` coroutine must send each ping back
+///   unchanged through the `SenderHandle`. A ping still unanswered when the
+///   next one is due counts as a stall. Off by default.
+/// - `on_stall`: Called as `on_stall(seconds)` from a Rust thread, once per
+///   stall, with the time since the last answered ping. Without it, stalls
+///   are logged as warnings.
+/// - `on_new_window`: Called as `on_new_window(url)` when the page calls
+///   `window.open` or follows a `target="_blank"` link. Returns `"browser"`
+///   to open the system browser (the default, also without a callback),
+///   `"webframe"` to open a new managed window, or `"deny"`. Not reported
+///   on Android.
+/// - `on_navigation`: Called as `on_navigation(url)` before any WebView
+///   navigates, including to its initial `url`. Returning `False` blocks
+///   the navigation, e.g. to confine the app to trusted origins; exceptions
+///   block it too. All navigation is allowed without a callback.
+/// - `on_file_drop`: Called as `on_file_drop(paths)` with the list of
+///   paths of the files dropped onto a WebView, once per drop.
+/// - `on_file_hover`: Called as `on_file_hover(paths)` when files are
+///   dragged over a WebView, and as `on_file_hover(None)` once they are
+///   dropped or the drag leaves or is cancelled.
+/// - `menu`: Native menu bar, as a list of submenus
+///   `{"title": "File", "items": [...]}`. Items are
+///   `{"id", "text", "accelerator", "enabled"}` dicts (e.g. accelerator
+///   `"CmdOrCtrl+O"`; the last two optional), nested submenus,
+///   `"separator"`, or platform items such as `{"predefined": "copy"}`
+///   (`cut`, `paste`, `select_all`, `undo`, `redo`, `minimize`, `quit`,
+///   ...). On macOS the menu belongs to the app rather than the window.
+/// - `on_menu`: Called as `on_menu(item_id)` when a menu item is clicked
+///   or its accelerator pressed.
+/// - `tray`: System tray icon, as a dict with `icon` (like `icon`), and
+///   optionally `tooltip`, `menu` (a context menu, entries like `menu`'s,
+///   reported to `on_menu`) and `toggle_window` (default `True`: a left
+///   click shows or hides the main window instead of opening the menu).
+/// - `on_tray_click`: Called as `on_tray_click(button)` with `"left"`,
+///   `"right"` or `"middle"` when the tray icon is clicked.
+/// - `blocking`: `False` runs the event loop on a background thread and
+///   returns a `FrameHandle` to `join()` or `quit()` it; see above. When
+///   that loop exits, the process keeps running.
 ///
 /// # Errors
-/// - Returns `PyOSError` if the window cannot be created.
-/// - Returns `PyRuntimeError` if WebView creation fails.
+/// - Returns `PyValueError` if `window_config` has unknown keys, a width or
+///   height that is not positive, only one of `x` and `y`, a `min_size`
+///   larger than `max_size`, or `resizable=False` with a `min_size` or
+///   `max_size` other than the window size.
+/// - Returns `PyValueError` unless exactly one of `html` and `url` is given,
+///   if `url` is malformed or not `http`, `https`, `file` or `about:blank`,
+///   or if `base_url` is combined with `url`.
+/// - Returns `PyValueError` if `base_url` or `user_agent` is empty.
+/// - Returns `PyValueError` if `asset_root` is not a directory or
+///   `watch_path` does not exist.
+/// - Returns `PyOSError` if `watch_path` cannot be watched.
+/// - Returns `PyValueError` if `icon` cannot be read or decoded, or its RGBA
+///   data does not match its size.
+/// - Returns `PyValueError` if `vibrancy` names an unknown material, or if
+///   `window_config` sets `transparent` to `False` while `transparent` or
+///   `vibrancy` is given.
+/// - Returns `PyRuntimeError` if the tokio runtime cannot be started.
+/// - Returns `PyValueError` if `message_capacity`, `max_ipc_body_size` or
+///   `large_payload_threshold` is zero.
+/// - Returns `PyValueError` if `menu` is malformed, names an unknown
+///   predefined item, or has an invalid accelerator.
+/// - Returns `PyValueError` if `tray` has unknown keys, no or an invalid
+///   icon, or a malformed menu.
+/// - Returns `PyRuntimeError` if the platform has no system tray, e.g. a
+///   Linux desktop without AppIndicator support.
+/// - Returns `PyValueError` if `ipc_format` is neither `"json"` nor `"msgpack"`,
+///   or `theme` or `message_validation` is unknown.
+/// - Returns `PyValueError` if `background_color` is malformed.
+/// - Returns `PyValueError` if `proxy` is not an `http` or `socks5` URL
+///   with a host and port.
+/// - Returns `PyValueError` if only one of `pyevent_to_rust_queue` and
+///   `rust_to_py_ipc` is given, `py_event_loop` is given without them, or
+///   `python_loop_policy` is unknown, and if `heartbeat` is not positive or
+///   it or `on_python_crash` is given without them.
+/// - Returns `WindowError` if the window cannot be created.
+/// - Returns `WebViewError` if WebView creation fails.
+/// - Returns `FrameError` if a webframe is already running.
+/// - Returns `PyNotImplementedError` for `blocking=False` on macOS, Android
+///   and iOS.
 ///
-pub enum RuntimeMessage {
-    Eval(String),
+#[pyfunction]
+#[allow(clippy::too_many_arguments)]
+#[pyo3(signature = (
+    handler,
+    html = None,
+    *,
+    url = None,
+    window_config = None,
+    window_state = None,
+    watch_path = None,
+    base_url = None,
+    asset_root = None,
+    icon = None,
+    splash = None,
+    devtools = false,
+    incognito = false,
+    accept_invalid_certs = false,
+    proxy = None,
+    data_directory = None,
+    zoom = None,
+    theme = "system",
+    auto_title = false,
+    background_color = None,
+    context_menu = true,
+    text_selection = true,
+    user_agent = None,
+    init_scripts = Vec::new(),
+    transparent = false,
+    vibrancy = None,
+    quit_on_last_window_closed = true,
+    handle_sigint = true,
+    runtime_threads = None,
+    on_occlusion_change = None,
+    on_focus = None,
+    on_title_changed = None,
+    on_window_event = None,
+    on_key = None,
+    on_close = None,
+    on_error = None,
+    ipc_format = "json",
+    binary_ipc = false,
+    max_ipc_body_size = Some(ipc_req::DEFAULT_MAX_BODY_SIZE),
+    large_payload_threshold = Some(payloads::DEFAULT_THRESHOLD),
+    message_capacity = None,
+    message_validation = "off",
+    auto_timestamp = true,
+    py_event_loop = None,
+    pyevent_to_rust_queue = None,
+    rust_to_py_ipc = None,
+    python_loop_policy = "restart",
+    on_python_crash = None,
+    heartbeat = None,
+    on_stall = None,
+    on_new_window = None,
+    on_navigation = None,
+    on_file_drop = None,
+    on_file_hover = None,
+    menu = None,
+    on_menu = None,
+    tray = None,
+    on_tray_click = None,
+    blocking = true,
+))]
+fn create_webframe(
+    py: Python<'_>,
+    handler: Py<PyAny>,
+    html: Option<String>,
+    url: Option<String>,
+    window_config: Option<Py<PyAny>>,
+    window_state: Option<std::path::PathBuf>,
+    watch_path: Option<std::path::PathBuf>,
+    base_url: Option<String>,
+    asset_root: Option<Py<PyAny>>,
+    icon: Option<Py<PyAny>>,
+    splash: Option<String>,
+    devtools: bool,
+    incognito: bool,
+    accept_invalid_certs: bool,
+    proxy: Option<String>,
+    data_directory: Option<std::path::PathBuf>,
+    zoom: Option<f64>,
+    theme: &str,
+    auto_title: bool,
+    background_color: Option<window_config::Color>,
+    context_menu: bool,
+    text_selection: bool,
+    user_agent: Option<String>,
+    init_scripts: Vec<String>,
+    transparent: bool,
+    vibrancy: Option<String>,
+    quit_on_last_window_closed: bool,
+    handle_sigint: bool,
+    runtime_threads: Option<usize>,
+    on_occlusion_change: Option<Py<PyAny>>,
+    on_focus: Option<Py<PyAny>>,
+    on_title_changed: Option<Py<PyAny>>,
+    on_window_event: Option<Py<PyAny>>,
+    on_key: Option<Py<PyAny>>,
+    on_close: Option<Py<PyAny>>,
+    on_error: Option<Py<PyAny>>,
+    ipc_format: &str,
+    binary_ipc: bool,
+    max_ipc_body_size: Option<usize>,
+    large_payload_threshold: Option<usize>,
+    message_capacity: Option<usize>,
+    message_validation: &str,
+    auto_timestamp: bool,
+    py_event_loop: Option<Py<PyAny>>,
+    pyevent_to_rust_queue: Option<Py<PyAny>>,
+    rust_to_py_ipc: Option<Py<PyAny>>,
+    python_loop_policy: &str,
+    on_python_crash: Option<Py<PyAny>>,
+    heartbeat: Option<f64>,
+    on_stall: Option<Py<PyAny>>,
+    on_new_window: Option<Py<PyAny>>,
+    on_navigation: Option<Py<PyAny>>,
+    on_file_drop: Option<Py<PyAny>>,
+    on_file_hover: Option<Py<PyAny>>,
+    menu: Option<Py<PyAny>>,
+    on_menu: Option<Py<PyAny>>,
+    tray: Option<Py<PyAny>>,
+    on_tray_click: Option<Py<PyAny>>,
+    blocking: bool,
+) -> PyResult<Option<handle::FrameHandle>> {
+    // tao supports a single event loop per process.
+    if EVENT_LOOP_PROXY.lock().unwrap_or_else(PoisonError::into_inner).is_some() {
+        return Err(errors::FrameError::new_err("A webframe is already running"));
+    }
+    let args = WebframeArgs {
+        handler,
+        html,
+        url,
+        window_config,
+        window_state,
+        watch_path,
+        base_url,
+        asset_root,
+        icon,
+        splash,
+        devtools,
+        incognito,
+        accept_invalid_certs,
+        proxy,
+        data_directory,
+        zoom,
+        theme: theme.to_string(),
+        auto_title,
+        background_color,
+        context_menu,
+        text_selection,
+        user_agent,
+        init_scripts,
+        transparent,
+        vibrancy,
+        quit_on_last_window_closed,
+        handle_sigint,
+        runtime_threads,
+        on_occlusion_change,
+        on_focus,
+        on_title_changed,
+        on_window_event,
+        on_key,
+        on_close,
+        on_error,
+        ipc_format: ipc_format.to_string(),
+        binary_ipc,
+        max_ipc_body_size,
+        large_payload_threshold,
+        message_capacity,
+        message_validation: message_validation.to_string(),
+        auto_timestamp,
+        py_event_loop,
+        pyevent_to_rust_queue,
+        rust_to_py_ipc,
+        python_loop_policy: python_loop_policy.to_string(),
+        on_python_crash,
+        heartbeat,
+        on_stall,
+        on_new_window,
+        on_navigation,
+        on_file_drop,
+        on_file_hover,
+        menu,
+        on_menu,
+        tray,
+        on_tray_click,
+    };
+    if blocking {
+        run_webframe(py, args, None)?;
+        return Ok(None);
+    }
+    handle::spawn(py, args).map(Some)
 }
 
+/// Like [`create_webframe`], but the main window opens on an empty page
+/// (`about:blank`) for the content to be pushed later, once it is known,
+/// with [`load_html`] or [`load_url`]. Every keyword argument of
+/// `create_webframe` is accepted except `html`, `url`, `base_url` and
+/// `asset_root`.
+///
+/// The lifecycle is that of `create_webframe`: with `blocking=True` (the
+/// default) this returns once the event loop exits, so the content is
+/// loaded from a callback, e.g. `on_window_event`, or another thread; with
+/// `blocking=False` it returns a `FrameHandle` once the window is up, and
+/// `load_html`/`load_url` can follow directly. `handler` stays bound
+/// throughout: the IPC bridge and `init_scripts` are injected into every
+/// page loaded later.
+///
+/// # Errors
+/// - Returns `PyValueError` if one of the content arguments is given.
+/// - Returns the errors of `create_webframe` otherwise.
 #[pyfunction]
-fn create_webframe(handler: Py<PyAny>, html: String) -> PyResult<()> {
-    let event_loop = EventLoopBuilder::<RuntimeMessage>::with_user_event().build();
+#[pyo3(signature = (handler, **kwargs))]
+fn create_webframe_blank<'py>(
+    py: Python<'py>,
+    handler: Py<PyAny>,
+    kwargs: Option<&Bound<'py, pyo3::types::PyDict>>,
+) -> PyResult<Bound<'py, PyAny>> {
+    let kwargs = match kwargs {
+        Some(kwargs) => kwargs.copy()?,
+        None => pyo3::types::PyDict::new(py),
+    };
+    for name in ["html", "url", "base_url", "asset_root"] {
+        if kwargs.contains(name)? {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "create_webframe_blank does not take {}; load content with load_html or load_url",
+                name
+            )));
+        }
+    }
+    kwargs.set_item("url", BLANK_URL)?;
+    wrap_pyfunction!(create_webframe, py)?.call((handler,), Some(&kwargs))
+}
+
+/// Builds the main window from `args` and runs the event loop on this
+/// thread. With `ready`, the loop was started by [`handle::spawn`]: it
+/// gets `Ok` once the window is up, and the loop returns when it exits.
+fn run_webframe(
+    py: Python<'_>,
+    args: WebframeArgs,
+    ready: Option<&crossbeam_channel::Sender<PyResult<()>>>,
+) -> PyResult<()> {
+    let WebframeArgs {
+        handler,
+        html,
+        url,
+        window_config,
+        window_state,
+        watch_path,
+        base_url,
+        asset_root,
+        icon,
+        splash,
+        devtools,
+        incognito,
+        accept_invalid_certs,
+        proxy,
+        data_directory,
+        zoom,
+        theme,
+        auto_title,
+        background_color,
+        context_menu,
+        text_selection,
+        user_agent,
+        init_scripts,
+        transparent,
+        vibrancy,
+        quit_on_last_window_closed,
+        handle_sigint,
+        runtime_threads,
+        on_occlusion_change,
+        on_focus,
+        on_title_changed,
+        on_window_event,
+        on_key,
+        on_close,
+        on_error,
+        ipc_format,
+        binary_ipc,
+        max_ipc_body_size,
+        large_payload_threshold,
+        message_capacity,
+        message_validation,
+        auto_timestamp,
+        py_event_loop,
+        pyevent_to_rust_queue,
+        rust_to_py_ipc,
+        python_loop_policy,
+        on_python_crash,
+        heartbeat,
+        on_stall,
+        on_new_window,
+        on_navigation,
+        on_file_drop,
+        on_file_hover,
+        menu,
+        on_menu,
+        tray,
+        on_tray_click,
+    } = args;
+    let mut window_config = window_config::WindowConfig::from_py(py, window_config)?;
+    if let Some(geometry) = window_state.as_deref().and_then(geometry::load) {
+        geometry.apply(&mut window_config);
+    }
+    let asset_source = asset_root
+        .map(|root| protocol::AssetSource::from_py(py, root))
+        .transpose()?
+        .map(Rc::new);
+    let content = match (&asset_source, &html, &url, &base_url) {
+        (Some(_), None, None, None) => Content::Url(protocol::index_url()),
+        _ => Content::from_args(html, url, base_url)?,
+    };
+    if user_agent.as_deref().is_some_and(|user_agent| user_agent.trim().is_empty()) {
+        return Err(pyo3::exceptions::PyValueError::new_err("user_agent must not be empty"));
+    }
+    let icon = icon.map(|icon| icon::from_py(py, icon)).transpose()?;
+    let zoom = zoom.map(check_zoom).transpose()?;
+    let theme = window_config::parse_theme(&theme)?;
+    let background_color = background_color.map(|color| color.rgba()).transpose()?;
+    if max_ipc_body_size == Some(0) {
+        return Err(pyo3::exceptions::PyValueError::new_err("max_ipc_body_size must be positive"));
+    }
+    if large_payload_threshold == Some(0) {
+        return Err(pyo3::exceptions::PyValueError::new_err("large_payload_threshold must be positive"));
+    }
+    let web_context = data_directory
+        .map(|dir| {
+            std::fs::create_dir_all(&dir).and_then(|()| std::path::absolute(&dir)).map_err(|e| {
+                pyo3::exceptions::PyValueError::new_err(format!(
+                    "Cannot use data_directory {}: {}",
+                    dir.display(),
+                    e
+                ))
+            })
+        })
+        .transpose()?
+        .map(|dir| wry::WebContext::new(Some(dir)));
+    let vibrancy = vibrancy.as_deref().map(vibrancy::Vibrancy::parse).transpose()?;
+    let proxy_server = proxy.as_deref().map(proxy_config::parse).transpose()?;
+    if proxy_server.is_some() {
+        proxy_config::warn_unsupported();
+    }
+    let ipc_format = codec::IpcFormat::parse(&ipc_format)?;
+    let message_validation = schema::Validation::parse(&message_validation)?;
+    let python_loop_policy = py_sender::RestartPolicy::parse(&python_loop_policy)?;
+    let python_loop = match (pyevent_to_rust_queue, rust_to_py_ipc) {
+        (Some(queue), Some(ipc)) => Some((queue, ipc)),
+        (None, None) if py_event_loop.is_none() => None,
+        _ => {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "pyevent_to_rust_queue and rust_to_py_ipc must be given together",
+            ))
+        }
+    };
+    if on_python_crash.is_some() && python_loop.is_none() {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "on_python_crash needs pyevent_to_rust_queue and rust_to_py_ipc",
+        ));
+    }
+    let heartbeat = match heartbeat {
+        None => None,
+        Some(_) if python_loop.is_none() => {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "heartbeat needs pyevent_to_rust_queue and rust_to_py_ipc",
+            ))
+        }
+        Some(seconds) => Some(
+            std::time::Duration::try_from_secs_f64(seconds)
+                .ok()
+                .filter(|interval| !interval.is_zero())
+                .ok_or_else(|| {
+                    pyo3::exceptions::PyValueError::new_err(format!("heartbeat must be positive, got {}", seconds))
+                })?,
+        ),
+    };
+    let transparent = transparent || vibrancy.is_some();
+    if window_config.transparent == Some(false) && transparent {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "transparent=True or vibrancy conflicts with window_config transparent=False",
+        ));
+    }
+    let transparent = transparent || window_config.transparent == Some(true);
+    window_config::warn_transparency_caveats(transparent);
+    let (py_to_rust_tx, py_to_rust_rx) = channel::message_channel::<Emitted>(message_capacity)?;
+
+    let menu_bar = menu
+        .map(|menu| menu::parse_entries(py, menu).and_then(|entries| menu::build_bar(&entries)))
+        .transpose()?;
+    let tray = tray.map(|tray| tray::TraySpec::from_py(py, tray)).transpose()?;
+    let tray_toggles_window = tray.as_ref().is_some_and(|tray| tray.toggle_window);
+
+    #[allow(unused_mut)]
+    let mut event_loop_builder = EventLoopBuilder::<RuntimeMessage>::with_user_event();
+    if ready.is_some() {
+        handle::allow_any_thread(&mut event_loop_builder);
+    }
+    #[cfg(target_os = "windows")]
+    if let Some(menu_bar) = &menu_bar {
+        menu::translate_accelerators(&mut event_loop_builder, menu_bar.clone());
+    }
+    let event_loop = event_loop_builder.build();
     let proxy = event_loop.create_proxy();
-    let window = tao::window::WindowBuilder::new()
-        .with_title("PyFrame")
+    let splash = splash.map(|html| splash::Splash::show(&event_loop, &html)).transpose()?;
+    let mut window_builder = window_config
+        .apply(tao::window::WindowBuilder::new())
+        .with_visible(splash.is_none())
+        .with_theme(theme)
+        .with_transparent(transparent)
+        .with_window_icon(icon);
+    if let Some(color) = background_color {
+        window_builder = window_builder.with_background_color(color);
+    }
+    let window = window_builder
         .build(&event_loop)
-        .map_err(|err| pyo3::exceptions::PyOSError::new_err(err.to_string()))?;
+        .map_err(|err| errors::WindowError::new_err(err.to_string()))?;
+
+    if let Some(vibrancy) = vibrancy {
+        vibrancy::apply(&window, vibrancy);
+    }
+    if let Some(menu_bar) = &menu_bar {
+        menu::attach(menu_bar, &window);
+    }
+    menu::forward_events(proxy.clone());
+    // macOS only shows status items once the app runs, see the event loop.
+    #[cfg(not(target_os = "macos"))]
+    let tray = tray.map(tray::TraySpec::build).transpose()?;
+    tray::forward_events(proxy.clone());
+    shortcuts::forward_events(proxy.clone());
 
-    let _webview = wry::WebViewBuilder::new()
-        .with_initialization_script(assets::INITIALIZEPY_SCRIPT)
-        .with_ipc_handler(ipc_req::handle_ipc_req(handler, proxy))
-        .with_html(&html)
-        .with_devtools(true)
-        .build(&window)
-        .map_err(|err| pyo3::exceptions::PyRuntimeError::new_err(err.to_string()))?;
-    
-    
     // Creates and enter Tokio runtime for async tasks.
-    let runtime = tokio::runtime::Runtime::new()
-        .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))?;
+    let runtime = runtime::TaskRuntime::new(runtime_threads)?;
     let _guard = runtime.enter();
+
+    // Asyncio loop that coroutines returned by the IPC handler run on.
+    // With a supervised Python thread, that thread's loop; otherwise a
+    // private one.
+    let handler_loop = executpy::HandlerLoop::default();
+    let stop_handler_loop = handler_loop.clone();
+    shutdown::reset();
+    stats::reset();
+    if python_loop.is_none() {
+        shutdown::track(executpy::spawn_asyncio_loop(py, &handler_loop)?);
+    }
+
+    // Every WebView gets its own IPC handler, so replies reach the view
+    // that sent the request. They all queue onto one worker thread.
+    let ipc_worker = ipc_req::IpcWorker::spawn(ipc_req::IpcSettings {
+        proxy: proxy.clone(),
+        format: ipc_format,
+        binary_ipc,
+        handler_loop: handler_loop.clone(),
+        runtime: runtime.handle().clone(),
+        on_error: on_error.map(Arc::new),
+        max_body_size: max_ipc_body_size,
+    })?;
+    let make_ipc_handler = {
+        let proxy = proxy.clone();
+        move |handler: Py<PyAny>, target: Target| -> IpcHandler {
+            Rc::new(ipc_req::handle_ipc_req(handler, target, proxy.clone(), ipc_worker.clone()))
+        }
+    };
+    let ipc_handler = make_ipc_handler(handler.clone_ref(py), Target::Window(windows::MAIN_WINDOW_ID));
+
+    let new_window_handler: NewWindowHandler =
+        Rc::new(new_window::handle_new_window_req(on_new_window, proxy.clone()));
+
+    let mut view_options = ViewOptions {
+        ipc_format,
+        max_ipc_body_size,
+        large_payloads: large_payload_threshold.is_some(),
+        theme,
+        background_color,
+        devtools,
+        incognito,
+        accept_invalid_certs,
+        proxy: proxy_server,
+        key_events: on_key.is_some(),
+        context_menu,
+        text_selection,
+        web_context,
+        asset_source,
+        navigation_handler: on_navigation
+            .map(|callback| Rc::new(navigation::handle_navigation(callback)) as NavigationHandler),
+        drag_drop_handler: (on_file_drop.is_some() || on_file_hover.is_some())
+            .then(|| Rc::new(events::handle_drag_drop(on_file_drop, on_file_hover)) as DragDropHandler),
+        user_agent,
+        init_scripts,
+        title_events: (auto_title || on_title_changed.is_some()).then(|| proxy.clone()),
+    };
+    let title_handler = view_options.title_handler(windows::MAIN_WINDOW_ID);
+    let builder = view_options
+        .builder(ipc_handler)
+        .with_new_window_req_handler({
+            let new_window_handler = new_window_handler.clone();
+            move |url| new_window_handler(url)
+        })
+        .with_transparent(transparent);
+    let builder = match title_handler {
+        Some(handler) => builder.with_document_title_changed_handler(handler),
+        None => builder,
+    };
+    let builder = match &content {
+        Content::Html(html) => builder.with_html(html),
+        Content::Url(url) => builder.with_url(url.as_str()),
+    };
+    let _webview = builder
+        .build(&window)
+        .map_err(|err| errors::WebViewError::new_err(err.to_string()))?;
+    if accept_invalid_certs {
+        certs::warn_insecure();
+        certs::accept_invalid(&_webview);
+    }
+    if let Some(zoom) = zoom {
+        _webview
+            .zoom(zoom)
+            .map_err(|e| errors::WebViewError::new_err(format!("Failed to zoom: {}", e)))?;
+    }
     
     // Create separate channels for communication between Python and Rust
-    let (py_to_rust_tx, mut py_to_rust_rx) = unbounded_channel();
     let (rust_to_py_tx, mut rust_to_py_rx) = unbounded_channel();
     
-    // Store the sender in our static variable
-    *MESSAGE_CHANNEL.lock().unwrap() = Some(py_to_rust_tx.clone());
+    // Register the main window's sender for `emit_*`
+    windows::register(windows::MAIN_WINDOW_ID, py_to_rust_tx);
+
+    // Start the supervised Python asyncio thread talking to Rust through
+    // the crossbeam channels of `py_sender`.
+    let mut to_py_thread = None;
+    let mut pinged = None;
+    if let Some((queue, ipc)) = python_loop {
+        let py_event_loop = match py_event_loop {
+            Some(py_event_loop) => py_event_loop,
+            None => py.import("asyncio")?.call_method0("new_event_loop")?.unbind(),
+        };
+        let (py_to_rust, from_py) = crossbeam_channel::unbounded::<py_sender::Envelope>();
+        let (to_py, rust_to_py) = crossbeam_channel::unbounded::<py_sender::Envelope>();
+
+        let pongs = Arc::new(heartbeat::Heartbeat::default());
+        if let Some(interval) = heartbeat {
+            pinged = Some((pongs.clone(), interval, to_py.clone()));
+        }
+        to_py_thread = Some(to_py);
+
+        // Consume objects sent by the Python thread. Messages sent with
+        // `send_and_wait` are acknowledged by replying with the object itself.
+        std::thread::spawn(move || {
+            for envelope in from_py.iter() {
+                stats::FROM_PYTHON.received();
+                if !Python::with_gil(|py| pongs.record_pong(envelope.msg.bind(py))) {
+                    log::debug!("Rust got from Python thread: {}", envelope.msg);
+                }
+                if let Some(id) = envelope.id {
+                    py_sender::reply(id, envelope.msg);
+                }
+            }
+        });
+
+        let shutdown_proxy = proxy.clone();
+        let supervisor = py_sender::supervise_py_event_loop(
+            py_event_loop,
+            queue,
+            ipc,
+            Arc::new(py_to_rust),
+            Arc::new(rust_to_py),
+            handler_loop,
+            python_loop_policy,
+            on_python_crash,
+            move || {
+                let _ = shutdown_proxy.send_event(RuntimeMessage::Quit);
+            },
+        );
+        shutdown::track(supervisor);
+    }
+
+    let mut hot_reload = watch_path
+        .map(|path| hot_reload::HotReload::spawn(runtime.handle(), &path, proxy.clone()))
+        .transpose()?;
+
+    message_validation.set();
+    payloads::set_threshold(large_payload_threshold);
+    schema::set_auto_timestamp(auto_timestamp);
+    *EVENT_LOOP_PROXY.lock().unwrap_or_else(PoisonError::into_inner) = Some(proxy);
 
     // Spawn background tasks before running the event loop
     // This async task is to receive events from python and process them in rust.
-    tokio::spawn(async move {
-        while let Some(msg) = py_to_rust_rx.recv().await {
-            println!("Rust got: {:?}", msg);
-            // Sending resp back to python
-            rust_to_py_tx.send("pong from rust").unwrap();
-        }
-    });
+    let mut tasks = tasks::Tasks::default();
+    spawn_message_consumer(
+        &mut tasks,
+        runtime.handle(),
+        windows::MAIN_WINDOW_ID,
+        py_to_rust_rx,
+        rust_to_py_tx.clone(),
+    );
 
     // This async task is to send events to python received from rust.
-    tokio::spawn(async move {
+    // With a Python thread, they reach its `ReceiverHandle` as `str`; the
+    // task keeps that channel open.
+    tasks.spawn(runtime.handle(), "reply forwarder".to_string(), async move {
         while let Some(reply) = rust_to_py_rx.recv().await {
-            println!("Python got: {}", reply);
+            log::debug!("Python got: {}", reply);
+            if let Some(to_py) = &to_py_thread {
+                let msg = Python::with_gil(|py| pyo3::types::PyString::new(py, reply).into_any().unbind());
+                stats::TO_PYTHON
+                    .count(to_py.send(py_sender::Envelope { id: None, msg }))
+                    .map_err(|_| "Python receiver closed".to_string())?;
+            }
         }
+        Ok(())
     });
 
+    if let Some((pongs, interval, to_py)) = pinged {
+        heartbeat::spawn(&mut tasks, runtime.handle(), pongs, interval, to_py, on_stall);
+    }
+
+    if handle_sigint {
+        signals::spawn(&mut tasks, runtime.handle());
+    }
+
+    let geometry_store = window_state.map(|path| geometry::GeometryStore::spawn(runtime.handle(), path));
+
     // Starting tao eventloop for handling gui events. 
-    event_loop.run(move |event, _window_target, flow: &mut ControlFlow| {
-        *flow = ControlFlow::Wait;
-        match event {
-            Event::WindowEvent {
-                window_id, event, ..
-            } => match event {
-                WindowEvent::CloseRequested => {
-                    println!("Close requested for window {:?}", window_id);
-                    *flow = ControlFlow::Exit;
-                }
-                _ => {}
-            },
-            Event::UserEvent(_user_event) => match _user_event {
-                RuntimeMessage::Eval(script) => {
-                    println!("Evaluating script: {}", script);
-                    let result = _webview.evaluate_script(&script);
-                    // Handle result or error if needed
-                    match result {
-                        Ok(r) => {
-                        // print the value of result, if its ok.
-                        println!("Script evaluated successfully: {:?}", r);
-                    }
-                        Err(e) => eprintln!("Error evaluating script: {:?}", e),
+    let runtime_handle = runtime.handle().clone();
+    let main_thread = MainThreadBound((
+        event_loop,
+        window,
+        _webview,
+        make_ipc_handler,
+        new_window_handler,
+        view_options,
+        menu_bar,
+        tray,
+        splash,
+    ));
+    let returning = ready.is_some();
+    if let Some(ready) = ready {
+        let _ = ready.send(Ok(()));
+    }
+    py.allow_threads(move || {
+        let (
+            event_loop,
+            window,
+            webview,
+            make_ipc_handler,
+            new_window_handler,
+            mut view_options,
+            _menu_bar,
+            tray,
+            mut splash,
+        ) = main_thread.into_inner();
+        #[cfg(target_os = "macos")]
+        let (mut pending_tray, mut _tray_icon) = (tray, None);
+        #[cfg(not(target_os = "macos"))]
+        let _tray_icon = tray;
+        // The open window, `None` once it was closed without quitting.
+        let mut frame = Some((webview, window));
+        let mut child_views = views::ChildViews::default();
+        // Windows opened by `spawn_window` and for `on_new_window` requests.
+        let mut opened_windows: HashMap<_, windows::HostedWindow> = HashMap::new();
+        let mut visibility = on_occlusion_change.map(events::VisibilityTracker::new);
+        let mut hotkeys = shortcuts::HotKeys::default();
+        run_event_loop(event_loop, returning, move |event, window_target, flow: &mut ControlFlow| {
+            *flow = ControlFlow::Wait;
+            match event {
+                #[cfg(target_os = "macos")]
+                Event::NewEvents(tao::event::StartCause::Init) => {
+                    if let Some(spec) = pending_tray.take() {
+                        match spec.build() {
+                            Ok(tray_icon) => _tray_icon = Some(tray_icon),
+                            Err(e) => log::error!("Failed to create tray icon: {}", e),
+                        }
                     }
                 }
-            },
-            _ => {}
-        }
+                Event::WindowEvent {
+                    window_id, event, ..
+                } => match event {
+                    WindowEvent::CloseRequested => {
+                        if splash.as_ref().is_some_and(|splash| splash.window_id() == window_id) {
+                            splash::dismiss(&mut splash, frame.as_ref().map(|(_, window)| window));
+                            return;
+                        }
+                        log::info!("Close requested for window {:?}", window_id);
+                        if let Some(hosted) = opened_windows.remove(&window_id) {
+                            windows::unregister(hosted.id);
+                        } else if !close_main_window(
+                            &mut frame,
+                            &mut child_views,
+                            &mut hot_reload,
+                            &mut splash,
+                            on_close.as_ref(),
+                            on_window_event.as_ref(),
+                            geometry_store.as_ref(),
+                        ) {
+                            return;
+                        }
+                        if quit_on_last_window_closed && frame.is_none() && opened_windows.is_empty() {
+                            *flow = ControlFlow::Exit;
+                        }
+                    }
+                    WindowEvent::Resized(size) => {
+                        let Some((_, window)) = frame.as_ref().filter(|(_, w)| w.id() == window_id) else {
+                            return;
+                        };
+                        child_views.reflow(size);
+                        if let Some(store) = &geometry_store {
+                            store.update(window);
+                        }
+                        if let Some(visibility) = &mut visibility {
+                            visibility.update(window);
+                        }
+                        if let Some(on_window_event) = &on_window_event {
+                            events::WindowEventPayload::resized(window, size).send(on_window_event);
+                        }
+                    }
+                    WindowEvent::Moved(position) => {
+                        let Some((_, window)) = frame.as_ref().filter(|(_, w)| w.id() == window_id) else {
+                            return;
+                        };
+                        if let Some(store) = &geometry_store {
+                            store.update(window);
+                        }
+                        if let Some(visibility) = &mut visibility {
+                            visibility.update(window);
+                        }
+                        if let Some(on_window_event) = &on_window_event {
+                            events::WindowEventPayload::moved(window, position).send(on_window_event);
+                        }
+                    }
+                    WindowEvent::ScaleFactorChanged { scale_factor, new_inner_size } => {
+                        if frame.as_ref().is_none_or(|(_, window)| window.id() != window_id) {
+                            return;
+                        }
+                        child_views.reflow(*new_inner_size);
+                        if let Some(on_window_event) = &on_window_event {
+                            events::WindowEventPayload::scale_factor_changed(scale_factor, *new_inner_size)
+                                .send(on_window_event);
+                        }
+                    }
+                    WindowEvent::ThemeChanged(theme) => {
+                        if frame.as_ref().is_none_or(|(_, window)| window.id() != window_id) {
+                            return;
+                        }
+                        if let Some(on_window_event) = &on_window_event {
+                            let theme = window_config::theme_name(theme);
+                            events::WindowEventPayload::ThemeChanged { theme }.send(on_window_event);
+                        }
+                    }
+                    WindowEvent::Focused(focused) => {
+                        if let (Some(visibility), Some((_, window))) = (&mut visibility, &frame) {
+                            visibility.update(window);
+                        }
+                        if frame.as_ref().is_none_or(|(_, window)| window.id() != window_id) {
+                            return;
+                        }
+                        if let Some(on_focus) = &on_focus {
+                            events::notify(on_focus, (focused,));
+                        }
+                        if let Some(on_window_event) = &on_window_event {
+                            events::WindowEventPayload::Focused { focused }.send(on_window_event);
+                        }
+                    }
+                    _ => {}
+                },
+                Event::UserEvent(user_event) => match user_event {
+                    RuntimeMessage::Eval(target, script) => {
+                        let webview = match target {
+                            Target::Window(id) => find_window(&frame, &opened_windows, id).map(|(webview, _)| webview),
+                            Target::View(id) => child_views.webview(id),
+                        };
+                        match webview {
+                            Some(webview) => evaluate(webview, &script, None),
+                            None => log::warn!("{:?} is closed, dropping script", target),
+                        }
+                    }
+                    RuntimeMessage::EvalBatch(target, scripts) => {
+                        let webview = match target {
+                            Target::Window(id) => find_window(&frame, &opened_windows, id).map(|(webview, _)| webview),
+                            Target::View(id) => child_views.webview(id),
+                        };
+                        match webview {
+                            Some(webview) => evaluate(webview, &scripts.join("\n"), None),
+                            None => log::warn!("{:?} is closed, dropping {} scripts", target, scripts.len()),
+                        }
+                    }
+                    RuntimeMessage::EvalWithReply(script, reply) => match &frame {
+                        Some((webview, _)) => evaluate(webview, &script, Some(reply)),
+                        None => {
+                            let _ = reply.send(Err("Window is closed".to_string()));
+                        }
+                    },
+                    RuntimeMessage::CloseWindow => {
+                        log::info!("Close window requested");
+                        let closed = close_main_window(
+                            &mut frame,
+                            &mut child_views,
+                            &mut hot_reload,
+                            &mut splash,
+                            on_close.as_ref(),
+                            on_window_event.as_ref(),
+                            geometry_store.as_ref(),
+                        );
+                        if closed && quit_on_last_window_closed && opened_windows.is_empty() {
+                            *flow = ControlFlow::Exit;
+                        }
+                    }
+                    message @ (RuntimeMessage::Quit | RuntimeMessage::Interrupt | RuntimeMessage::Shutdown) => {
+                        if matches!(message, RuntimeMessage::Interrupt) && frame.is_some() {
+                            if !on_close.as_ref().is_none_or(events::allow_close) {
+                                log::info!("Ignoring on_close veto after Ctrl+C");
+                            }
+                            if let Some(on_window_event) = &on_window_event {
+                                events::WindowEventPayload::CloseRequested.send(on_window_event);
+                            }
+                        }
+                        if let (Some(store), Some((_, window))) = (&geometry_store, &frame) {
+                            store.flush(window);
+                        }
+                        log::info!("Quit requested");
+                        opened_windows.clear();
+                        child_views.clear();
+                        windows::clear();
+                        frame = None;
+                        drop(hot_reload.take());
+                        drop(splash.take());
+                        tasks.abort_all();
+                        if matches!(message, RuntimeMessage::Shutdown) {
+                            Python::with_gil(|py| stop_handler_loop.stop(py));
+                        }
+                        *flow = ControlFlow::Exit;
+                    }
+                    RuntimeMessage::AddChildView { id, html, bounds } => {
+                        let Some((_, window)) = &frame else {
+                            views::release_id(id);
+                            return;
+                        };
+                        let handler = Python::with_gil(|py| handler.clone_ref(py));
+                        let ipc_handler = make_ipc_handler(handler, Target::View(id));
+                        match build_child_view(window, &html, bounds, &mut view_options, ipc_handler) {
+                            Ok(webview) => child_views.insert(id, webview, bounds),
+                            Err(e) => {
+                                log::error!("Failed to create child view {}: {:?}", id, e);
+                                views::release_id(id);
+                            }
+                        }
+                    }
+                    RuntimeMessage::Reload(id) => match find_window(&frame, &opened_windows, id) {
+                        Some((webview, _)) => {
+                            if let Err(e) = webview.reload() {
+                                log::error!("Failed to reload window {}: {}", id, e);
+                            }
+                        }
+                        None => log::warn!("Window {} is closed, not reloading it", id),
+                    },
+                    RuntimeMessage::ClearBrowsingData { id, reply } => {
+                        match find_window(&frame, &opened_windows, id) {
+                            Some((webview, _)) => {
+                                let _ = reply.send(webview.clear_all_browsing_data().map_err(|e| {
+                                    errors::WebViewError::new_err(format!("Failed to clear browsing data: {}", e))
+                                }));
+                            }
+                            None => log::warn!("Window {} is closed, not clearing its browsing data", id),
+                        }
+                    }
+                    RuntimeMessage::Load(id, content) => match find_window(&frame, &opened_windows, id) {
+                        Some((webview, _)) => {
+                            let loaded = match &content {
+                                Content::Html(html) => webview.load_html(html),
+                                Content::Url(url) => webview.load_url(url.as_str()),
+                            };
+                            if let Err(e) = loaded {
+                                log::error!("Failed to load content into window {}: {}", id, e);
+                            }
+                        }
+                        None => log::warn!("Window {} is closed, not loading content", id),
+                    },
+                    RuntimeMessage::OpenDevtools(id) => match find_window(&frame, &opened_windows, id).map(|(webview, _)| webview) {
+                        Some(_) if !view_options.devtools => {
+                            log::warn!("Devtools are disabled, pass devtools=True to create_webframe");
+                        }
+                        #[cfg(any(target_os = "android", target_os = "ios"))]
+                        Some(_) => log::warn!("Devtools are not available on this platform"),
+                        #[cfg(not(any(target_os = "android", target_os = "ios")))]
+                        Some(webview) => webview.open_devtools(),
+                        None => log::warn!("Window {} is closed, not opening devtools", id),
+                    },
+                    RuntimeMessage::SetTitle(id, title) => match find_window(&frame, &opened_windows, id) {
+                        Some((_, window)) => window.set_title(&title),
+                        None => log::warn!("Window {} is closed, not setting its title", id),
+                    },
+                    RuntimeMessage::SetPosition(id, position) => match find_window(&frame, &opened_windows, id) {
+                        Some((_, window)) => geometry::move_to(window, position),
+                        None => log::warn!("Window {} is closed, not moving it", id),
+                    },
+                    RuntimeMessage::SetOpacity(id, value) => match find_window(&frame, &opened_windows, id) {
+                        Some((_, window)) => opacity::set(window, value),
+                        None => log::warn!("Window {} is closed, not changing its opacity", id),
+                    },
+                    RuntimeMessage::CenterWindow(id) => match find_window(&frame, &opened_windows, id) {
+                        Some((_, window)) => geometry::center(window),
+                        None => log::warn!("Window {} is closed, not centering it", id),
+                    },
+                    RuntimeMessage::SetZoom(id, factor) => match find_window(&frame, &opened_windows, id) {
+                        Some((webview, _)) => {
+                            if let Err(e) = webview.zoom(factor) {
+                                log::error!("Failed to zoom window {}: {}", id, e);
+                            }
+                        }
+                        None => log::warn!("Window {} is closed, not zooming it", id),
+                    },
+                    RuntimeMessage::FrontendReady(Target::Window(windows::MAIN_WINDOW_ID)) => {
+                        splash::dismiss(&mut splash, frame.as_ref().map(|(_, window)| window));
+                    }
+                    RuntimeMessage::FrontendReady(_) => {}
+                    RuntimeMessage::Key(target, event) => {
+                        let Some(script) = on_key.as_ref().and_then(|on_key| events::handle_key(on_key, &event))
+                        else {
+                            return;
+                        };
+                        let webview = match target {
+                            Target::Window(id) => find_window(&frame, &opened_windows, id).map(|(webview, _)| webview),
+                            Target::View(id) => child_views.webview(id),
+                        };
+                        if let Some(webview) = webview {
+                            evaluate(webview, &script, None);
+                        }
+                    }
+                    RuntimeMessage::StartDrag(target) => {
+                        let id = match target {
+                            Target::Window(id) => id,
+                            // Child views live in the main window.
+                            Target::View(_) => windows::MAIN_WINDOW_ID,
+                        };
+                        if let Some((_, window)) = find_window(&frame, &opened_windows, id) {
+                            if let Err(e) = window.drag_window() {
+                                log::warn!("Failed to drag window {}: {}", id, e);
+                            }
+                        }
+                    }
+                    RuntimeMessage::MenuClicked(id) => match &on_menu {
+                        Some(on_menu) => events::notify(on_menu, (id,)),
+                        None => log::debug!("Menu item {:?} clicked without on_menu", id),
+                    },
+                    RuntimeMessage::TrayClicked(button) => {
+                        if button == "left" && tray_toggles_window {
+                            if let Some((_, window)) = &frame {
+                                let visible = !window.is_visible();
+                                window.set_visible(visible);
+                                if visible {
+                                    window.set_focus();
+                                }
+                            }
+                        }
+                        if let Some(on_tray_click) = &on_tray_click {
+                            events::notify(on_tray_click, (button,));
+                        }
+                    }
+                    RuntimeMessage::CaptureRegion { id, reply } => {
+                        let region = find_window(&frame, &opened_windows, id)
+                            .and_then(|(_, window)| capture::Region::of(window));
+                        let _ = reply.send(region);
+                    }
+                    RuntimeMessage::Cookies { id, op, reply } => match find_window(&frame, &opened_windows, id) {
+                        Some((webview, _)) => {
+                            let _ = reply.send(op.run(webview));
+                        }
+                        None => log::warn!("Window {} is closed, not accessing its cookies", id),
+                    },
+                    RuntimeMessage::PrintToPdf { id, path, reply } => match find_window(&frame, &opened_windows, id) {
+                        Some((webview, _)) => pdf::print_to_pdf(webview, path, reply),
+                        None => log::warn!("Window {} is closed, not printing it", id),
+                    },
+                    RuntimeMessage::RegisterShortcut(hotkey) => hotkeys.register(hotkey),
+                    RuntimeMessage::UnregisterShortcut(hotkey) => hotkeys.unregister(hotkey),
+                    RuntimeMessage::ShortcutPressed(id) => shortcuts::dispatch(id),
+                    RuntimeMessage::SetFullscreen { id, enabled, reply } => {
+                        let Some((_, window)) = find_window(&frame, &opened_windows, id) else {
+                            return log::warn!("Window {} is closed, not changing fullscreen", id);
+                        };
+                        window.set_fullscreen(enabled.then_some(tao::window::Fullscreen::Borderless(None)));
+                        let fullscreen = window.fullscreen().is_some();
+                        let _ = reply.send(fullscreen);
+                        if let (windows::MAIN_WINDOW_ID, Some(on_window_event)) = (id, &on_window_event) {
+                            events::WindowEventPayload::FullscreenChanged { fullscreen }.send(on_window_event);
+                        }
+                    }
+                    RuntimeMessage::Monitors { reply } => {
+                        let primary = window_target.primary_monitor();
+                        let monitors = window_target
+                            .available_monitors()
+                            .map(|monitor| monitors::Monitor::of(&monitor, primary.as_ref()))
+                            .collect();
+                        let _ = reply.send(monitors);
+                    }
+                    RuntimeMessage::CurrentMonitor { id, reply } => match find_window(&frame, &opened_windows, id) {
+                        Some((_, window)) => {
+                            let primary = window.primary_monitor();
+                            let monitor = window.current_monitor();
+                            let _ = reply.send(monitor.map(|monitor| monitors::Monitor::of(&monitor, primary.as_ref())));
+                        }
+                        None => log::warn!("Window {} is closed, not reading its monitor", id),
+                    },
+                    RuntimeMessage::TitleChanged(id, title) => {
+                        if auto_title && !title.trim().is_empty() {
+                            if let Some((_, window)) = find_window(&frame, &opened_windows, id) {
+                                window.set_title(&title);
+                            }
+                        }
+                        if let (Some(on_title_changed), windows::MAIN_WINDOW_ID) = (&on_title_changed, id) {
+                            events::notify(on_title_changed, (title,));
+                        }
+                    }
+                    RuntimeMessage::Theme { id, reply } => match find_window(&frame, &opened_windows, id) {
+                        Some((_, window)) => {
+                            let _ = reply.send(window_config::theme_name(window.theme()));
+                        }
+                        None => log::warn!("Window {} is closed, not reading its theme", id),
+                    },
+                    RuntimeMessage::ScaleFactor { id, reply } => match find_window(&frame, &opened_windows, id) {
+                        Some((_, window)) => {
+                            let _ = reply.send(window.scale_factor());
+                        }
+                        None => log::warn!("Window {} is closed, not reading its scale factor", id),
+                    },
+                    RuntimeMessage::SetAlwaysOnTop { id, enabled, reply } => match find_window(&frame, &opened_windows, id) {
+                        Some((_, window)) => {
+                            window.set_always_on_top(enabled);
+                            let _ = reply.send(window.is_always_on_top());
+                        }
+                        None => log::warn!("Window {} is closed, not changing always-on-top", id),
+                    },
+                    RuntimeMessage::FileDialog { id, dialog, reply } => match find_window(&frame, &opened_windows, id) {
+                        Some((_, window)) => {
+                            let _ = reply.send(dialog.show(window));
+                        }
+                        None => log::warn!("Window {} is closed, not showing {:?}", id, dialog),
+                    },
+                    RuntimeMessage::MessageDialog { id, dialog, reply } => {
+                        let parent = find_window(&frame, &opened_windows, id).map(|(_, window)| window);
+                        let _ = reply.send(dialog.show(parent));
+                    }
+                    RuntimeMessage::ControlWindow { id, action, reply } => match find_window(&frame, &opened_windows, id) {
+                        Some((_, window)) => {
+                            let _ = reply.send(action.apply(window));
+                        }
+                        None => log::warn!("Window {} is closed, not applying {:?}", id, action),
+                    },
+                    RuntimeMessage::SetViewBounds { id, bounds } => {
+                        if let Some((_, window)) = &frame {
+                            child_views.set_bounds(id, bounds, window.inner_size());
+                        }
+                    }
+                    RuntimeMessage::OpenWindow { url } => {
+                        let content = match url::Url::parse(&url) {
+                            Ok(url) => Content::Url(url),
+                            Err(e) => return log::error!("Failed to open window for {}: {}", url, e),
+                        };
+                        let id = windows::allocate_id();
+                        let handler = Python::with_gil(|py| handler.clone_ref(py));
+                        let ipc_handler = make_ipc_handler(handler, Target::Window(id));
+                        let config = window_config::WindowConfig::default();
+                        match build_window(
+                            window_target,
+                            id,
+                            &config,
+                            &content,
+                            &mut view_options,
+                            ipc_handler,
+                            &new_window_handler,
+                        ) {
+                            Ok((webview, window)) => {
+                                opened_windows.insert(window.id(), windows::HostedWindow { id, webview, window });
+                            }
+                            Err(e) => log::error!("Failed to open window for {}: {}", url, e),
+                        }
+                    }
+                    RuntimeMessage::CreateWindow { id, handler, content, config, messages } => {
+                        let ipc_handler = make_ipc_handler(handler, Target::Window(id));
+                        match build_window(
+                            window_target,
+                            id,
+                            &config,
+                            &content,
+                            &mut view_options,
+                            ipc_handler,
+                            &new_window_handler,
+                        ) {
+                            Ok((webview, window)) => {
+                                spawn_message_consumer(
+                                    &mut tasks,
+                                    &runtime_handle,
+                                    id,
+                                    messages,
+                                    rust_to_py_tx.clone(),
+                                );
+                                opened_windows.insert(window.id(), windows::HostedWindow { id, webview, window });
+                            }
+                            Err(e) => {
+                                log::error!("Failed to create window {}: {}", id, e);
+                                windows::unregister(id);
+                            }
+                        }
+                    }
+                },
+                _ => {}
+            }
+        });
     });
+    *EVENT_LOOP_PROXY.lock().unwrap_or_else(PoisonError::into_inner) = None;
+    Ok(())
 }
 
+/// Runs `event_loop` until it exits. Without `returning`, this is tao's
+/// `run`, which ends the process afterwards; otherwise the loop runs on a
+/// thread started by `create_webframe(blocking=False)` and returns.
+fn run_event_loop<F>(event_loop: tao::event_loop::EventLoop<RuntimeMessage>, returning: bool, handler: F)
+where
+    F: FnMut(Event<'_, RuntimeMessage>, &tao::event_loop::EventLoopWindowTarget<RuntimeMessage>, &mut ControlFlow)
+        + 'static,
+{
+    #[cfg(not(any(target_os = "android", target_os = "ios")))]
+    if returning {
+        use tao::platform::run_return::EventLoopExtRunReturn;
+        let mut event_loop = event_loop;
+        event_loop.run_return(handler);
+        return;
+    }
+    #[cfg(any(target_os = "android", target_os = "ios"))]
+    let _ = returning;
+    event_loop.run(handler)
+}
 
 // Dump code
 // static START_CONSUMER_ONCE: Once = Once::new();
@@ -188,40 +2199,893 @@ fn create_webframe(handler: Py<PyAny>, html: String) -> PyResult<()> {
 
 
 
+/// Sends a message to the Rust side.
+///
+/// `json` is a JSON `str`, or MessagePack `bytes` of the same structure,
+/// with the fields `message`, `timestamp` and `payload`, all optional.
+/// `payload` takes any JSON value, so structured data needs no second
+/// layer of encoding: `{"message": "rows", "payload": {"rows": [1, 2]}}`.
+///
+/// The message goes to the channel of `window_id` (e.g. `MAIN_WINDOW_ID`),
+/// or of the first open window if omitted.
+///
+/// # Errors
+/// - Raises `PyValueError` if `window_id` names no open window, or the
+///   message does not decode or breaks `message_validation` (see
+///   `create_webframe`).
+/// - Raises `ChannelError` if the channel is full (see
+///   `message_capacity`), closed, or not initialized.
 #[pyfunction]
-fn emit_str(json: &str) -> PyResult<()> {
+#[pyo3(signature = (json, window_id = None))]
+fn emit_str(json: codec::EncodedMessage, window_id: Option<u64>) -> PyResult<()> {
     // Parse JSON into our Message structure
-    let message: Message = serde_json::from_str(json)
-        .map_err(|e| pyo3::exceptions::PyValueError::new_err(format!("Invalid JSON: {}", e)))?;
-    
-    if let Some(sender) = MESSAGE_CHANNEL.lock().unwrap().as_ref() {
-        println!("[RUST] event sent to Rust: {:?}", message);
-        sender.send(message)
-            .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(format!("Failed to send message: {}", e)))?;
-        Ok(())
-    } else {
-        Err(pyo3::exceptions::PyRuntimeError::new_err("Channel not initialized"))?
-    }
+    let message = schema::decode(&json)?;
+
+    let sender = windows::sender(window_id)?;
+    log::debug!("[RUST] event sent to Rust: {:?}", message);
+    sender.try_send(message.into())
+}
+
+/// Variant of [`emit_str`] that blocks, with the GIL released, until the
+/// Rust consumer has processed the message, and returns its reply.
+///
+/// `timeout` is in seconds; `None` waits forever.
+///
+/// # Errors
+/// - Raises `PyValueError` for a negative `timeout` or an unknown
+///   `window_id`.
+/// - Raises `PyTimeoutError` if the message is not acknowledged in time.
+/// - Raises `ChannelError` like [`emit_str`], and if the consumer stopped
+///   before acknowledging.
+#[pyfunction]
+#[pyo3(signature = (json, timeout = None, window_id = None))]
+fn emit_sync(
+    py: Python<'_>,
+    json: codec::EncodedMessage,
+    timeout: Option<f64>,
+    window_id: Option<u64>,
+) -> PyResult<&'static str> {
+    let timeout = timeout
+        .map(std::time::Duration::try_from_secs_f64)
+        .transpose()
+        .map_err(|e| pyo3::exceptions::PyValueError::new_err(format!("invalid timeout: {e}")))?;
+    let message = schema::decode(&json)?;
+
+    let sender = windows::sender(window_id)?;
+    let (ack, acked) = crossbeam_channel::bounded(1);
+    log::debug!("[RUST] (sync) event sent to Rust: {:?}", message);
+    sender.try_send(Emitted { message, ack: Some(ack) })?;
+
+    py.allow_threads(|| match timeout {
+        Some(timeout) => acked.recv_timeout(timeout),
+        None => acked.recv().map_err(|_| crossbeam_channel::RecvTimeoutError::Disconnected),
+    })
+    .map_err(|e| match e {
+        crossbeam_channel::RecvTimeoutError::Timeout => {
+            pyo3::exceptions::PyTimeoutError::new_err("message not acknowledged within the timeout")
+        }
+        crossbeam_channel::RecvTimeoutError::Disconnected => {
+            errors::ChannelError::new_err("message consumer stopped before acknowledging")
+        }
+    })
 }
 
 
+/// Awaitable variant of [`emit_str`], accepting the same arguments.
 #[pyfunction]
-fn emit_async<'a>(py: Python<'a>, json: &'a str) -> PyResult<pyo3::Bound<'a, pyo3::PyAny>> {
+#[pyo3(signature = (json, window_id = None))]
+fn emit_async(
+    py: Python<'_>,
+    json: codec::EncodedMessage,
+    window_id: Option<u64>,
+) -> PyResult<pyo3::Bound<'_, pyo3::PyAny>> {
     // Parse JSON into our Message structure
-    let message: Message = serde_json::from_str(json)
-        .map_err(|e| pyo3::exceptions::PyValueError::new_err(format!("Invalid JSON: {}", e)))?;
-
-    if let Some(sender) = MESSAGE_CHANNEL.lock().unwrap().as_ref() {
-        let sender = sender.clone();
-        pyo3_async_runtimes::tokio::future_into_py(py, async move {
-            println!("[RUST] (async) event sent to Rust: {:?}", message);
-            sender.send(message)
-                .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(format!("Failed to send message: {}", e)))?;
-            Python::with_gil(|py| Ok(py.None()))
+    let message = schema::decode(&json)?;
+
+    let sender = windows::sender(window_id)?;
+    pyo3_async_runtimes::tokio::future_into_py(py, async move {
+        log::debug!("[RUST] (async) event sent to Rust: {:?}", message);
+        sender.send(message.into()).await?;
+        Python::with_gil(|py| Ok(py.None()))
+    })
+}
+
+
+
+
+/// Pushes `event` to the page of the window `window_id` (the main window
+/// by default), where `window.ipcOn(event, callback)` subscribers get
+/// `payload` and a `CustomEvent` named `event` is dispatched on `window`
+/// with `payload` as its `detail`.
+///
+/// `payload` is any value `json.dumps` accepts, `None` by default. Large
+/// payloads are fetched by the page rather than inlined, see
+/// `create_webframe(large_payload_threshold=...)`.
+///
+/// # Errors
+/// - Raises `PyValueError` if `payload` cannot be serialized to JSON, or
+///   for an unknown window id.
+/// - Raises `FrameError` if no webframe is running.
+#[pyfunction]
+#[pyo3(signature = (event, payload = None, window_id = None))]
+fn emit_to_js(py: Python<'_>, event: &str, payload: Option<Py<PyAny>>, window_id: Option<u64>) -> PyResult<()> {
+    let id = window_id.unwrap_or(windows::MAIN_WINDOW_ID);
+    windows::check_id(id)?;
+    let json: String = py.import("json")?.call_method1("dumps", (payload,))?.extract()?;
+    let script = payloads::emit_script(event, &json).map_err(|e| {
+        pyo3::exceptions::PyValueError::new_err(format!("Payload is not valid JSON: {}", e))
+    })?;
+    send_runtime_message(RuntimeMessage::Eval(Target::Window(id), script))
+}
+
+/// Pushes several events to the page of the window `window_id` at once,
+/// as `(event, payload)` tuples handled like [`emit_to_js`] calls in
+/// order. For high-frequency updates such as progress, this wakes the
+/// event loop and evaluates a script once per batch instead of once per
+/// event.
+///
+/// Only the Rust side is measured, by `benches/emit_batch.rs`: building
+/// the scripts and handing them to the event loop took about half as long
+/// batched for bursts of 100 to 1000 events (0.46 ms against 1.0 ms for
+/// 1000, on a single-core Linux machine). The time the WebView takes to
+/// evaluate the scripts is not measured.
+///
+/// # Errors
+/// Like [`emit_to_js`]; nothing is sent if any payload fails.
+#[pyfunction]
+#[pyo3(signature = (events, window_id = None))]
+fn emit_batch_to_js(py: Python<'_>, events: Vec<(String, Option<Py<PyAny>>)>, window_id: Option<u64>) -> PyResult<()> {
+    let id = window_id.unwrap_or(windows::MAIN_WINDOW_ID);
+    windows::check_id(id)?;
+    if events.is_empty() {
+        return Ok(());
+    }
+    let dumps = py.import("json")?.getattr("dumps")?;
+    let scripts = events
+        .into_iter()
+        .map(|(event, payload)| {
+            let json: String = dumps.call1((payload,))?.extract()?;
+            payloads::emit_script(&event, &json).map_err(|e| {
+                pyo3::exceptions::PyValueError::new_err(format!("Payload of {:?} is not valid JSON: {}", event, e))
+            })
         })
-    } else {
-        Err(pyo3::exceptions::PyRuntimeError::new_err("Channel not initialized"))?
+        .collect::<PyResult<Vec<_>>>()?;
+    send_runtime_message(RuntimeMessage::EvalBatch(Target::Window(id), scripts))
+}
+
+/// Evaluates `script` in the WebView and returns an awaitable that resolves
+/// with the JSON-parsed result of the script's completion value.
+///
+/// Values that cannot be represented in JSON (e.g. `undefined`) resolve to
+/// `None`. On backends without callback support (Android) the result is
+/// always `None`.
+///
+/// Also exported as `eval`.
+///
+/// # Errors
+/// - Raises `FrameError` if no webframe is running.
+/// - Raises `WebViewError` if the script could not be evaluated.
+#[pyfunction]
+fn eval_js<'a>(py: Python<'a>, script: String) -> PyResult<pyo3::Bound<'a, pyo3::PyAny>> {
+    let (tx, reply) = oneshot::channel();
+    send_runtime_message(RuntimeMessage::EvalWithReply(script, tx))?;
+
+    pyo3_async_runtimes::tokio::future_into_py(py, async move {
+        let json = reply
+            .await
+            .map_err(|e| errors::WebViewError::new_err(format!("Evaluation dropped: {}", e)))?
+            .map_err(|e| errors::WebViewError::new_err(format!("Failed to evaluate script: {}", e)))?;
+        Python::with_gil(|py| {
+            if json.is_empty() {
+                return Ok(py.None());
+            }
+            Ok(py.import("json")?.call_method1("loads", (json,))?.unbind())
+        })
+    })
+}
+
+
+
+
+/// Opens another window hosting its own WebView and returns its window id.
+///
+/// The window is hosted by the event loop that the first (and only)
+/// [`create_webframe`] call owns and runs; call this once that loop is
+/// running, e.g. from an IPC handler or another Python thread. `handler`
+/// receives this window's IPC requests, and `html`/`url`/`base_url` and
+/// `window_config` work as for `create_webframe`. The returned id targets
+/// the window's own message channel in [`emit_str`], created with
+/// `message_capacity`.
+///
+/// # Errors
+/// - Raises `PyValueError` for invalid content, window config or capacity.
+/// - Raises `FrameError` if no webframe is running.
+#[pyfunction]
+#[pyo3(signature = (handler, html = None, *, url = None, base_url = None, window_config = None, message_capacity = None))]
+fn spawn_window(
+    py: Python<'_>,
+    handler: Py<PyAny>,
+    html: Option<String>,
+    url: Option<String>,
+    base_url: Option<String>,
+    window_config: Option<Py<PyAny>>,
+    message_capacity: Option<usize>,
+) -> PyResult<u64> {
+    let content = Content::from_args(html, url, base_url)?;
+    let config = window_config::WindowConfig::from_py(py, window_config)?;
+    let (sender, messages) = channel::message_channel(message_capacity)?;
+
+    let id = windows::allocate_id();
+    windows::register(id, sender);
+    send_runtime_message(RuntimeMessage::CreateWindow { id, handler, content, config, messages })
+        .inspect_err(|_| windows::unregister(id))?;
+    Ok(id)
+}
+
+
+
+
+/// Closes the main window, like clicking its close button.
+///
+/// With `quit_on_last_window_closed` (the default) and no windows opened
+/// through `on_new_window`, this ends the event loop and the process. Use
+/// [`quit_app`] to exit regardless of other windows.
+///
+/// # Errors
+/// - Raises `FrameError` if no webframe is running.
+#[pyfunction]
+fn close_window() -> PyResult<()> {
+    send_runtime_message(RuntimeMessage::CloseWindow)
+}
+
+/// Opens the web inspector of the window `window_id` (the main window by
+/// default).
+///
+/// Requires `create_webframe(devtools=True)`; otherwise, and on platforms
+/// without an inspector (Android, iOS), a warning is logged instead.
+///
+/// # Errors
+/// - Raises `PyValueError` for an unknown window id.
+/// - Raises `FrameError` if no webframe is running.
+#[pyfunction]
+#[pyo3(signature = (window_id=None))]
+fn open_devtools(window_id: Option<u64>) -> PyResult<()> {
+    let id = window_id.unwrap_or(windows::MAIN_WINDOW_ID);
+    windows::check_id(id)?;
+    send_runtime_message(RuntimeMessage::OpenDevtools(id))
+}
+
+
+
+
+/// Changes the title of the window `window_id` (the main window by
+/// default), e.g. to show the document name or unsaved state.
+///
+/// # Errors
+/// - Raises `PyValueError` for an unknown window id.
+/// - Raises `FrameError` if no webframe is running.
+#[pyfunction]
+#[pyo3(signature = (title, window_id=None))]
+fn set_window_title(title: String, window_id: Option<u64>) -> PyResult<()> {
+    let id = window_id.unwrap_or(windows::MAIN_WINDOW_ID);
+    windows::check_id(id)?;
+    send_runtime_message(RuntimeMessage::SetTitle(id, title))
+}
+
+/// Zooms the page of the window `window_id` (the main window by default)
+/// to `factor`, 1.0 being the normal size, like the browser's zoom: text,
+/// images and layout scale together. It applies to pages loaded later too.
+///
+/// # Errors
+/// - Raises `PyValueError` for an unknown window id and a `factor` outside
+///   0.25 to 5.0.
+/// - Raises `FrameError` if no webframe is running.
+#[pyfunction]
+#[pyo3(signature = (factor, window_id=None))]
+fn set_zoom(factor: f64, window_id: Option<u64>) -> PyResult<()> {
+    let factor = check_zoom(factor)?;
+    let id = window_id.unwrap_or(windows::MAIN_WINDOW_ID);
+    windows::check_id(id)?;
+    send_runtime_message(RuntimeMessage::SetZoom(id, factor))
+}
+
+/// Moves the window `window_id` (the main window by default) so its outer
+/// frame starts at `x`, `y`, in logical pixels like `window_config`. A
+/// position where no monitor would show the window is logged as a warning
+/// but still applied.
+///
+/// # Errors
+/// - Raises `PyValueError` for an unknown window id and non-finite
+///   coordinates.
+/// - Raises `FrameError` if no webframe is running.
+#[pyfunction]
+#[pyo3(signature = (x, y, window_id=None))]
+fn set_position(x: f64, y: f64, window_id: Option<u64>) -> PyResult<()> {
+    if !x.is_finite() || !y.is_finite() {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "Window position must be finite, got ({}, {})",
+            x, y
+        )));
+    }
+    let id = window_id.unwrap_or(windows::MAIN_WINDOW_ID);
+    windows::check_id(id)?;
+    send_runtime_message(RuntimeMessage::SetPosition(id, tao::dpi::LogicalPosition::new(x, y)))
+}
+
+/// Sets the opacity of the whole window `window_id` (the main window by
+/// default), title bar included, from 0.0 (invisible) to 1.0 (opaque);
+/// other values are clamped. Calling it repeatedly fades the window, e.g.
+/// for overlays. Unlike `transparent`, which lets the page draw see-through
+/// areas, this dims everything evenly.
+///
+/// Supported on Windows, macOS and Linux (with a compositing window
+/// manager); elsewhere a warning is logged and nothing changes.
+///
+/// # Errors
+/// - Raises `PyValueError` for an unknown window id and a NaN `value`.
+/// - Raises `FrameError` if no webframe is running.
+#[pyfunction]
+#[pyo3(signature = (value, window_id=None))]
+fn set_opacity(value: f64, window_id: Option<u64>) -> PyResult<()> {
+    if value.is_nan() {
+        return Err(pyo3::exceptions::PyValueError::new_err("Window opacity must be a number, got NaN"));
     }
+    let id = window_id.unwrap_or(windows::MAIN_WINDOW_ID);
+    windows::check_id(id)?;
+    send_runtime_message(RuntimeMessage::SetOpacity(id, value))
+}
+
+/// Centers the window `window_id` (the main window by default) on the
+/// monitor showing it.
+///
+/// # Errors
+/// - Raises `PyValueError` for an unknown window id.
+/// - Raises `FrameError` if no webframe is running.
+#[pyfunction]
+#[pyo3(signature = (window_id=None))]
+fn center_window(window_id: Option<u64>) -> PyResult<()> {
+    let id = window_id.unwrap_or(windows::MAIN_WINDOW_ID);
+    windows::check_id(id)?;
+    send_runtime_message(RuntimeMessage::CenterWindow(id))
+}
+
+/// Reloads the page of the window `window_id` (the main window by
+/// default), as if the user pressed F5. The IPC bridge and `init_scripts`
+/// are injected again.
+///
+/// # Errors
+/// - Raises `PyValueError` for an unknown window id.
+/// - Raises `FrameError` if no webframe is running.
+#[pyfunction]
+#[pyo3(signature = (window_id=None))]
+fn reload(window_id: Option<u64>) -> PyResult<()> {
+    let id = window_id.unwrap_or(windows::MAIN_WINDOW_ID);
+    windows::check_id(id)?;
+    send_runtime_message(RuntimeMessage::Reload(id))
+}
+
+/// Deletes the browsing data of the WebView of window `window_id` (the
+/// main window by default): cookies, cache, localStorage, sessionStorage,
+/// IndexedDB and the other site data, e.g. on logout or between tests. The
+/// open page keeps running and only sees the data gone on its next access.
+///
+/// Returns an awaitable resolving once the data is deleted.
+///
+/// Platform differences:
+/// - WebView2 (Windows) clears the whole profile, shared by every window.
+/// - WKWebView (macOS, iOS) clears the data store of the WebView, and
+///   WebKitGTK (Linux) that of its web context; both are shared by every
+///   window, as frame creates them with the default context.
+/// - Android resolves as soon as the request is handed to the WebView.
+///
+/// # Errors
+/// - Raises `PyValueError` for an unknown window id.
+/// - Raises `FrameError` if no webframe is running.
+/// - Raises `WindowError` if the window closed before clearing.
+/// - Raises `WebViewError` if the data cannot be deleted.
+#[pyfunction]
+#[pyo3(signature = (window_id=None))]
+fn clear_browsing_data(py: Python<'_>, window_id: Option<u64>) -> PyResult<pyo3::Bound<'_, pyo3::PyAny>> {
+    let id = window_id.unwrap_or(windows::MAIN_WINDOW_ID);
+    windows::check_id(id)?;
+    let (tx, reply) = oneshot::channel();
+    send_runtime_message(RuntimeMessage::ClearBrowsingData { id, reply: tx })?;
+
+    pyo3_async_runtimes::tokio::future_into_py(py, async move {
+        reply
+            .await
+            .map_err(|_| errors::WindowError::new_err(format!("Window {} is closed", id)))??;
+        Ok(())
+    })
+}
+
+/// Replaces the entire document of the window `window_id` (the main window
+/// by default) with `html`: page state and scripts are discarded, and the
+/// IPC bridge and `init_scripts` run again as on a fresh page.
+///
+/// # Errors
+/// - Raises `PyValueError` for an unknown window id.
+/// - Raises `FrameError` if no webframe is running.
+#[pyfunction]
+#[pyo3(signature = (html, window_id=None))]
+fn load_html(html: String, window_id: Option<u64>) -> PyResult<()> {
+    let id = window_id.unwrap_or(windows::MAIN_WINDOW_ID);
+    windows::check_id(id)?;
+    send_runtime_message(RuntimeMessage::Load(id, Content::Html(html)))
+}
+
+/// Navigates the window `window_id` (the main window by default) to `url`;
+/// `"about:blank"` empties it.
+///
+/// # Errors
+/// - Raises `PyValueError` for an unknown window id, and for a `url`
+///   rejected like `create_webframe(url=...)`.
+/// - Raises `FrameError` if no webframe is running.
+#[pyfunction]
+#[pyo3(signature = (url, window_id=None))]
+fn load_url(url: &str, window_id: Option<u64>) -> PyResult<()> {
+    let id = window_id.unwrap_or(windows::MAIN_WINDOW_ID);
+    windows::check_id(id)?;
+    send_runtime_message(RuntimeMessage::Load(id, Content::Url(parse_url(url)?)))
+}
+
+/// Puts the window `window_id` (the main window by default) into
+/// borderless fullscreen on its current monitor, or back into a window.
+///
+/// Returns an awaitable resolving to whether the window is fullscreen
+/// afterwards. For the main window, `on_window_event` is also called with
+/// `{"type": "fullscreen_changed", "fullscreen": ...}`.
+///
+/// # Errors
+/// - Raises `PyValueError` for an unknown window id.
+/// - Raises `FrameError` if no webframe is running.
+/// - Raises `WindowError` if the window closed before the change.
+#[pyfunction]
+#[pyo3(signature = (enabled, window_id=None))]
+fn set_fullscreen(py: Python<'_>, enabled: bool, window_id: Option<u64>) -> PyResult<pyo3::Bound<'_, pyo3::PyAny>> {
+    let id = window_id.unwrap_or(windows::MAIN_WINDOW_ID);
+    windows::check_id(id)?;
+    let (tx, reply) = oneshot::channel();
+    send_runtime_message(RuntimeMessage::SetFullscreen { id, enabled, reply: tx })?;
+
+    pyo3_async_runtimes::tokio::future_into_py(py, async move {
+        reply
+            .await
+            .map_err(|_| errors::WindowError::new_err(format!("Window {} is closed", id)))
+    })
+}
+
+/// Keeps the window `window_id` (the main window by default) above all
+/// other windows, as set initially by `window_config`'s `always_on_top`,
+/// or lets it be covered again.
+///
+/// Returns an awaitable resolving to whether the window is now on top, as
+/// reported by the window afterwards. It can differ from `enabled` where
+/// the platform refuses the change, and is always `False` on Android and
+/// iOS, which do not support it.
+///
+/// # Errors
+/// - Raises `PyValueError` for an unknown window id.
+/// - Raises `FrameError` if no webframe is running.
+/// - Raises `WindowError` if the window closed before the change.
+#[pyfunction]
+#[pyo3(signature = (enabled, window_id=None))]
+fn set_always_on_top(py: Python<'_>, enabled: bool, window_id: Option<u64>) -> PyResult<pyo3::Bound<'_, pyo3::PyAny>> {
+    let id = window_id.unwrap_or(windows::MAIN_WINDOW_ID);
+    windows::check_id(id)?;
+    let (tx, reply) = oneshot::channel();
+    send_runtime_message(RuntimeMessage::SetAlwaysOnTop { id, enabled, reply: tx })?;
+
+    pyo3_async_runtimes::tokio::future_into_py(py, async move {
+        reply
+            .await
+            .map_err(|_| errors::WindowError::new_err(format!("Window {} is closed", id)))
+    })
+}
+
+/// Reads the theme of the window `window_id` (the main window by
+/// default), `"light"` or `"dark"`: the one passed as `create_webframe`'s
+/// `theme`, or the OS appearance with `"system"`. Changes are reported to
+/// `on_window_event` as `{"type": "theme_changed", "theme"}`.
+///
+/// Returns an awaitable resolving to the theme.
+///
+/// # Errors
+/// - Raises `PyValueError` for an unknown window id.
+/// - Raises `FrameError` if no webframe is running.
+/// - Raises `WindowError` if the window closed before reading.
+#[pyfunction]
+#[pyo3(signature = (window_id=None))]
+fn get_theme(py: Python<'_>, window_id: Option<u64>) -> PyResult<pyo3::Bound<'_, pyo3::PyAny>> {
+    let id = window_id.unwrap_or(windows::MAIN_WINDOW_ID);
+    windows::check_id(id)?;
+    let (tx, reply) = oneshot::channel();
+    send_runtime_message(RuntimeMessage::Theme { id, reply: tx })?;
+
+    pyo3_async_runtimes::tokio::future_into_py(py, async move {
+        reply
+            .await
+            .map_err(|_| errors::WindowError::new_err(format!("Window {} is closed", id)))
+    })
+}
+
+/// Reads the scale factor of the window `window_id` (the main window by
+/// default): physical pixels per logical pixel, e.g. 2.0 on a Retina or
+/// 200% display. It changes when the window moves to a monitor with
+/// another factor, reported to `on_window_event` as
+/// `{"type": "scale_factor_changed", ...}`.
+///
+/// Returns an awaitable resolving to the factor.
+///
+/// # Errors
+/// - Raises `PyValueError` for an unknown window id.
+/// - Raises `FrameError` if no webframe is running.
+/// - Raises `WindowError` if the window closed before reading.
+#[pyfunction]
+#[pyo3(signature = (window_id=None))]
+fn get_scale_factor(py: Python<'_>, window_id: Option<u64>) -> PyResult<pyo3::Bound<'_, pyo3::PyAny>> {
+    let id = window_id.unwrap_or(windows::MAIN_WINDOW_ID);
+    windows::check_id(id)?;
+    let (tx, reply) = oneshot::channel();
+    send_runtime_message(RuntimeMessage::ScaleFactor { id, reply: tx })?;
+
+    pyo3_async_runtimes::tokio::future_into_py(py, async move {
+        reply
+            .await
+            .map_err(|_| errors::WindowError::new_err(format!("Window {} is closed", id)))
+    })
+}
+
+fn control_window(
+    py: Python<'_>,
+    action: windows::WindowAction,
+    window_id: Option<u64>,
+) -> PyResult<pyo3::Bound<'_, pyo3::PyAny>> {
+    let id = window_id.unwrap_or(windows::MAIN_WINDOW_ID);
+    windows::check_id(id)?;
+    let (tx, reply) = oneshot::channel();
+    send_runtime_message(RuntimeMessage::ControlWindow { id, action, reply: tx })?;
+
+    pyo3_async_runtimes::tokio::future_into_py(py, async move {
+        reply
+            .await
+            .map_err(|_| errors::WindowError::new_err(format!("Window {} is closed", id)))
+    })
+}
+
+/// Hides the window `window_id` (the main window by default) without
+/// closing it.
+///
+/// Returns an awaitable resolving to whether the window is visible
+/// afterwards, like [`show_window`], [`minimize_window`] and
+/// [`maximize_window`].
+///
+/// # Errors
+/// - Raises `PyValueError` for an unknown window id.
+/// - Raises `FrameError` if no webframe is running.
+/// - Raises `WindowError` if the window closed before the change.
+#[pyfunction]
+#[pyo3(signature = (window_id=None))]
+fn hide_window(py: Python<'_>, window_id: Option<u64>) -> PyResult<pyo3::Bound<'_, pyo3::PyAny>> {
+    control_window(py, windows::WindowAction::Hide, window_id)
+}
+
+/// Shows the window `window_id` again, restoring it if minimized, and
+/// focuses it.
+///
+/// # Errors
+/// Like [`hide_window`].
+#[pyfunction]
+#[pyo3(signature = (window_id=None))]
+fn show_window(py: Python<'_>, window_id: Option<u64>) -> PyResult<pyo3::Bound<'_, pyo3::PyAny>> {
+    control_window(py, windows::WindowAction::Show, window_id)
+}
+
+/// Minimizes the window `window_id`.
+///
+/// # Errors
+/// Like [`hide_window`].
+#[pyfunction]
+#[pyo3(signature = (window_id=None))]
+fn minimize_window(py: Python<'_>, window_id: Option<u64>) -> PyResult<pyo3::Bound<'_, pyo3::PyAny>> {
+    control_window(py, windows::WindowAction::Minimize, window_id)
+}
+
+/// Maximizes the window `window_id`.
+///
+/// # Errors
+/// Like [`hide_window`].
+#[pyfunction]
+#[pyo3(signature = (window_id=None))]
+fn maximize_window(py: Python<'_>, window_id: Option<u64>) -> PyResult<pyo3::Bound<'_, pyo3::PyAny>> {
+    control_window(py, windows::WindowAction::Maximize, window_id)
+}
+
+/// Takes a screenshot of the content of window `window_id` (the main
+/// window by default), its WebView, as PNG.
+///
+/// Returns an awaitable resolving to the PNG `bytes`, which are also
+/// written to `path` if given. The window rect is captured from the
+/// screen, so the window must be shown, and whatever overlaps it is part
+/// of the picture; capturing may need the screen recording permission
+/// on macOS.
+///
+/// # Errors
+/// - Raises `PyValueError` for an unknown window id.
+/// - Raises `FrameError` if no webframe is running.
+/// - Raises `WindowError` if the window is closed, hidden or minimized.
+/// - Raises `PyOSError` if the screen cannot be captured or `path` not
+///   written.
+#[pyfunction]
+#[pyo3(signature = (path=None, window_id=None))]
+fn capture_webview(
+    py: Python<'_>,
+    path: Option<std::path::PathBuf>,
+    window_id: Option<u64>,
+) -> PyResult<pyo3::Bound<'_, pyo3::PyAny>> {
+    let id = window_id.unwrap_or(windows::MAIN_WINDOW_ID);
+    windows::check_id(id)?;
+    let (tx, reply) = oneshot::channel();
+    send_runtime_message(RuntimeMessage::CaptureRegion { id, reply: tx })?;
+
+    pyo3_async_runtimes::tokio::future_into_py(py, async move {
+        let region = reply.await.ok().flatten().ok_or_else(|| {
+            errors::WindowError::new_err(format!("Window {} is not on screen", id))
+        })?;
+        let png = tokio::task::spawn_blocking(move || {
+            let png = capture::capture_png(region)
+                .map_err(|e| pyo3::exceptions::PyOSError::new_err(format!("Failed to capture window: {}", e)))?;
+            if let Some(path) = path {
+                std::fs::write(&path, &png).map_err(|e| {
+                    pyo3::exceptions::PyOSError::new_err(format!("Failed to write {}: {}", path.display(), e))
+                })?;
+            }
+            Ok::<_, PyErr>(png)
+        })
+        .await
+        .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))??;
+        Ok(Python::with_gil(|py| pyo3::types::PyBytes::new(py, &png).unbind()))
+    })
+}
+
+/// Sends `op` to the WebView of window `window_id` (the main window by
+/// default), returning the receiver of its outcome.
+fn send_cookie_op(
+    op: cookies::CookieOp,
+    window_id: Option<u64>,
+) -> PyResult<(u64, oneshot::Receiver<PyResult<Vec<wry::cookie::Cookie<'static>>>>)> {
+    let id = window_id.unwrap_or(windows::MAIN_WINDOW_ID);
+    windows::check_id(id)?;
+    let (tx, reply) = oneshot::channel();
+    send_runtime_message(RuntimeMessage::Cookies { id, op, reply: tx })?;
+    Ok((id, reply))
+}
+
+/// Reads the cookies the WebView of window `window_id` (the main window by
+/// default) would send to `url`, or all of its cookies without `url`.
+///
+/// Returns an awaitable resolving to a list of dicts with `name`, `value`,
+/// `domain`, `path`, `expires` (seconds since the Unix epoch, `None` for
+/// session cookies), `secure`, `http_only` and `same_site` (`"strict"`,
+/// `"lax"`, `"none"` or `None`).
+///
+/// # Errors
+/// - Raises `PyValueError` for an unknown window id.
+/// - Raises `FrameError` if no webframe is running.
+/// - Raises `WindowError` if the window closed before reading.
+/// - Raises `WebViewError` if the WebView cannot read its cookies.
+#[pyfunction]
+#[pyo3(signature = (url=None, window_id=None))]
+fn get_cookies(py: Python<'_>, url: Option<String>, window_id: Option<u64>) -> PyResult<pyo3::Bound<'_, pyo3::PyAny>> {
+    let (id, reply) = send_cookie_op(cookies::CookieOp::Get(url), window_id)?;
+
+    pyo3_async_runtimes::tokio::future_into_py(py, async move {
+        let cookies = reply
+            .await
+            .map_err(|_| errors::WindowError::new_err(format!("Window {} is closed", id)))??;
+        Python::with_gil(|py| {
+            let dicts = cookies
+                .iter()
+                .map(|cookie| cookies::to_dict(py, cookie))
+                .collect::<PyResult<Vec<_>>>()?;
+            Ok(pyo3::types::PyList::new(py, dicts)?.unbind())
+        })
+    })
+}
+
+/// Stores a cookie for `url` in the WebView of window `window_id` (the
+/// main window by default), e.g. to restore a login session. The cookie's
+/// domain is the host of `url`, and it is `secure` by default for `https`
+/// URLs. `expires` is in seconds since the Unix epoch; without it, the
+/// cookie only lasts for the session. Only supported with WebView2.
+///
+/// Returns an awaitable resolving once the cookie is stored.
+///
+/// # Errors
+/// - Raises `PyValueError` for an unknown window id, a `url` rejected like
+///   `create_webframe(url=...)` or without a host, and an unknown
+///   `same_site`.
+/// - Raises `FrameError` if no webframe is running.
+/// - Raises `WindowError` if the window closed before storing.
+/// - Raises `PyNotImplementedError` on platforms other than Windows.
+/// - Raises `WebViewError` if the WebView rejects the cookie.
+#[pyfunction]
+#[allow(clippy::too_many_arguments)]
+#[pyo3(signature = (url, name, value, *, path="/", expires=None, secure=None, http_only=false, same_site=None, window_id=None))]
+fn set_cookie<'py>(
+    py: Python<'py>,
+    url: &str,
+    name: String,
+    value: String,
+    path: &str,
+    expires: Option<f64>,
+    secure: Option<bool>,
+    http_only: bool,
+    same_site: Option<&str>,
+    window_id: Option<u64>,
+) -> PyResult<pyo3::Bound<'py, pyo3::PyAny>> {
+    let url = parse_url(url)?;
+    let domain = url
+        .host_str()
+        .ok_or_else(|| pyo3::exceptions::PyValueError::new_err(format!("URL {} has no host", url)))?
+        .to_string();
+    let spec = cookies::CookieSpec {
+        name,
+        value,
+        domain,
+        path: path.to_string(),
+        expires,
+        secure: secure.unwrap_or(url.scheme() == "https"),
+        http_only,
+        same_site: same_site.map(cookies::parse_same_site).transpose()?,
+    };
+    let (id, reply) = send_cookie_op(cookies::CookieOp::Set(spec), window_id)?;
+
+    pyo3_async_runtimes::tokio::future_into_py(py, async move {
+        reply
+            .await
+            .map_err(|_| errors::WindowError::new_err(format!("Window {} is closed", id)))??;
+        Ok(())
+    })
+}
+
+/// Deletes every cookie of the WebView of window `window_id` (the main
+/// window by default); windows share their cookies. Only supported with
+/// WebView2.
+///
+/// Returns an awaitable resolving once the cookies are gone.
+///
+/// # Errors
+/// - Raises `PyValueError` for an unknown window id.
+/// - Raises `FrameError` if no webframe is running.
+/// - Raises `WindowError` if the window closed before clearing.
+/// - Raises `PyNotImplementedError` on platforms other than Windows.
+/// - Raises `WebViewError` if the cookies cannot be deleted.
+#[pyfunction]
+#[pyo3(signature = (window_id=None))]
+fn clear_cookies(py: Python<'_>, window_id: Option<u64>) -> PyResult<pyo3::Bound<'_, pyo3::PyAny>> {
+    let (id, reply) = send_cookie_op(cookies::CookieOp::Clear, window_id)?;
+
+    pyo3_async_runtimes::tokio::future_into_py(py, async move {
+        reply
+            .await
+            .map_err(|_| errors::WindowError::new_err(format!("Window {} is closed", id)))??;
+        Ok(())
+    })
+}
+
+/// Prints the page shown in window `window_id` (the main window by
+/// default) to a PDF file at `path`, without a print dialog.
+///
+/// Returns an awaitable resolving once the file is written.
+///
+/// # Errors
+/// - Raises `PyValueError` for an unknown window id.
+/// - Raises `FrameError` if no webframe is running.
+/// - Raises `WindowError` if the window closed before printing.
+/// - Raises `PyNotImplementedError` on platforms other than Windows.
+/// - Raises `WebViewError` if printing or writing the file fails.
+#[pyfunction]
+#[pyo3(signature = (path, window_id=None))]
+fn print_to_pdf(py: Python<'_>, path: std::path::PathBuf, window_id: Option<u64>) -> PyResult<pyo3::Bound<'_, pyo3::PyAny>> {
+    let id = window_id.unwrap_or(windows::MAIN_WINDOW_ID);
+    windows::check_id(id)?;
+    let path = std::path::absolute(&path)?;
+    let (tx, reply) = oneshot::channel();
+    send_runtime_message(RuntimeMessage::PrintToPdf { id, path, reply: tx })?;
+
+    pyo3_async_runtimes::tokio::future_into_py(py, async move {
+        reply
+            .await
+            .map_err(|_| errors::WindowError::new_err(format!("Window {} is closed", id)))?
+    })
+}
+
+/// Installs a logger printing the crate's log records to stderr.
+///
+/// Nothing is logged until this is called. `level` is one of `"off"`,
+/// `"error"`, `"warn"`, `"info"` (default), `"debug"` or `"trace"`;
+/// directives in the `RUST_LOG` environment variable take precedence, e.g.
+/// `RUST_LOG=frame=debug`.
+///
+/// # Errors
+/// - Raises `PyValueError` for an unknown `level`.
+/// - Raises `PyRuntimeError` if a logger is already installed.
+#[pyfunction]
+#[pyo3(signature = (level = "info"))]
+fn init_logging(level: &str) -> PyResult<()> {
+    let level: log::LevelFilter = level
+        .parse()
+        .map_err(|_| pyo3::exceptions::PyValueError::new_err(format!("Unknown log level: {:?}", level)))?;
+    env_logger::Builder::new()
+        .filter_level(level)
+        .parse_env(env_logger::Env::default())
+        .try_init()
+        .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(format!("Logger already installed: {}", e)))
+}
+
+
+
+
+/// Closes the window and exits the application.
+///
+/// Like closing the last window, this ends the event loop that
+/// [`create_webframe`] runs, which terminates the process.
+///
+/// # Errors
+/// - Raises `FrameError` if no webframe is running.
+#[pyfunction]
+fn quit_app() -> PyResult<()> {
+    send_runtime_message(RuntimeMessage::Quit)
+}
+
+
+
+
+/// Adds a child WebView showing `html` and returns its view id.
+///
+/// Bounds are logical pixels, or with `relative=True` fractions of the
+/// window's inner size that are re-applied on every resize. Child views
+/// share the main view's initialization scripts and IPC handler. Linux
+/// supports them on X11 only.
+///
+/// # Errors
+/// - Raises `PyValueError` for invalid bounds.
+/// - Raises `FrameError` if no webframe is running.
+#[pyfunction]
+#[pyo3(signature = (html, x, y, width, height, *, relative = false))]
+fn add_child_view(html: String, x: f64, y: f64, width: f64, height: f64, relative: bool) -> PyResult<u64> {
+    let bounds = views::ViewBounds::new(x, y, width, height, relative)?;
+    let id = views::allocate_id();
+    send_runtime_message(RuntimeMessage::AddChildView { id, html, bounds })
+        .inspect_err(|_| views::release_id(id))?;
+    Ok(id)
+}
+
+/// Moves and resizes the child view `view_id` (see [`add_child_view`] for
+/// the meaning of the bounds).
+///
+/// # Errors
+/// - Raises `PyValueError` for unknown view ids or invalid bounds.
+/// - Raises `FrameError` if no webframe is running.
+#[pyfunction]
+#[pyo3(signature = (view_id, x, y, width, height, *, relative = false))]
+fn set_view_bounds(view_id: u64, x: f64, y: f64, width: f64, height: f64, relative: bool) -> PyResult<()> {
+    views::check_id(view_id)?;
+    let bounds = views::ViewBounds::new(x, y, width, height, relative)?;
+    send_runtime_message(RuntimeMessage::SetViewBounds { id: view_id, bounds })
+}
+
+
+
+
+/// Returns the state of the Python event loop thread started through
+/// `pyevent_to_rust_queue`/`rust_to_py_ipc`: `"running"`, `"stopped"`
+/// (exited normally) or `"crashed"` (raised or panicked). `"stopped"` if
+/// no such thread was configured.
+#[pyfunction]
+fn python_loop_state() -> &'static str {
+    py_sender::LoopState::current().name()
 }
 
 
@@ -236,8 +3100,65 @@ fn emit_async<'a>(py: Python<'a>, json: &'a str) -> PyResult<pyo3::Bound<'a, pyo
 #[pymodule]
 fn frame(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(create_webframe, m)?)?;
+    m.add_function(wrap_pyfunction!(create_webframe_blank, m)?)?;
+    m.add("MAIN_WINDOW_ID", windows::MAIN_WINDOW_ID)?;
+    errors::register(m)?;
     m.add_function(wrap_pyfunction!(emit_str, m)?)?;
     m.add_function(wrap_pyfunction!(emit_async, m)?)?;
+    m.add_function(wrap_pyfunction!(emit_sync, m)?)?;
+    m.add_function(wrap_pyfunction!(py_sender::create_channel, m)?)?;
+    m.add_class::<py_sender::SenderHandle>()?;
+    m.add_class::<py_sender::ReceiverHandle>()?;
+    m.add_class::<handle::FrameHandle>()?;
+    m.add_function(wrap_pyfunction!(eval_js, m)?)?;
+    m.add_function(wrap_pyfunction!(emit_to_js, m)?)?;
+    m.add_function(wrap_pyfunction!(emit_batch_to_js, m)?)?;
+    m.add("eval", m.getattr("eval_js")?)?;
+    m.add_function(wrap_pyfunction!(spawn_window, m)?)?;
+    m.add_function(wrap_pyfunction!(close_window, m)?)?;
+    m.add_function(wrap_pyfunction!(open_devtools, m)?)?;
+    m.add_function(wrap_pyfunction!(set_window_title, m)?)?;
+    m.add_function(wrap_pyfunction!(reload, m)?)?;
+    m.add_function(wrap_pyfunction!(set_zoom, m)?)?;
+    m.add_function(wrap_pyfunction!(set_position, m)?)?;
+    m.add_function(wrap_pyfunction!(center_window, m)?)?;
+    m.add_function(wrap_pyfunction!(set_opacity, m)?)?;
+    m.add_function(wrap_pyfunction!(load_html, m)?)?;
+    m.add_function(wrap_pyfunction!(load_url, m)?)?;
+    m.add_function(wrap_pyfunction!(set_fullscreen, m)?)?;
+    m.add_function(wrap_pyfunction!(set_always_on_top, m)?)?;
+    m.add_function(wrap_pyfunction!(get_scale_factor, m)?)?;
+    m.add_function(wrap_pyfunction!(get_theme, m)?)?;
+    m.add_function(wrap_pyfunction!(hide_window, m)?)?;
+    m.add_function(wrap_pyfunction!(show_window, m)?)?;
+    m.add_function(wrap_pyfunction!(minimize_window, m)?)?;
+    m.add_function(wrap_pyfunction!(maximize_window, m)?)?;
+    m.add_function(wrap_pyfunction!(capture_webview, m)?)?;
+    m.add_function(wrap_pyfunction!(print_to_pdf, m)?)?;
+    m.add_function(wrap_pyfunction!(get_cookies, m)?)?;
+    m.add_function(wrap_pyfunction!(set_cookie, m)?)?;
+    m.add_function(wrap_pyfunction!(clear_cookies, m)?)?;
+    m.add_function(wrap_pyfunction!(clear_browsing_data, m)?)?;
+    m.add_function(wrap_pyfunction!(dialogs::open_file_dialog, m)?)?;
+    m.add_function(wrap_pyfunction!(dialogs::save_file_dialog, m)?)?;
+    m.add_function(wrap_pyfunction!(dialogs::message_box, m)?)?;
+    m.add_function(wrap_pyfunction!(dialogs::confirm, m)?)?;
+    m.add_function(wrap_pyfunction!(monitors::list_monitors, m)?)?;
+    m.add_function(wrap_pyfunction!(monitors::current_monitor, m)?)?;
+    m.add_function(wrap_pyfunction!(rpc::register_method, m)?)?;
+    m.add_function(wrap_pyfunction!(router::register_route, m)?)?;
+    m.add_function(wrap_pyfunction!(clipboard::clipboard_set, m)?)?;
+    m.add_function(wrap_pyfunction!(clipboard::clipboard_get, m)?)?;
+    m.add_function(wrap_pyfunction!(shortcuts::register_shortcut, m)?)?;
+    m.add_function(wrap_pyfunction!(shortcuts::unregister_shortcut, m)?)?;
+    m.add_function(wrap_pyfunction!(shortcuts::list_shortcuts, m)?)?;
+    m.add_function(wrap_pyfunction!(init_logging, m)?)?;
+    m.add_function(wrap_pyfunction!(quit_app, m)?)?;
+    m.add_function(wrap_pyfunction!(shutdown::shutdown, m)?)?;
+    m.add_function(wrap_pyfunction!(stats::channel_stats, m)?)?;
+    m.add_function(wrap_pyfunction!(python_loop_state, m)?)?;
+    m.add_function(wrap_pyfunction!(add_child_view, m)?)?;
+    m.add_function(wrap_pyfunction!(set_view_bounds, m)?)?;
     // m.add_function(wrap_pyfunction!(start_event_loop, m)?)?;
     Ok(())
 }
@@ -0,0 +1,226 @@
+use muda::{IsMenuItem, MenuItem, PredefinedMenuItem, Submenu};
+use pyo3::prelude::*;
+use serde::Deserialize;
+use std::sync::{Mutex, PoisonError};
+
+use crate::RuntimeMessage;
+
+/// One entry of a menu description passed from Python:
+/// - `{"id": "open", "text": "Open", "accelerator": "CmdOrCtrl+O"}`, a
+///   clickable item reported to `on_menu` by its id (`enabled` defaults
+///   to `True`),
+/// - `{"title": "Recent", "items": [...]}`, a submenu,
+/// - `{"predefined": "copy"}`, a native item handled by the platform,
+/// - `"separator"`.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum MenuEntry {
+    Item(ItemDesc),
+    Submenu(SubmenuDesc),
+    Predefined(PredefinedDesc),
+    Separator(String),
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ItemDesc {
+    id: String,
+    text: String,
+    #[serde(default)]
+    accelerator: Option<String>,
+    #[serde(default = "enabled")]
+    enabled: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SubmenuDesc {
+    title: String,
+    items: Vec<MenuEntry>,
+    #[serde(default = "enabled")]
+    enabled: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PredefinedDesc {
+    predefined: String,
+}
+
+fn enabled() -> bool {
+    true
+}
+
+/// Reads a menu description, a list of entries, from Python.
+///
+/// # Errors
+/// Returns `PyValueError` if `value` does not match [`MenuEntry`].
+pub fn parse_entries(py: Python<'_>, value: Py<PyAny>) -> PyResult<Vec<MenuEntry>> {
+    let json: String = py.import("json")?.call_method1("dumps", (value,))?.extract()?;
+    serde_json::from_str(&json)
+        .map_err(|e| pyo3::exceptions::PyValueError::new_err(format!("Invalid menu: {}", e)))
+}
+
+/// Builds `entries` into `menu`, a menu bar or context menu.
+///
+/// # Errors
+/// Returns `PyValueError` for invalid accelerators, unknown predefined
+/// items or separators spelled otherwise, and `PyRuntimeError` if the
+/// platform rejects an item.
+pub fn build(entries: &[MenuEntry]) -> PyResult<muda::Menu> {
+    let menu = muda::Menu::new();
+    append_entries(entries, &|item| menu.append(item))?;
+    Ok(menu)
+}
+
+/// Builds the menu bar from the description passed as `menu`; top-level
+/// entries must be submenus (File, Edit, ...), as required on macOS.
+///
+/// # Errors
+/// Like [`build`], and `PyValueError` for top-level entries that are not
+/// submenus.
+pub fn build_bar(entries: &[MenuEntry]) -> PyResult<muda::Menu> {
+    if !entries.iter().all(|entry| matches!(entry, MenuEntry::Submenu(_))) {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "Top-level menu entries must be submenus ({\"title\": ..., \"items\": [...]})",
+        ));
+    }
+    build(entries)
+}
+
+fn append_entries(
+    entries: &[MenuEntry],
+    append: &dyn Fn(&dyn IsMenuItem) -> muda::Result<()>,
+) -> PyResult<()> {
+    let platform_error =
+        |e: muda::Error| pyo3::exceptions::PyRuntimeError::new_err(format!("Failed to build menu: {}", e));
+    for entry in entries {
+        match entry {
+            MenuEntry::Item(item) => {
+                let accelerator = item
+                    .accelerator
+                    .as_deref()
+                    .map(|accelerator| {
+                        accelerator.parse::<muda::accelerator::Accelerator>().map_err(|e| {
+                            pyo3::exceptions::PyValueError::new_err(format!(
+                                "Invalid accelerator {:?}: {}",
+                                accelerator, e
+                            ))
+                        })
+                    })
+                    .transpose()?;
+                let item = MenuItem::with_id(item.id.as_str(), &item.text, item.enabled, accelerator);
+                append(&item).map_err(platform_error)?;
+            }
+            MenuEntry::Submenu(desc) => {
+                let submenu = Submenu::new(&desc.title, desc.enabled);
+                append_entries(&desc.items, &|item| submenu.append(item))?;
+                append(&submenu).map_err(platform_error)?;
+            }
+            MenuEntry::Predefined(desc) => {
+                append(&predefined(&desc.predefined)?).map_err(platform_error)?;
+            }
+            MenuEntry::Separator(name) if name == "separator" => {
+                append(&PredefinedMenuItem::separator()).map_err(platform_error)?;
+            }
+            MenuEntry::Separator(name) => {
+                return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                    "Invalid menu entry {:?} (expected \"separator\")",
+                    name
+                )))
+            }
+        }
+    }
+    Ok(())
+}
+
+fn predefined(name: &str) -> PyResult<PredefinedMenuItem> {
+    Ok(match name {
+        "separator" => PredefinedMenuItem::separator(),
+        "copy" => PredefinedMenuItem::copy(None),
+        "cut" => PredefinedMenuItem::cut(None),
+        "paste" => PredefinedMenuItem::paste(None),
+        "select_all" => PredefinedMenuItem::select_all(None),
+        "undo" => PredefinedMenuItem::undo(None),
+        "redo" => PredefinedMenuItem::redo(None),
+        "minimize" => PredefinedMenuItem::minimize(None),
+        "maximize" => PredefinedMenuItem::maximize(None),
+        "fullscreen" => PredefinedMenuItem::fullscreen(None),
+        "hide" => PredefinedMenuItem::hide(None),
+        "hide_others" => PredefinedMenuItem::hide_others(None),
+        "show_all" => PredefinedMenuItem::show_all(None),
+        "close_window" => PredefinedMenuItem::close_window(None),
+        "quit" => PredefinedMenuItem::quit(None),
+        "about" => PredefinedMenuItem::about(None, None),
+        "services" => PredefinedMenuItem::services(None),
+        other => {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Unknown predefined menu item: {:?}",
+                other
+            )))
+        }
+    })
+}
+
+/// Shows `menu` as the menu bar of `window` (of the whole app on macOS).
+pub fn attach(menu: &muda::Menu, window: &tao::window::Window) {
+    #[cfg(target_os = "windows")]
+    {
+        use tao::platform::windows::WindowExtWindows;
+        // SAFETY: the HWND belongs to a live tao window.
+        if let Err(e) = unsafe { menu.init_for_hwnd(window.hwnd()) } {
+            log::error!("Failed to attach menu: {}", e);
+        }
+    }
+    #[cfg(any(
+        target_os = "linux",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd"
+    ))]
+    {
+        use tao::platform::unix::WindowExtUnix;
+        if let Err(e) = menu.init_for_gtk_window(window.gtk_window(), window.default_vbox()) {
+            log::error!("Failed to attach menu: {}", e);
+        }
+    }
+    #[cfg(target_os = "macos")]
+    {
+        let _ = window;
+        menu.init_for_nsapp();
+    }
+    #[cfg(any(target_os = "android", target_os = "ios"))]
+    {
+        let _ = (menu, window);
+        log::warn!("Menus are not supported on this platform");
+    }
+}
+
+/// Makes the event loop translate the keyboard accelerators of `menu`;
+/// Windows only delivers them through the message loop.
+#[cfg(target_os = "windows")]
+pub fn translate_accelerators(
+    builder: &mut tao::event_loop::EventLoopBuilder<RuntimeMessage>,
+    menu: muda::Menu,
+) {
+    use tao::platform::windows::EventLoopBuilderExtWindows;
+    use windows_sys::Win32::UI::WindowsAndMessaging::{TranslateAcceleratorW, MSG};
+    builder.with_msg_hook(move |msg| {
+        let msg = msg as *const MSG;
+        // SAFETY: tao passes a pointer to the message being dispatched.
+        unsafe { TranslateAcceleratorW((*msg).hwnd, menu.haccel() as _, msg) == 1 }
+    });
+}
+
+/// Forwards clicks on menu items, of the menu bar and of context menus,
+/// to the event loop as [`RuntimeMessage::MenuClicked`].
+pub fn forward_events(proxy: tao::event_loop::EventLoopProxy<RuntimeMessage>) {
+    let proxy = Mutex::new(proxy);
+    muda::MenuEvent::set_event_handler(Some(move |event: muda::MenuEvent| {
+        let _ = proxy
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .send_event(RuntimeMessage::MenuClicked(event.id.0));
+    }));
+}
@@ -0,0 +1,104 @@
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use tao::monitor::MonitorHandle;
+use tokio::sync::oneshot;
+
+use crate::{errors, send_runtime_message, windows, RuntimeMessage};
+
+/// A display as reported to Python. Sizes and positions are logical
+/// pixels of that display, like `window_config`.
+#[derive(Debug, Clone)]
+pub struct Monitor {
+    name: Option<String>,
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+    scale_factor: f64,
+    primary: bool,
+}
+
+impl Monitor {
+    /// Reads the geometry of `handle`; `primary` is the primary display,
+    /// if the platform has one.
+    pub fn of(handle: &MonitorHandle, primary: Option<&MonitorHandle>) -> Self {
+        let scale_factor = handle.scale_factor();
+        let position = handle.position().to_logical::<f64>(scale_factor);
+        let size = handle.size().to_logical::<f64>(scale_factor);
+        Monitor {
+            name: handle.name(),
+            x: position.x,
+            y: position.y,
+            width: size.width,
+            height: size.height,
+            scale_factor,
+            primary: primary == Some(handle),
+        }
+    }
+
+    fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let dict = PyDict::new(py);
+        dict.set_item("name", &self.name)?;
+        dict.set_item("x", self.x)?;
+        dict.set_item("y", self.y)?;
+        dict.set_item("width", self.width)?;
+        dict.set_item("height", self.height)?;
+        dict.set_item("scale_factor", self.scale_factor)?;
+        dict.set_item("primary", self.primary)?;
+        Ok(dict)
+    }
+}
+
+/// Lists the connected displays, e.g. to place windows on a given one
+/// with `set_position`.
+///
+/// Returns an awaitable resolving to a list of dicts with `name` (`None`
+/// where unknown), `x`, `y`, `width`, `height`, `scale_factor` and
+/// `primary`, the geometry in logical pixels of each display.
+///
+/// # Errors
+/// Raises `FrameError` if no webframe is running.
+#[pyfunction]
+pub fn list_monitors(py: Python<'_>) -> PyResult<Bound<'_, PyAny>> {
+    let (tx, reply) = oneshot::channel();
+    send_runtime_message(RuntimeMessage::Monitors { reply: tx })?;
+
+    pyo3_async_runtimes::tokio::future_into_py(py, async move {
+        let monitors = reply
+            .await
+            .map_err(|_| errors::FrameError::new_err("Event loop exited before listing monitors"))?;
+        Python::with_gil(|py| {
+            let dicts = monitors
+                .iter()
+                .map(|monitor| monitor.to_dict(py))
+                .collect::<PyResult<Vec<_>>>()?;
+            Ok(pyo3::types::PyList::new(py, dicts)?.unbind())
+        })
+    })
+}
+
+/// Reads the display showing the window `window_id` (the main window by
+/// default), as a dict like those of [`list_monitors`].
+///
+/// Returns an awaitable resolving to the dict, or `None` if the platform
+/// cannot tell.
+///
+/// # Errors
+/// - Raises `PyValueError` for an unknown window id.
+/// - Raises `FrameError` if no webframe is running.
+/// - Raises `WindowError` if the window closed before reading.
+#[pyfunction]
+#[pyo3(signature = (window_id=None))]
+pub fn current_monitor(py: Python<'_>, window_id: Option<u64>) -> PyResult<Bound<'_, PyAny>> {
+    let id = window_id.unwrap_or(windows::MAIN_WINDOW_ID);
+    windows::check_id(id)?;
+    let (tx, reply) = oneshot::channel();
+    send_runtime_message(RuntimeMessage::CurrentMonitor { id, reply: tx })?;
+
+    pyo3_async_runtimes::tokio::future_into_py(py, async move {
+        let monitor = reply
+            .await
+            .map_err(|_| errors::WindowError::new_err(format!("Window {} is closed", id)))?;
+        Python::with_gil(|py| monitor.map(|monitor| monitor.to_dict(py).map(Bound::unbind)).transpose())
+    })
+}
@@ -0,0 +1,25 @@
+use pyo3::prelude::*;
+
+/// Creates a handler for [`wry::WebViewBuilder::with_navigation_handler`].
+///
+/// `on_navigation(url)` returns whether the WebView may load `url`; only an
+/// explicit `False` blocks it, so returning `None` allows the navigation.
+/// A callback that raises blocks it, keeping a broken allow-list closed.
+/// The initial `url` passed to `create_webframe` is checked as well.
+pub fn handle_navigation(on_navigation: Py<PyAny>) -> impl Fn(String) -> bool + 'static {
+    move |url: String| {
+        Python::with_gil(|py| match on_navigation.call1(py, (url.clone(),)) {
+            Ok(res) => {
+                let allowed = !matches!(res.extract::<Option<bool>>(py), Ok(Some(false)));
+                if !allowed {
+                    log::info!("Blocked navigation to {}", url);
+                }
+                allowed
+            }
+            Err(error) => {
+                log::error!("on_navigation error, blocking {}: {:?}", url, error);
+                false
+            }
+        })
+    }
+}
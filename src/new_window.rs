@@ -0,0 +1,78 @@
+use pyo3::prelude::*;
+
+use crate::RuntimeMessage;
+
+/// What to do with a page's request to open a new window
+/// (`window.open`, `target="_blank"`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NewWindowAction {
+    /// Open the URL in the system browser.
+    Browser,
+    /// Open the URL in a new window managed by this process.
+    Webframe,
+    /// Ignore the request.
+    Deny,
+}
+
+impl NewWindowAction {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "browser" => Some(NewWindowAction::Browser),
+            "webframe" => Some(NewWindowAction::Webframe),
+            "deny" => Some(NewWindowAction::Deny),
+            _ => None,
+        }
+    }
+}
+
+/// Creates a handler for [`wry::WebViewBuilder::with_new_window_req_handler`].
+///
+/// `on_new_window(url)` returns `"browser"`, `"webframe"` or `"deny"`;
+/// returning `None` (or passing no callback) opens the system browser. A
+/// callback that raises or returns anything else denies the request.
+///
+/// The backend never opens its own unmanaged popup: the handler always
+/// answers `false` and carries out the action itself. WebView2, WKWebView
+/// and WebKitGTK report these requests; Android does not.
+pub fn handle_new_window_req(
+    on_new_window: Option<Py<PyAny>>,
+    proxy: tao::event_loop::EventLoopProxy<RuntimeMessage>,
+) -> impl Fn(String) -> bool + 'static {
+    move |url: String| {
+        Python::with_gil(|py| {
+            let action = match &on_new_window {
+                None => NewWindowAction::Browser,
+                Some(callback) => match callback
+                    .call1(py, (url.clone(),))
+                    .and_then(|res| res.extract::<Option<String>>(py))
+                {
+                    Ok(None) => NewWindowAction::Browser,
+                    Ok(Some(name)) => NewWindowAction::parse(&name).unwrap_or_else(|| {
+                        log::warn!("on_new_window returned unknown action {:?}, denying {}", name, url);
+                        NewWindowAction::Deny
+                    }),
+                    Err(error) => {
+                        log::error!("on_new_window error: {:?}", error);
+                        NewWindowAction::Deny
+                    }
+                },
+            };
+
+            match action {
+                NewWindowAction::Browser => {
+                    if let Err(error) = py
+                        .import("webbrowser")
+                        .and_then(|webbrowser| webbrowser.call_method1("open", (url.clone(),)))
+                    {
+                        log::error!("Failed to open {} in the system browser: {:?}", url, error);
+                    }
+                }
+                NewWindowAction::Webframe => {
+                    let _ = proxy.send_event(RuntimeMessage::OpenWindow { url });
+                }
+                NewWindowAction::Deny => log::info!("Denied new window for {}", url),
+            }
+        });
+        false
+    }
+}
@@ -0,0 +1,75 @@
+use std::ops::RangeInclusive;
+
+/// Accepted window opacities, from invisible to opaque; values outside
+/// are clamped.
+pub const RANGE: RangeInclusive<f64> = 0.0..=1.0;
+
+/// Makes the whole `window`, title bar and WebView included, `opacity`
+/// opaque. Platforms without window opacity log a warning instead.
+pub fn set(window: &tao::window::Window, opacity: f64) {
+    let opacity = opacity.clamp(*RANGE.start(), *RANGE.end());
+    if let Err(e) = set_native(window, opacity) {
+        log::warn!("Window opacity {} not applied: {}", opacity, e);
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn set_native(window: &tao::window::Window, opacity: f64) -> Result<(), String> {
+    use tao::platform::windows::WindowExtWindows;
+    use windows_sys::Win32::UI::WindowsAndMessaging::{
+        GetWindowLongPtrW, SetLayeredWindowAttributes, SetWindowLongPtrW, GWL_EXSTYLE, LWA_ALPHA, WS_EX_LAYERED,
+    };
+    let hwnd = window.hwnd() as windows_sys::Win32::Foundation::HWND;
+    let alpha = (opacity * 255.0).round() as u8;
+    // SAFETY: `hwnd` is a live window owned by this thread.
+    let applied = unsafe {
+        let style = GetWindowLongPtrW(hwnd, GWL_EXSTYLE);
+        SetWindowLongPtrW(hwnd, GWL_EXSTYLE, style | WS_EX_LAYERED as isize);
+        SetLayeredWindowAttributes(hwnd, 0, alpha, LWA_ALPHA)
+    };
+    if applied == 0 {
+        return Err(std::io::Error::last_os_error().to_string());
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn set_native(window: &tao::window::Window, opacity: f64) -> Result<(), String> {
+    use objc2::runtime::AnyObject;
+    use tao::platform::macos::WindowExtMacOS;
+    // SAFETY: `ns_window` is the live `NSWindow` of `window`, used on the
+    // main thread.
+    unsafe {
+        let ns_window = &*(window.ns_window() as *const AnyObject);
+        let _: () = objc2::msg_send![ns_window, setAlphaValue: opacity];
+    }
+    Ok(())
+}
+
+#[cfg(any(
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+fn set_native(window: &tao::window::Window, opacity: f64) -> Result<(), String> {
+    use gtk::prelude::WidgetExt;
+    use tao::platform::unix::WindowExtUnix;
+    // Needs a compositing window manager; GTK ignores it otherwise.
+    window.gtk_window().set_opacity(opacity);
+    Ok(())
+}
+
+#[cfg(not(any(
+    target_os = "windows",
+    target_os = "macos",
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd"
+)))]
+fn set_native(_window: &tao::window::Window, _opacity: f64) -> Result<(), String> {
+    Err("not supported on this platform".to_string())
+}
@@ -0,0 +1,101 @@
+use once_cell::sync::Lazy;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Mutex, PoisonError};
+use wry::http::header::{ACCESS_CONTROL_ALLOW_ORIGIN, CONTENT_TYPE};
+use wry::http::{Request, Response, StatusCode};
+
+use crate::js;
+
+/// Custom scheme large `emit_to_js` payloads are fetched from.
+pub const SCHEME: &str = "pyframe";
+
+/// Default of `create_webframe(large_payload_threshold=...)`: payloads of
+/// at least this many bytes of JSON are fetched rather than inlined.
+///
+/// 1 MiB is an unmeasured default, not a benchmarked crossover. Fetching
+/// trades a round trip through the custom protocol for not compiling the
+/// payload as script source; where that pays off depends on the engine
+/// and the data, and has not been timed on any of them.
+pub const DEFAULT_THRESHOLD: usize = 1024 * 1024;
+
+/// Payloads not fetched yet beyond this many are dropped, oldest first,
+/// e.g. those of a page that navigated away before fetching them.
+const MAX_PENDING: usize = 64;
+
+/// Current threshold, 0 inlining every payload.
+static THRESHOLD: AtomicUsize = AtomicUsize::new(0);
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+/// JSON payloads waiting to be fetched, by id.
+static PENDING: Lazy<Mutex<BTreeMap<u64, Vec<u8>>>> = Lazy::new(|| Mutex::new(BTreeMap::new()));
+
+/// Makes payloads of at least `threshold` bytes go through [`SCHEME`],
+/// `None` inlining all of them.
+pub fn set_threshold(threshold: Option<usize>) {
+    THRESHOLD.store(threshold.unwrap_or(0), Ordering::Relaxed);
+    PENDING.lock().unwrap_or_else(PoisonError::into_inner).clear();
+}
+
+/// Script dispatching `event` with the JSON text `json` to the page, like
+/// [`js::emit_script`]. Large payloads are kept for the page to fetch
+/// from [`SCHEME`], so the script stays small.
+///
+/// # Errors
+/// Returns the parse error if `json` is not valid JSON.
+pub fn emit_script(event: &str, json: &str) -> serde_json::Result<String> {
+    let threshold = THRESHOLD.load(Ordering::Relaxed);
+    if threshold == 0 || json.len() < threshold {
+        let payload: serde_json::Value = serde_json::from_str(json)?;
+        return Ok(js::emit_script(event, &payload));
+    }
+    serde_json::from_str::<serde::de::IgnoredAny>(json)?;
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    let mut pending = PENDING.lock().unwrap_or_else(PoisonError::into_inner);
+    pending.insert(id, json.as_bytes().to_vec());
+    while pending.len() > MAX_PENDING {
+        pending.pop_first();
+    }
+    Ok(js::call("window.__emitFetched", &[js::string_literal(event), js::string_literal(&url(id))]))
+}
+
+/// URL of the payload `id`. WebView2 and Android expose custom schemes as
+/// `http://<scheme>.<host>`, the other backends as `<scheme>://<host>`.
+fn url(id: u64) -> String {
+    if cfg!(any(target_os = "windows", target_os = "android")) {
+        format!("http://{}.localhost/{}", SCHEME, id)
+    } else {
+        format!("{}://localhost/{}", SCHEME, id)
+    }
+}
+
+/// Registers [`SCHEME`] on `builder`, serving each payload once.
+pub fn register(builder: wry::WebViewBuilder<'_>) -> wry::WebViewBuilder<'_> {
+    builder.with_custom_protocol(SCHEME.to_string(), |_webview_id, request| respond(request))
+}
+
+fn respond(request: Request<Vec<u8>>) -> Response<Cow<'static, [u8]>> {
+    let payload = request
+        .uri()
+        .path()
+        .trim_start_matches('/')
+        .parse::<u64>()
+        .ok()
+        .and_then(|id| PENDING.lock().unwrap_or_else(PoisonError::into_inner).remove(&id));
+    // Pages served from another origin, e.g. a dev server, fetch
+    // cross-origin.
+    let response = Response::builder().header(ACCESS_CONTROL_ALLOW_ORIGIN, "*");
+    match payload {
+        Some(json) => response.header(CONTENT_TYPE, "application/json").body(Cow::Owned(json)),
+        None => {
+            log::warn!("Payload not found or already fetched: {}", request.uri());
+            response
+                .status(StatusCode::NOT_FOUND)
+                .header(CONTENT_TYPE, "text/plain")
+                .body(Cow::Borrowed(&b"Not Found"[..]))
+        }
+    }
+    .expect("payload response is valid")
+}
@@ -0,0 +1,58 @@
+use pyo3::PyErr;
+use std::path::PathBuf;
+use tokio::sync::oneshot;
+
+/// Prints the page shown in `webview` to a PDF file at `path`, an
+/// absolute path; `reply` gets the outcome once the file is written.
+///
+/// Only WebView2 (Windows) can print to a file without a dialog, so other
+/// platforms reply with `PyNotImplementedError`.
+pub fn print_to_pdf(webview: &wry::WebView, path: PathBuf, reply: oneshot::Sender<Result<(), PyErr>>) {
+    #[cfg(target_os = "windows")]
+    {
+        use std::cell::Cell;
+        use std::rc::Rc;
+        use webview2_com::Microsoft::Web::WebView2::Win32::{ICoreWebView2PrintSettings, ICoreWebView2_7};
+        use windows::core::{Interface, HSTRING};
+        use wry::WebViewExtWindows;
+
+        // Sent from the completion handler, or right away if printing
+        // cannot start.
+        let reply = Rc::new(Cell::new(Some(reply)));
+        let send = {
+            let reply = reply.clone();
+            move |result: Result<(), String>| {
+                if let Some(reply) = reply.take() {
+                    let _ = reply.send(result.map_err(|e| {
+                        crate::errors::WebViewError::new_err(format!("Failed to print to PDF: {}", e))
+                    }));
+                }
+            }
+        };
+        let on_done = send.clone();
+        let handler = webview2_com::PrintToPdfCompletedHandler::create(Box::new(move |result, printed| {
+            on_done(match result {
+                Ok(()) if printed => Ok(()),
+                Ok(()) => Err("the page could not be printed".to_string()),
+                Err(e) => Err(e.to_string()),
+            });
+            Ok(())
+        }));
+        // SAFETY: WebView2 is called on the thread owning the WebView.
+        let started = unsafe {
+            webview.controller().CoreWebView2().and_then(|core| core.cast::<ICoreWebView2_7>()).and_then(|core| {
+                core.PrintToPdf(&HSTRING::from(path.as_os_str()), None::<&ICoreWebView2PrintSettings>, &handler)
+            })
+        };
+        if let Err(e) = started {
+            send(Err(e.to_string()));
+        }
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = (webview, path);
+        let _ = reply.send(Err(pyo3::exceptions::PyNotImplementedError::new_err(
+            "print_to_pdf is only supported with WebView2 on Windows",
+        )));
+    }
+}
@@ -0,0 +1,161 @@
+use pyo3::prelude::*;
+use std::borrow::Cow;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use wry::http::{header::CONTENT_TYPE, Request, Response, StatusCode};
+
+/// Custom scheme the bundled frontend is served from.
+pub const SCHEME: &str = "app";
+
+/// Where `app://` requests are answered from, set with
+/// `create_webframe(asset_root=...)`.
+pub enum AssetSource {
+    /// Files below this (canonical) directory.
+    Dir(PathBuf),
+    /// `callback(path)` returning `bytes`, `str`, or `None` for "not found".
+    Callback(Py<PyAny>),
+}
+
+impl AssetSource {
+    /// Reads `asset_root`: a callable, or a path to a directory.
+    ///
+    /// # Errors
+    /// Returns `PyValueError` if the path does not name a directory.
+    pub fn from_py(py: Python<'_>, value: Py<PyAny>) -> PyResult<Self> {
+        if value.bind(py).is_callable() {
+            return Ok(AssetSource::Callback(value));
+        }
+        let path: PathBuf = py.import("os")?.call_method1("fspath", (value,))?.extract()?;
+        let root = path.canonicalize().ok().filter(|root| root.is_dir()).ok_or_else(|| {
+            pyo3::exceptions::PyValueError::new_err(format!(
+                "asset_root {:?} is not a directory",
+                path
+            ))
+        })?;
+        Ok(AssetSource::Dir(root))
+    }
+
+    fn load(&self, path: &str) -> Option<Vec<u8>> {
+        match self {
+            AssetSource::Dir(root) => {
+                let file = resolve(root, path)?;
+                std::fs::read(file).ok()
+            }
+            AssetSource::Callback(callback) => Python::with_gil(|py| {
+                let result = match callback.call1(py, (path,)) {
+                    Ok(result) => result,
+                    Err(error) => {
+                        log::error!("asset_root callback error for {}: {:?}", path, error);
+                        return None;
+                    }
+                };
+                let result = result.bind(py);
+                if result.is_none() {
+                    None
+                } else if let Ok(text) = result.extract::<String>() {
+                    Some(text.into_bytes())
+                } else {
+                    match result.extract::<Vec<u8>>() {
+                        Ok(bytes) => Some(bytes),
+                        Err(_) => {
+                            log::error!("asset_root callback must return bytes, str or None for {}", path);
+                            None
+                        }
+                    }
+                }
+            }),
+        }
+    }
+}
+
+/// URL of `index.html`. WebView2 and Android expose custom schemes as
+/// `http://<scheme>.<host>`, the other backends as `<scheme>://<host>`.
+pub fn index_url() -> url::Url {
+    let url = if cfg!(any(target_os = "windows", target_os = "android")) {
+        format!("http://{}.localhost/index.html", SCHEME)
+    } else {
+        format!("{}://localhost/index.html", SCHEME)
+    };
+    url::Url::parse(&url).expect("index URL is valid")
+}
+
+/// Registers the `app://` protocol on `builder` when assets are configured.
+pub fn register<'a>(
+    builder: wry::WebViewBuilder<'a>,
+    assets: Option<&Rc<AssetSource>>,
+) -> wry::WebViewBuilder<'a> {
+    let Some(assets) = assets.cloned() else {
+        return builder;
+    };
+    builder.with_custom_protocol(SCHEME.to_string(), move |_webview_id, request| {
+        respond(&assets, request)
+    })
+}
+
+fn respond(assets: &AssetSource, request: Request<Vec<u8>>) -> Response<Cow<'static, [u8]>> {
+    let path = match request.uri().path() {
+        "" | "/" => "/index.html",
+        path => path,
+    };
+    match assets.load(path) {
+        Some(body) => Response::builder()
+            .header(CONTENT_TYPE, content_type(path))
+            .body(Cow::Owned(body)),
+        None => {
+            log::warn!("Asset not found: {}", path);
+            Response::builder()
+                .status(StatusCode::NOT_FOUND)
+                .header(CONTENT_TYPE, "text/plain")
+                .body(Cow::Borrowed(&b"Not Found"[..]))
+        }
+    }
+    .expect("asset response is valid")
+}
+
+/// Maps the URL path `path` to a file below `root`, refusing anything that
+/// escapes it (`..`, symlinks pointing outside).
+fn resolve(root: &Path, path: &str) -> Option<PathBuf> {
+    let path = percent_encoding::percent_decode_str(path).decode_utf8().ok()?;
+    let mut file = root.to_path_buf();
+    for segment in path.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => return None,
+            segment if segment.contains(['\\', ':']) => return None,
+            segment => file.push(segment),
+        }
+    }
+    let file = file.canonicalize().ok()?;
+    (file.starts_with(root) && file.is_file()).then_some(file)
+}
+
+/// Content type sent for `path`, from its extension.
+fn content_type(path: &str) -> &'static str {
+    let extension = Path::new(path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_ascii_lowercase);
+    match extension.as_deref() {
+        Some("html" | "htm") => "text/html",
+        Some("js" | "mjs") => "text/javascript",
+        Some("css") => "text/css",
+        Some("json" | "map") => "application/json",
+        Some("wasm") => "application/wasm",
+        Some("svg") => "image/svg+xml",
+        Some("png") => "image/png",
+        Some("jpg" | "jpeg") => "image/jpeg",
+        Some("gif") => "image/gif",
+        Some("webp") => "image/webp",
+        Some("ico") => "image/x-icon",
+        Some("woff") => "font/woff",
+        Some("woff2") => "font/woff2",
+        Some("ttf") => "font/ttf",
+        Some("otf") => "font/otf",
+        Some("txt") => "text/plain",
+        Some("mp4") => "video/mp4",
+        Some("webm") => "video/webm",
+        Some("mp3") => "audio/mpeg",
+        Some("wav") => "audio/wav",
+        _ => "application/octet-stream",
+    }
+}
@@ -0,0 +1,52 @@
+use wry::{ProxyConfig, ProxyEndpoint};
+
+/// Parses a proxy URL such as `http://proxy.corp:3128` or
+/// `socks5://127.0.0.1:1080`.
+///
+/// # Errors
+/// Returns `PyValueError` for anything but an `http` or `socks5` URL with
+/// a host and port and nothing else: credentials, paths and queries are
+/// not supported by the backends.
+pub fn parse(proxy: &str) -> pyo3::PyResult<ProxyConfig> {
+    let invalid =
+        |reason: &str| pyo3::exceptions::PyValueError::new_err(format!("Invalid proxy {:?}: {}", proxy, reason));
+    let url = url::Url::parse(proxy).map_err(|e| invalid(&e.to_string()))?;
+    if !url.username().is_empty() || url.password().is_some() {
+        return Err(invalid("credentials are not supported"));
+    }
+    if !matches!(url.path(), "" | "/") || url.query().is_some() || url.fragment().is_some() {
+        return Err(invalid("expected only a scheme, host and port"));
+    }
+    let host = url.host_str().ok_or_else(|| invalid("missing host"))?.to_string();
+    let port = url.port_or_known_default().ok_or_else(|| invalid("missing port"))?.to_string();
+    let endpoint = ProxyEndpoint { host, port };
+    match url.scheme() {
+        "http" => Ok(ProxyConfig::Http(endpoint)),
+        "socks5" => Ok(ProxyConfig::Socks5(endpoint)),
+        scheme => Err(invalid(&format!("unsupported scheme {:?} (expected http or socks5)", scheme))),
+    }
+}
+
+/// The WebView2 argument selecting `config`, as wry passes it unless
+/// arguments are given explicitly.
+#[cfg(target_os = "windows")]
+pub fn webview2_arg(config: &ProxyConfig) -> String {
+    match config {
+        ProxyConfig::Http(endpoint) => format!("--proxy-server=http://{}:{}", endpoint.host, endpoint.port),
+        ProxyConfig::Socks5(endpoint) => format!("--proxy-server=socks5://{}:{}", endpoint.host, endpoint.port),
+    }
+}
+
+/// Warns where the WebView ignores the proxy, so traffic would bypass it.
+pub fn warn_unsupported() {
+    if !cfg!(any(
+        target_os = "windows",
+        target_os = "linux",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd"
+    )) {
+        log::warn!("proxy is not supported on this platform, WebView traffic goes out directly");
+    }
+}
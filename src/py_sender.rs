@@ -1,14 +1,65 @@
-use crossbeam_channel::{unbounded, Receiver, Sender};
-use pyo3::{prelude::*, types::PyDict};
-use std::sync::Arc;
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender};
+use once_cell::sync::Lazy;
+use pyo3::prelude::*;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
 use std::time::{Duration, Instant};
 
+use crate::executpy::HandlerLoop;
+use crate::stats;
+
+/// A message sent by the Python thread, tagged with an id when its sender
+/// waits for a reply.
+pub struct Envelope {
+    pub id: Option<u64>,
+    pub msg: Py<PyAny>,
+}
+
+/// Senders waiting in [`SenderHandle::send_and_wait`], keyed by message id.
+static PENDING_REPLIES: Lazy<Mutex<HashMap<u64, Sender<Py<PyAny>>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Locks the waiting senders. Inserting or removing one cannot be left
+/// half done by a panic, so a poisoned lock is recovered instead of taking
+/// every later `send_and_wait` down with it.
+fn pending_replies() -> MutexGuard<'static, HashMap<u64, Sender<Py<PyAny>>>> {
+    PENDING_REPLIES.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+static NEXT_MESSAGE_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Hands `reply` to the Python sender waiting on message `id`.
+///
+/// Called by the Rust consumer of the channel. Replies to ids nobody waits
+/// for any more (the sender timed out) are dropped.
+pub fn reply(id: u64, reply: Py<PyAny>) {
+    match pending_replies().remove(&id) {
+        Some(waiter) => {
+            let _ = waiter.send(reply);
+        }
+        None => log::warn!("Dropping reply to message {}: no sender is waiting", id),
+    }
+}
+
 /// A handle that allows sending messages **from Python to Rust**.
 ///
-/// Exposed to Python as a class. Wraps a `Sender<Py<PyAny>>`.
+/// Exposed to Python as a class. Wraps a `Sender<Envelope>`.
 #[pyclass]
-struct SenderHandle {
-    tx: Arc<Sender<Py<PyAny>>>,
+pub struct SenderHandle {
+    tx: Arc<Sender<Envelope>>,
+    /// Where sends are counted, for the Python thread's handle.
+    stats: Option<&'static stats::Counter>,
+}
+
+impl SenderHandle {
+    fn send_envelope(&self, envelope: Envelope) -> Result<(), crossbeam_channel::SendError<Envelope>> {
+        let sent = self.tx.send(envelope);
+        match self.stats {
+            Some(stats) => stats.count(sent),
+            None => sent,
+        }
+    }
 }
 
 #[pymethods]
@@ -16,20 +67,87 @@ impl SenderHandle {
     /// Send a Python object to the Rust event loop.
     ///
     /// # Errors
-    /// Returns a `PyRuntimeError` if the send operation fails.
+    /// Returns a `ChannelError` if the send operation fails.
     fn send(&self, msg: Py<PyAny>) -> PyResult<()> {
-        self.tx
-            .send(msg)
-            .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(format!("send failed: {e}")))
+        self.send_envelope(Envelope { id: None, msg })
+            .map_err(|e| crate::errors::ChannelError::new_err(format!("send failed: {e}")))
+    }
+
+    /// Send a Python object to the Rust event loop and wait for its reply.
+    ///
+    /// The message is tagged with a fresh id and the call blocks, with the
+    /// GIL released, until the Rust side replies to that id or `timeout`
+    /// seconds pass (`None` waits forever). Called from a coroutine, this
+    /// blocks the whole asyncio loop for that long.
+    ///
+    /// # Errors
+    /// Returns a `PyValueError` for a negative `timeout`, a `PyTimeoutError`
+    /// if no reply arrives in time and a `ChannelError` if the channel is
+    /// closed.
+    #[pyo3(signature = (msg, timeout=None))]
+    fn send_and_wait(&self, py: Python<'_>, msg: Py<PyAny>, timeout: Option<f64>) -> PyResult<Py<PyAny>> {
+        let timeout = timeout
+            .map(Duration::try_from_secs_f64)
+            .transpose()
+            .map_err(|e| pyo3::exceptions::PyValueError::new_err(format!("invalid timeout: {e}")))?;
+
+        let id = NEXT_MESSAGE_ID.fetch_add(1, Ordering::Relaxed);
+        let (waiter, reply_rx) = crossbeam_channel::bounded(1);
+        pending_replies().insert(id, waiter);
+
+        if let Err(e) = self.send_envelope(Envelope { id: Some(id), msg }) {
+            pending_replies().remove(&id);
+            return Err(crate::errors::ChannelError::new_err(format!("send failed: {e}")));
+        }
+
+        let outcome = py.allow_threads(|| match timeout {
+            Some(timeout) => reply_rx.recv_timeout(timeout),
+            None => reply_rx.recv().map_err(|_| RecvTimeoutError::Disconnected),
+        });
+        outcome.map_err(|e| {
+            pending_replies().remove(&id);
+            match e {
+                RecvTimeoutError::Timeout => pyo3::exceptions::PyTimeoutError::new_err(format!(
+                    "no reply to message {id} within the timeout"
+                )),
+                RecvTimeoutError::Disconnected => {
+                    crate::errors::ChannelError::new_err("reply channel closed")
+                }
+            }
+        })
     }
 }
 
 /// A handle that allows receiving messages **from Rust to Python**.
 ///
-/// Exposed to Python as a class. Wraps a `Receiver<Py<PyAny>>`.
+/// Exposed to Python as a class. Wraps a `Receiver<Envelope>`. Receiving a
+/// message sent with [`SenderHandle::send_and_wait`] replies to it with the
+/// message itself, so the sender of a [`create_channel`] pair learns that
+/// it was taken.
 #[pyclass]
-struct ReceiverHandle {
-    rx: Arc<Receiver<Py<PyAny>>>,
+pub struct ReceiverHandle {
+    rx: Arc<Receiver<Envelope>>,
+    /// Keeps the thread of [`ReceiverHandle::on_message`] running; dropping
+    /// it stops the thread.
+    listener: Mutex<Option<Sender<()>>>,
+    /// Where receipts are counted, for the Python thread's handle.
+    stats: Option<&'static stats::Counter>,
+}
+
+impl ReceiverHandle {
+    fn new(rx: Arc<Receiver<Envelope>>, stats: Option<&'static stats::Counter>) -> Self {
+        ReceiverHandle { rx, listener: Mutex::new(None), stats }
+    }
+
+    fn open(py: Python<'_>, envelope: Envelope, stats: Option<&stats::Counter>) -> Py<PyAny> {
+        if let Some(stats) = stats {
+            stats.received();
+        }
+        if let Some(id) = envelope.id {
+            reply(id, envelope.msg.clone_ref(py));
+        }
+        envelope.msg
+    }
 }
 
 #[pymethods]
@@ -37,49 +155,120 @@ impl ReceiverHandle {
     /// Attempt to receive a message without blocking.
     ///
     /// Returns `Some(PyAny)` if a message is available, otherwise `None`.
-    fn recv(&self) -> Option<Py<PyAny>> {
-        self.rx.try_recv().ok()
+    fn recv(&self, py: Python<'_>) -> Option<Py<PyAny>> {
+        self.rx.try_recv().ok().map(|envelope| Self::open(py, envelope, self.stats))
     }
 
     /// Wait until a message is available and return it.
     ///
     /// This method blocks the current thread until a message is received.
-    fn recv_blocking(&self) -> Option<Py<PyAny>> {
-        self.rx.recv().ok()
+    fn recv_blocking(&self, py: Python<'_>) -> Option<Py<PyAny>> {
+        let envelope = py.allow_threads(|| self.rx.recv().ok())?;
+        Some(Self::open(py, envelope, self.stats))
+    }
+
+    /// Wait up to `seconds` for a message, with the GIL released.
+    ///
+    /// Returns `None` on timeout or once the channel is closed, so a
+    /// polling thread can check for shutdown between calls.
+    ///
+    /// # Errors
+    /// Returns a `PyValueError` for a negative `seconds`.
+    fn recv_timeout(&self, py: Python<'_>, seconds: f64) -> PyResult<Option<Py<PyAny>>> {
+        let timeout = Duration::try_from_secs_f64(seconds)
+            .map_err(|e| pyo3::exceptions::PyValueError::new_err(format!("invalid timeout: {e}")))?;
+        let envelope = py.allow_threads(|| self.rx.recv_timeout(timeout).ok());
+        Ok(envelope.map(|envelope| Self::open(py, envelope, self.stats)))
+    }
+
+    /// Deliver every message to `callback(msg)` as it arrives, instead of
+    /// polling with `recv`.
+    ///
+    /// The callback runs on a Rust thread of its own, taking the GIL for
+    /// each message, so it must not block for long; schedule work on an
+    /// asyncio loop with `loop.call_soon_threadsafe` instead. Exceptions
+    /// it raises are logged and delivery goes on. Registering another
+    /// callback replaces this one, and `None` stops delivery; the thread
+    /// also stops once the channel is closed. While a callback is
+    /// registered, `recv` and friends compete with it for messages.
+    #[pyo3(signature = (callback))]
+    fn on_message(&self, callback: Option<Py<PyAny>>) {
+        let mut listener = self.listener.lock().unwrap_or_else(PoisonError::into_inner);
+        // Dropping the previous sender stops its thread.
+        *listener = None;
+        let Some(callback) = callback else {
+            return;
+        };
+        let (keep_alive, stopped) = crossbeam_channel::bounded::<()>(0);
+        *listener = Some(keep_alive);
+        let rx = self.rx.clone();
+        let stats = self.stats;
+        std::thread::spawn(move || loop {
+            crossbeam_channel::select! {
+                recv(rx) -> envelope => {
+                    let Ok(envelope) = envelope else {
+                        break;
+                    };
+                    Python::with_gil(|py| {
+                        let msg = Self::open(py, envelope, stats);
+                        if let Err(error) = callback.call1(py, (msg,)) {
+                            log::error!("on_message callback error: {:?}", error);
+                        }
+                    });
+                }
+                recv(stopped) -> _ => break,
+            }
+        });
     }
 }
 
+/// Creates a connected `(SenderHandle, ReceiverHandle)` pair over an
+/// unbounded channel, e.g. to pass objects between Python threads.
+#[pyfunction]
+pub fn create_channel(py: Python<'_>) -> PyResult<(Py<SenderHandle>, Py<ReceiverHandle>)> {
+    let (tx, rx) = crossbeam_channel::unbounded();
+    Ok((
+        Py::new(py, SenderHandle { tx: Arc::new(tx), stats: None })?,
+        Py::new(py, ReceiverHandle::new(Arc::new(rx), None))?,
+    ))
+}
+
 
 
 fn spawn_py_event_loop(
     py_event_loop: Py<PyAny>,
     pyevent_to_rust_queue: Py<PyAny>,
     rust_to_py_ipc: Py<PyAny>,
-    tx_from_py_to_rust: Arc<Sender<Py<PyAny>>>,
-    rx_from_rust_to_py: Arc<Receiver<Py<PyAny>>>,
-) -> anyhow::Result<std::thread::JoinHandle<()>> {
+    tx_from_py_to_rust: Arc<Sender<Envelope>>,
+    rx_from_rust_to_py: Arc<Receiver<Envelope>>,
+    handler_loop: HandlerLoop,
+) -> anyhow::Result<std::thread::JoinHandle<PyResult<()>>> {
     let handle = std::thread::spawn(move || {
         Python::with_gil(|py| -> PyResult<()> {
-            println!("[PY] Python-Thread started");
+            log::info!("[PY] Python-Thread started");
 
             let asyncio = py.import("asyncio")?;
             let loop_obj = py_event_loop.bind(py);
 
             // Set the event loop
             asyncio.call_method1("set_event_loop", (loop_obj.clone(),))?;
+            // IPC handler coroutines run on this loop too.
+            handler_loop.set(py, loop_obj)?;
+            if handler_loop.is_stopped() {
+                return Ok(());
+            }
 
             // Create sender and receiver handles
             let sender = Py::new(
                 py,
                 SenderHandle {
                     tx: tx_from_py_to_rust.clone(),
+                    stats: Some(&stats::FROM_PYTHON),
                 },
             )?;
             let receiver = Py::new(
                 py,
-                ReceiverHandle {
-                    rx: rx_from_rust_to_py.clone(),
-                },
+                ReceiverHandle::new(rx_from_rust_to_py.clone(), Some(&stats::TO_PYTHON)),
             )?;
 
             // Register Python tasks
@@ -94,8 +283,212 @@ fn spawn_py_event_loop(
 
             Ok(())
         })
-        .unwrap_or_else(|e| eprintln!("Python thread error: {:?}", e));
+        .inspect_err(|e| log::error!("Python thread error: {:?}", e))
     });
 
     Ok(handle)
 }
+
+/// State of the supervised Python event loop thread.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum LoopState {
+    /// The loop is running (or being restarted).
+    Running = 0,
+    /// `run_forever` returned normally, e.g. after `loop.stop()`.
+    Stopped = 1,
+    /// The thread raised or panicked.
+    Crashed = 2,
+}
+
+static LOOP_STATE: AtomicU8 = AtomicU8::new(LoopState::Stopped as u8);
+
+impl LoopState {
+    fn set(self) {
+        LOOP_STATE.store(self as u8, Ordering::SeqCst);
+    }
+
+    /// Current state of the Python event loop thread; `Stopped` if none
+    /// was configured.
+    pub fn current() -> Self {
+        match LOOP_STATE.load(Ordering::SeqCst) {
+            0 => LoopState::Running,
+            2 => LoopState::Crashed,
+            _ => LoopState::Stopped,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            LoopState::Running => "running",
+            LoopState::Stopped => "stopped",
+            LoopState::Crashed => "crashed",
+        }
+    }
+}
+
+/// What the supervisor does once the Python event loop thread exits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RestartPolicy {
+    /// Start the thread again on a fresh asyncio loop.
+    Restart,
+    /// Close the window and exit the application.
+    Shutdown,
+}
+
+impl RestartPolicy {
+    /// Parses a policy name as passed from Python (`"restart"` or `"shutdown"`).
+    ///
+    /// # Errors
+    /// Returns `PyValueError` for unknown policy names.
+    pub fn parse(name: &str) -> PyResult<Self> {
+        match name {
+            "restart" => Ok(RestartPolicy::Restart),
+            "shutdown" => Ok(RestartPolicy::Shutdown),
+            other => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Unknown restart policy: {:?} (expected \"restart\" or \"shutdown\")",
+                other
+            ))),
+        }
+    }
+}
+
+/// Threads that exit sooner than this after starting are restarted only
+/// after a delay, so a loop that crashes on startup does not spin. The
+/// delay starts at [`RESTART_DELAY`] and doubles with each such exit in a
+/// row, up to [`MAX_RESTART_DELAY`]; a shutdown cuts it short.
+const MIN_UPTIME: Duration = Duration::from_secs(5);
+const RESTART_DELAY: Duration = Duration::from_secs(1);
+const MAX_RESTART_DELAY: Duration = Duration::from_secs(30);
+
+/// Runs [`spawn_py_event_loop`] under a supervisor thread.
+///
+/// When the Python thread exits, cleanly or by raising, the outcome is
+/// recorded in [`LoopState`] and `policy` decides what happens next:
+/// - [`RestartPolicy::Restart`] spawns the thread again on a *new* asyncio
+///   loop, re-importing asyncio and recreating the sender/receiver handles
+///   and both coroutines. Tasks, loop-local state and any message the old
+///   coroutines had taken off a channel but not processed are lost; the
+///   channels themselves, and messages still queued in them, survive.
+/// - [`RestartPolicy::Shutdown`] calls `shutdown` and stops supervising.
+///
+/// Once `handler_loop` is stopped, by the `shutdown()` pyfunction, the
+/// supervisor returns without consulting `policy`.
+///
+/// A crash, by raising or panicking, is reported to `on_crash(error,
+/// restarting)` first, if given.
+///
+/// Each run's loop is installed as `handler_loop` while the thread is
+/// alive, so IPC handler coroutines share it with the two coroutines.
+#[allow(clippy::too_many_arguments)]
+pub fn supervise_py_event_loop(
+    py_event_loop: Py<PyAny>,
+    pyevent_to_rust_queue: Py<PyAny>,
+    rust_to_py_ipc: Py<PyAny>,
+    tx_from_py_to_rust: Arc<Sender<Envelope>>,
+    rx_from_rust_to_py: Arc<Receiver<Envelope>>,
+    handler_loop: HandlerLoop,
+    policy: RestartPolicy,
+    on_crash: Option<Py<PyAny>>,
+    shutdown: impl FnOnce() + Send + 'static,
+) -> std::thread::JoinHandle<()> {
+    std::thread::spawn(move || {
+        let mut py_event_loop = Some(py_event_loop);
+        let mut restart_delay = RESTART_DELAY;
+        loop {
+            let started = Python::with_gil(|py| -> PyResult<_> {
+                let loop_obj = match py_event_loop.take() {
+                    Some(loop_obj) => loop_obj,
+                    None => py.import("asyncio")?.call_method0("new_event_loop")?.unbind(),
+                };
+                Ok((
+                    loop_obj,
+                    pyevent_to_rust_queue.clone_ref(py),
+                    rust_to_py_ipc.clone_ref(py),
+                ))
+            });
+            let (loop_obj, queue, ipc) = match started {
+                Ok(handles) => handles,
+                Err(e) => {
+                    log::error!("Failed to create Python event loop: {:?}", e);
+                    LoopState::Crashed.set();
+                    shutdown();
+                    return;
+                }
+            };
+
+            LoopState::Running.set();
+            let started_at = Instant::now();
+            let outcome = spawn_py_event_loop(
+                loop_obj,
+                queue,
+                ipc,
+                tx_from_py_to_rust.clone(),
+                rx_from_rust_to_py.clone(),
+                handler_loop.clone(),
+            )
+            .map(|handle| handle.join());
+            handler_loop.clear();
+            let crash = match outcome {
+                Ok(Ok(Ok(()))) => None,
+                Ok(Ok(Err(error))) => Some(
+                    Python::with_gil(|py| crate::events::format_traceback(py, &error))
+                        .unwrap_or_else(|_| error.to_string()),
+                ),
+                Ok(Err(_)) => Some("Python event loop thread panicked".to_string()),
+                Err(error) => Some(format!("Failed to start Python event loop thread: {}", error)),
+            };
+            let state = if crash.is_some() { LoopState::Crashed } else { LoopState::Stopped };
+            state.set();
+            if handler_loop.is_stopped() {
+                log::info!("[PY] Python event loop thread stopped on shutdown");
+                return;
+            }
+            log::warn!("Python event loop thread exited ({})", state.name());
+            if let (Some(on_crash), Some(error)) = (&on_crash, crash) {
+                crate::events::notify(on_crash, (error, policy == RestartPolicy::Restart));
+            }
+
+            match policy {
+                RestartPolicy::Restart => {
+                    if started_at.elapsed() < MIN_UPTIME {
+                        log::info!("[PY] Restarting Python event loop thread in {:?}", restart_delay);
+                        if handler_loop.wait_stopped(restart_delay) {
+                            log::info!("[PY] Python event loop thread not restarted on shutdown");
+                            return;
+                        }
+                        restart_delay = (restart_delay * 2).min(MAX_RESTART_DELAY);
+                    } else {
+                        restart_delay = RESTART_DELAY;
+                    }
+                    log::info!("[PY] Restarting Python event loop thread");
+                }
+                RestartPolicy::Shutdown => {
+                    shutdown();
+                    return;
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pyo3::types::PyDict;
+
+    #[test]
+    fn create_channel_passes_objects_between_handles() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let (sender, receiver) = create_channel(py).unwrap();
+            let msg = PyDict::new(py);
+            msg.set_item("n", 1).unwrap();
+            sender.borrow(py).send(msg.clone().into_any().unbind()).unwrap();
+
+            let received = receiver.borrow(py).recv(py).expect("a message");
+            assert!(received.bind(py).is(&msg));
+            assert!(receiver.borrow(py).recv(py).is_none());
+        });
+    }
+}
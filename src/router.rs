@@ -0,0 +1,196 @@
+use once_cell::sync::Lazy;
+use pyo3::prelude::*;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// One part of a route pattern, between slashes.
+#[derive(Debug, PartialEq)]
+enum Segment {
+    /// Matches this text exactly.
+    Literal(String),
+    /// `{name}`: matches any single segment.
+    Param(String),
+    /// `{*name}`: matches the rest of the path, possibly empty.
+    Rest(String),
+}
+
+/// A handler registered with [`register_route`].
+struct Route {
+    /// Upper-case HTTP method, or any method.
+    method: Option<String>,
+    pattern: String,
+    segments: Vec<Segment>,
+    handler: Arc<Py<PyAny>>,
+}
+
+/// A matched route's handler and path parameters.
+pub type Match = (Arc<Py<PyAny>>, BTreeMap<String, String>);
+
+/// The routes, tried in registration order.
+static ROUTES: Lazy<Mutex<Vec<Route>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+/// Parses a route pattern such as `/users/{id}` or `/files/{*path}`.
+///
+/// # Errors
+/// Returns `PyValueError` for a pattern not starting with `/`, an empty
+/// or unclosed parameter, and `{*name}` anywhere but at the end.
+fn parse_pattern(pattern: &str) -> PyResult<Vec<Segment>> {
+    let invalid = |reason: &str| {
+        pyo3::exceptions::PyValueError::new_err(format!("Invalid route pattern {:?}: {}", pattern, reason))
+    };
+    let Some(path) = pattern.strip_prefix('/') else {
+        return Err(invalid("it must start with /"));
+    };
+    let parts: Vec<&str> = path.split('/').collect();
+    let mut segments = Vec::with_capacity(parts.len());
+    for (i, part) in parts.iter().enumerate() {
+        let segment = match part.strip_prefix('{') {
+            None if part.contains(['{', '}']) => return Err(invalid("braces must enclose a whole segment")),
+            None => Segment::Literal(part.to_string()),
+            Some(name) => {
+                let name = name.strip_suffix('}').ok_or_else(|| invalid("unclosed {"))?;
+                match name.strip_prefix('*') {
+                    Some(_) if i + 1 != parts.len() => return Err(invalid("{*name} must come last")),
+                    Some(rest) if !rest.is_empty() => Segment::Rest(rest.to_string()),
+                    None if !name.is_empty() => Segment::Param(name.to_string()),
+                    _ => return Err(invalid("parameters need a name")),
+                }
+            }
+        };
+        segments.push(segment);
+    }
+    Ok(segments)
+}
+
+/// Matches `path` against `segments`, returning the percent-decoded
+/// parameters.
+fn match_path(segments: &[Segment], path: &str) -> Option<BTreeMap<String, String>> {
+    let decode = |part: &str| percent_encoding::percent_decode_str(part).decode_utf8_lossy().into_owned();
+    let mut parts = path.strip_prefix('/').unwrap_or(path).split('/');
+    let mut params = BTreeMap::new();
+    for segment in segments {
+        match segment {
+            Segment::Literal(literal) => {
+                if parts.next()? != literal {
+                    return None;
+                }
+            }
+            Segment::Param(name) => {
+                let part = parts.next().filter(|part| !part.is_empty())?;
+                params.insert(name.clone(), decode(part));
+            }
+            Segment::Rest(name) => {
+                let rest = parts.by_ref().collect::<Vec<_>>().join("/");
+                params.insert(name.clone(), decode(&rest));
+            }
+        }
+    }
+    parts.next().is_none().then_some(params)
+}
+
+/// Finds the first route matching `method` and `path`, returning its
+/// handler and the path parameters.
+pub fn find(method: &str, path: &str) -> Option<Match> {
+    let routes = ROUTES.lock().unwrap_or_else(PoisonError::into_inner);
+    routes.iter().find_map(|route| {
+        if route.method.as_deref().is_some_and(|expected| !expected.eq_ignore_ascii_case(method)) {
+            return None;
+        }
+        match_path(&route.segments, path).map(|params| (route.handler.clone(), params))
+    })
+}
+
+/// Sends IPC requests naming a path that matches `pattern` to `handler`
+/// instead of the window's handler. Pages name the path with
+/// `window.ipcRoute(path, payload, method="POST")`, which posts `{"route":
+/// path, "method": method, "payload": payload}`; requests without a
+/// `route`, such as those of `window.invoke`, never match, whatever the
+/// page's URL.
+///
+/// `pattern` is a path such as `/users/{id}`, where `{id}` matches one
+/// segment, and may end with `{*rest}` matching the remaining ones; the
+/// matched values, decoded, are passed as the request's `route_params`.
+/// With `method`, only requests naming that method match.
+///
+/// `handler` is called like the `create_webframe` handler, with the same
+/// encoded request, and its return value settles the `ipcRoute` promise.
+/// Routes are tried in registration order and serve every window.
+/// Registering the same pattern and method again replaces the handler;
+/// `None` removes the route.
+///
+/// # Errors
+/// Raises `PyValueError` for a malformed pattern.
+#[pyfunction]
+#[pyo3(signature = (pattern, handler, *, method=None))]
+pub fn register_route(pattern: String, handler: Option<Py<PyAny>>, method: Option<String>) -> PyResult<()> {
+    let segments = parse_pattern(&pattern)?;
+    let method = method.map(|method| method.to_ascii_uppercase());
+    let mut routes = ROUTES.lock().unwrap_or_else(PoisonError::into_inner);
+    let existing = routes.iter().position(|route| route.pattern == pattern && route.method == method);
+    match (handler, existing) {
+        (Some(handler), Some(i)) => routes[i].handler = Arc::new(handler),
+        (Some(handler), None) => routes.push(Route { method, pattern, segments, handler: Arc::new(handler) }),
+        (None, Some(i)) => {
+            routes.remove(i);
+        }
+        (None, None) => {}
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs.iter().map(|&(name, value)| (name.to_string(), value.to_string())).collect()
+    }
+
+    #[test]
+    fn parses_patterns() {
+        assert_eq!(parse_pattern("/").unwrap(), vec![Segment::Literal(String::new())]);
+        assert_eq!(
+            parse_pattern("/users/{id}/files/{*path}").unwrap(),
+            vec![
+                Segment::Literal("users".to_string()),
+                Segment::Param("id".to_string()),
+                Segment::Literal("files".to_string()),
+                Segment::Rest("path".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_patterns() {
+        for pattern in ["users", "/{}", "/{*}", "/{id", "/a{id}", "/{*rest}/more"] {
+            assert!(parse_pattern(pattern).is_err(), "{}", pattern);
+        }
+    }
+
+    #[test]
+    fn matches_literals_and_params() {
+        let segments = parse_pattern("/users/{id}").unwrap();
+        assert_eq!(match_path(&segments, "/users/7"), Some(params(&[("id", "7")])));
+        assert_eq!(match_path(&segments, "/users/a%20b"), Some(params(&[("id", "a b")])));
+        assert_eq!(match_path(&segments, "/users/"), None);
+        assert_eq!(match_path(&segments, "/users"), None);
+        assert_eq!(match_path(&segments, "/users/7/more"), None);
+        assert_eq!(match_path(&segments, "/groups/7"), None);
+    }
+
+    #[test]
+    fn matches_the_rest_of_the_path() {
+        let segments = parse_pattern("/files/{*path}").unwrap();
+        assert_eq!(match_path(&segments, "/files/a/b.txt"), Some(params(&[("path", "a/b.txt")])));
+        assert_eq!(match_path(&segments, "/files/"), Some(params(&[("path", "")])));
+        assert_eq!(match_path(&segments, "/files"), Some(params(&[("path", "")])));
+        assert_eq!(match_path(&segments, "/other/a"), None);
+    }
+
+    #[test]
+    fn matches_the_root_only_at_the_root() {
+        let segments = parse_pattern("/").unwrap();
+        assert_eq!(match_path(&segments, "/"), Some(BTreeMap::new()));
+        assert_eq!(match_path(&segments, "/index.html"), None);
+    }
+}
@@ -0,0 +1,151 @@
+use once_cell::sync::Lazy;
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList, PyTuple};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use crate::js;
+
+/// Error codes defined by JSON-RPC 2.0.
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const INTERNAL_ERROR: i64 = -32603;
+/// First code of the range reserved for implementation-defined errors,
+/// used for exceptions raised by methods.
+const SERVER_ERROR: i64 = -32000;
+
+/// Python methods callable through JSON-RPC, by name.
+static METHODS: Lazy<Mutex<HashMap<String, Arc<Py<PyAny>>>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// A JSON-RPC 2.0 request posted by a page, e.g. through `window.rpc`.
+#[derive(Debug, Deserialize)]
+pub struct Call {
+    jsonrpc: String,
+    pub method: String,
+    #[serde(default)]
+    params: Option<serde_json::Value>,
+    /// Absent for notifications, which get no response.
+    #[serde(default)]
+    pub id: Option<serde_json::Value>,
+}
+
+/// Parses `body` as a JSON-RPC request. Anything else, including batches,
+/// gives `None` and goes to the IPC handler as before.
+pub fn parse(body: &str) -> Option<Call> {
+    if !body.trim_start().starts_with('{') {
+        return None;
+    }
+    serde_json::from_str::<Call>(body).ok().filter(|call| call.jsonrpc == "2.0")
+}
+
+/// The error member of a JSON-RPC response.
+#[derive(Debug, Serialize)]
+pub struct Fault {
+    code: i64,
+    message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    data: Option<serde_json::Value>,
+}
+
+impl Fault {
+    fn new(code: i64, message: String) -> Self {
+        Fault { code, message, data: None }
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// The fault for an exception raised by a method: its message, with
+    /// the exception type as `data.type`.
+    fn raised(py: Python<'_>, error: &PyErr) -> Self {
+        let kind = error.get_type(py).name().map(|name| name.to_string()).unwrap_or_default();
+        Fault {
+            code: SERVER_ERROR,
+            message: error.value(py).to_string(),
+            data: Some(serde_json::json!({ "type": kind })),
+        }
+    }
+}
+
+/// Calls the method named by `call` with its params: an array as
+/// positional arguments, an object as keyword arguments.
+///
+/// # Errors
+/// Returns the fault to respond with for an unknown method or params of
+/// another type. An exception raised by the method is returned as is.
+pub fn invoke(py: Python<'_>, call: &Call) -> Result<PyResult<Py<PyAny>>, Fault> {
+    let method = METHODS.lock().unwrap_or_else(PoisonError::into_inner).get(&call.method).cloned();
+    let Some(method) = method else {
+        return Err(Fault::new(METHOD_NOT_FOUND, format!("Method not found: {}", call.method)));
+    };
+    let params = match &call.params {
+        Some(params @ (serde_json::Value::Array(_) | serde_json::Value::Object(_))) => Some(params),
+        Some(_) => return Err(Fault::new(INVALID_PARAMS, "Params must be an array or an object".to_string())),
+        None => None,
+    };
+    let params = params
+        .map(|params| py.import("json")?.call_method1("loads", (params.to_string(),)))
+        .transpose()
+        .map_err(|e| Fault::new(INVALID_PARAMS, e.to_string()))?;
+    Ok(match params {
+        Some(params) if params.is_instance_of::<PyList>() => {
+            let args = PyTuple::new(py, params.downcast::<PyList>().expect("checked list"))
+                .map_err(|e| Fault::new(INVALID_PARAMS, e.to_string()))?;
+            method.call1(py, args)
+        }
+        Some(params) => method.call(py, (), Some(params.downcast::<PyDict>().expect("checked object"))),
+        None => method.call0(py),
+    })
+}
+
+/// Builds the script passing the response to `call` `id` to
+/// `window.__rpcReply` (see `assets/py.js`).
+pub fn response_script(py: Python<'_>, id: &serde_json::Value, outcome: Result<PyResult<Py<PyAny>>, Fault>) -> String {
+    let result = match outcome {
+        Ok(Ok(value)) => to_value(py, value).map_err(|e| Fault::new(INTERNAL_ERROR, e.to_string())),
+        Ok(Err(error)) => Err(Fault::raised(py, &error)),
+        Err(fault) => Err(fault),
+    };
+    let response = match result {
+        Ok(result) => serde_json::json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(fault) => serde_json::json!({ "jsonrpc": "2.0", "id": id, "error": fault }),
+    };
+    js::call("window.__rpcReply", &[js::value_literal(&response)])
+}
+
+/// Converts a method's return value to JSON with Python's `json.dumps`.
+fn to_value(py: Python<'_>, value: Py<PyAny>) -> PyResult<serde_json::Value> {
+    let json: String = py.import("json")?.call_method1("dumps", (value,))?.extract()?;
+    serde_json::from_str(&json).map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))
+}
+
+/// Makes `handler` callable from the page as the JSON-RPC method `name`,
+/// e.g. `await window.rpc("add", [1, 2])` for `register_method("add",
+/// lambda a, b: a + b)`. Registering a name again replaces its handler,
+/// and `None` removes it. Methods can be registered before or while the
+/// webframe runs, and serve every window.
+///
+/// Requests are JSON bodies posted with `"jsonrpc": "2.0"`, such as those
+/// of `window.rpc(method, params)` and `window.rpcNotify(method, params)`;
+/// they skip the IPC handler. Params given as an array are passed as
+/// positional arguments, as an object as keyword arguments. The return
+/// value, awaited if the handler is `async def`, must be JSON-serializable.
+/// An exception is returned as error -32000 with its message and type
+/// (`data.type`), and passed to `on_error`; an unknown method gives
+/// -32601. Requests without an `id` are notifications and get no response.
+/// Batches are not supported.
+#[pyfunction]
+#[pyo3(signature = (name, handler))]
+pub fn register_method(name: String, handler: Option<Py<PyAny>>) {
+    let mut methods = METHODS.lock().unwrap_or_else(PoisonError::into_inner);
+    match handler {
+        Some(handler) => {
+            methods.insert(name, Arc::new(handler));
+        }
+        None => {
+            methods.remove(&name);
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use std::ops::Deref;
+use tokio::runtime::Runtime;
+
+/// The tokio runtime running a webframe's background tasks: message
+/// consumers, reply forwarding, hot reload, the heartbeat and IPC replies.
+pub enum TaskRuntime {
+    /// Created for this webframe and shut down when it returns.
+    Owned(Runtime),
+    /// The process-wide runtime of pyo3-async-runtimes, which also drives
+    /// the awaitables returned by pyfunctions such as `eval_js`.
+    Shared(&'static Runtime),
+}
+
+impl TaskRuntime {
+    /// Builds the runtime for `create_webframe(runtime_threads=threads)`:
+    /// by default a multi-threaded runtime with one worker per core,
+    /// `Some(0)` the shared runtime, and `Some(n)` `n` workers.
+    ///
+    /// # Errors
+    /// Returns `PyRuntimeError` if the runtime cannot be started.
+    pub fn new(threads: Option<usize>) -> pyo3::PyResult<Self> {
+        let runtime = match threads {
+            Some(0) => return Ok(TaskRuntime::Shared(pyo3_async_runtimes::tokio::get_runtime())),
+            Some(threads) => tokio::runtime::Builder::new_multi_thread()
+                .worker_threads(threads)
+                .thread_name("pyframe-tokio")
+                .enable_all()
+                .build(),
+            None => Runtime::new(),
+        };
+        runtime
+            .map(TaskRuntime::Owned)
+            .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))
+    }
+}
+
+impl Deref for TaskRuntime {
+    type Target = Runtime;
+
+    fn deref(&self) -> &Runtime {
+        match self {
+            TaskRuntime::Owned(runtime) => runtime,
+            TaskRuntime::Shared(runtime) => runtime,
+        }
+    }
+}
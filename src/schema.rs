@@ -0,0 +1,287 @@
+use pyo3::exceptions::PyValueError;
+use pyo3::PyResult;
+use serde::de::IgnoredAny;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::{codec, Message};
+
+/// How strictly `emit_*` check messages, set by
+/// `create_webframe(message_validation=...)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Validation {
+    /// Accept anything that decodes, dropping unknown fields.
+    Off = 0,
+    /// Also require `timestamp`, when present, to be RFC 3339.
+    Timestamps = 1,
+    /// Also reject fields `Message` does not have.
+    Strict = 2,
+}
+
+static VALIDATION: AtomicU8 = AtomicU8::new(Validation::Off as u8);
+
+/// Whether messages without a timestamp get the time of the emit, set by
+/// `create_webframe(auto_timestamp=...)`.
+static AUTO_TIMESTAMP: AtomicBool = AtomicBool::new(true);
+
+/// Sets whether later emits stamp messages that have no timestamp.
+pub fn set_auto_timestamp(enabled: bool) {
+    AUTO_TIMESTAMP.store(enabled, Ordering::Relaxed);
+}
+
+impl Validation {
+    /// # Errors
+    /// Returns `PyValueError` for names other than `"off"`, `"timestamps"`
+    /// and `"strict"`.
+    pub fn parse(name: &str) -> PyResult<Self> {
+        match name {
+            "off" => Ok(Validation::Off),
+            "timestamps" => Ok(Validation::Timestamps),
+            "strict" => Ok(Validation::Strict),
+            other => Err(PyValueError::new_err(format!(
+                "Unknown message validation: {:?} (expected \"off\", \"timestamps\" or \"strict\")",
+                other
+            ))),
+        }
+    }
+
+    /// Makes this the validation of every later emit.
+    pub fn set(self) {
+        VALIDATION.store(self as u8, Ordering::Relaxed);
+    }
+
+    fn current() -> Self {
+        match VALIDATION.load(Ordering::Relaxed) {
+            1 => Validation::Timestamps,
+            2 => Validation::Strict,
+            _ => Validation::Off,
+        }
+    }
+}
+
+/// A [`Message`] along with the fields it does not have.
+#[derive(Deserialize)]
+struct Fields {
+    #[serde(flatten)]
+    message: Message,
+    #[serde(flatten)]
+    unknown: BTreeMap<String, IgnoredAny>,
+}
+
+/// Decodes an emitted message, checked as the current [`Validation`] asks
+/// and stamped with the current time if it has no timestamp, unless
+/// [`set_auto_timestamp`] turned that off.
+///
+/// # Errors
+/// Returns `PyValueError` if the message does not decode, has a timestamp
+/// that is not RFC 3339, or, in strict mode, unknown fields.
+pub fn decode(encoded: &codec::EncodedMessage) -> PyResult<Message> {
+    decode_as(Validation::current(), encoded)
+}
+
+/// Decodes an emitted message as [`decode`] does, checked as `validation`
+/// asks.
+fn decode_as(validation: Validation, encoded: &codec::EncodedMessage) -> PyResult<Message> {
+    let mut message = match validation {
+        Validation::Off | Validation::Timestamps => encoded.decode::<Message>()?,
+        Validation::Strict => {
+            let fields = encoded.decode::<Fields>()?;
+            if !fields.unknown.is_empty() {
+                let names = fields.unknown.keys().map(String::as_str).collect::<Vec<_>>().join(", ");
+                return Err(PyValueError::new_err(format!(
+                    "Unknown message fields: {} (expected message, timestamp and payload)",
+                    names
+                )));
+            }
+            fields.message
+        }
+    };
+    let invalid = |timestamp: &&str| validation != Validation::Off && !is_rfc3339(timestamp);
+    if let Some(timestamp) = message.timestamp.as_deref().filter(invalid) {
+        return Err(PyValueError::new_err(format!(
+            "Invalid message timestamp {:?}: expected RFC 3339, e.g. \"2024-05-01T12:30:00Z\"",
+            timestamp
+        )));
+    }
+    if message.timestamp.is_none() && AUTO_TIMESTAMP.load(Ordering::Relaxed) {
+        message.timestamp = Some(now_rfc3339());
+    }
+    Ok(message)
+}
+
+/// The current UTC time in RFC 3339 with milliseconds, e.g.
+/// `2024-05-01T12:30:00.250Z`.
+fn now_rfc3339() -> String {
+    format_rfc3339(SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default())
+}
+
+/// The UTC time `elapsed` after the Unix epoch in RFC 3339 with
+/// milliseconds.
+fn format_rfc3339(elapsed: Duration) -> String {
+    let seconds = elapsed.as_secs();
+    let (days, time) = (seconds / 86_400, seconds % 86_400);
+    // Civil date of `days` since 1970-01-01, after Howard Hinnant's
+    // `civil_from_days`, counting in 400-year eras from 0000-03-01.
+    let days = days as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        time / 3_600,
+        time % 3_600 / 60,
+        time % 60,
+        elapsed.subsec_millis()
+    )
+}
+
+/// Whether `text` is an RFC 3339 date-time such as
+/// `2024-05-01T12:30:00.5+02:00`.
+fn is_rfc3339(text: &str) -> bool {
+    let bytes = text.as_bytes();
+    let number = |range: std::ops::Range<usize>| -> Option<u32> {
+        let digits = bytes.get(range)?;
+        digits
+            .iter()
+            .all(u8::is_ascii_digit)
+            .then(|| digits.iter().fold(0, |n, digit| n * 10 + u32::from(digit - b'0')))
+    };
+    let separators = [(4, b'-'), (7, b'-'), (13, b':'), (16, b':')];
+    if bytes.len() < 20
+        || separators.iter().any(|&(i, separator)| bytes[i] != separator)
+        || !matches!(bytes[10], b'T' | b't' | b' ')
+    {
+        return false;
+    }
+    let (Some(year), Some(month), Some(day), Some(hour), Some(minute), Some(second)) =
+        (number(0..4), number(5..7), number(8..10), number(11..13), number(14..16), number(17..19))
+    else {
+        return false;
+    };
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let days = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if leap => 29,
+        2 => 28,
+        _ => return false,
+    };
+    if !(1..=days).contains(&day) || hour > 23 || minute > 59 || second > 60 {
+        return false;
+    }
+    let mut rest = &text[19..];
+    if let Some(fraction) = rest.strip_prefix('.') {
+        let digits = fraction.bytes().take_while(u8::is_ascii_digit).count();
+        if digits == 0 {
+            return false;
+        }
+        rest = &fraction[digits..];
+    }
+    let zone = text.len() - rest.len();
+    match rest.as_bytes() {
+        [b'Z' | b'z'] => true,
+        [b'+' | b'-', _, _, b':', _, _] => {
+            number(zone + 1..zone + 3).is_some_and(|hours| hours <= 23)
+                && number(zone + 4..zone + 6).is_some_and(|minutes| minutes <= 59)
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stamps_messages_without_timestamp_when_validation_is_off() {
+        assert_eq!(Validation::current(), Validation::Off);
+        let encoded = codec::EncodedMessage::Json(r#"{"message": "hello"}"#.to_string());
+        let timestamp = decode(&encoded).unwrap().timestamp.expect("stamped");
+        assert!(is_rfc3339(&timestamp), "{}", timestamp);
+    }
+
+    #[test]
+    fn formats_dates_across_leap_days() {
+        let at = |seconds: u64, millis: u64| format_rfc3339(Duration::from_millis(seconds * 1_000 + millis));
+        assert_eq!(at(0, 0), "1970-01-01T00:00:00.000Z");
+        assert_eq!(at(951_782_400, 0), "2000-02-29T00:00:00.000Z");
+        assert_eq!(at(1_677_628_799, 999), "2023-02-28T23:59:59.999Z");
+        assert_eq!(at(1_677_628_800, 0), "2023-03-01T00:00:00.000Z");
+        assert_eq!(at(1_709_164_800, 0), "2024-02-29T00:00:00.000Z");
+        assert_eq!(at(1_714_566_600, 250), "2024-05-01T12:30:00.250Z");
+        assert_eq!(at(4_107_542_400, 0), "2100-03-01T00:00:00.000Z");
+    }
+
+    #[test]
+    fn accepts_rfc3339_date_times() {
+        for text in [
+            "2024-05-01T12:30:00Z",
+            "2024-05-01t12:30:00z",
+            "2024-05-01 12:30:00Z",
+            "2024-05-01T12:30:00.5Z",
+            "2024-05-01T12:30:00.123456789Z",
+            "2024-05-01T12:30:00+02:00",
+            "2024-05-01T12:30:00.250-05:30",
+            "2024-02-29T00:00:00Z",
+            "2000-02-29T00:00:00Z",
+            "2016-12-31T23:59:60Z",
+        ] {
+            assert!(is_rfc3339(text), "{}", text);
+        }
+    }
+
+    #[test]
+    fn rejects_other_date_times() {
+        for text in [
+            "2024-13-01T12:30:00Z",
+            "2024-00-01T12:30:00Z",
+            "2024-04-31T12:30:00Z",
+            "2024-05-00T12:30:00Z",
+            "2023-02-29T00:00:00Z",
+            "1900-02-29T00:00:00Z",
+            "2024-05-01T24:00:00Z",
+            "2024-05-01T12:60:00Z",
+            "2024-05-01T12:30:00",
+            "2024-05-01T12:30:00.Z",
+            "2024-05-01T12:30:00+24:00",
+            "2024-05-01T12:30:00+02",
+            "2024-05-01T12:30:00+0200",
+            "2024-05-01",
+            "2024/05/01T12:30:00Z",
+            "",
+        ] {
+            assert!(!is_rfc3339(text), "{}", text);
+        }
+    }
+
+    #[test]
+    fn checks_timestamps_unless_validation_is_off() {
+        pyo3::prepare_freethreaded_python();
+        let encoded = codec::EncodedMessage::Json(r#"{"message": "hello", "timestamp": "yesterday"}"#.to_string());
+        assert!(decode_as(Validation::Off, &encoded).is_ok());
+        let err = decode_as(Validation::Timestamps, &encoded).unwrap_err();
+        assert!(err.to_string().contains("Invalid message timestamp"), "{}", err);
+    }
+
+    #[test]
+    fn rejects_unknown_fields_only_when_strict() {
+        pyo3::prepare_freethreaded_python();
+        let encoded = codec::EncodedMessage::Json(r#"{"message": "hello", "level": 1, "extra": true}"#.to_string());
+        assert_eq!(decode_as(Validation::Timestamps, &encoded).unwrap().message, "hello");
+        let err = decode_as(Validation::Strict, &encoded).unwrap_err();
+        assert!(err.to_string().contains("Unknown message fields: extra, level"), "{}", err);
+        let known = codec::EncodedMessage::Json(r#"{"message": "hi", "payload": [1]}"#.to_string());
+        assert_eq!(decode_as(Validation::Strict, &known).unwrap().payload, serde_json::json!([1]));
+    }
+}
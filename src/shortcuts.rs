@@ -0,0 +1,134 @@
+use global_hotkey::hotkey::HotKey;
+use global_hotkey::{GlobalHotKeyEvent, GlobalHotKeyManager, HotKeyState};
+use once_cell::sync::Lazy;
+use pyo3::prelude::*;
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use crate::{events, send_runtime_message, RuntimeMessage};
+
+struct Shortcut {
+    accelerator: String,
+    callback: Py<PyAny>,
+}
+
+/// Registered shortcuts by hotkey id, so spellings of the same
+/// accelerator (`"ctrl+a"`, `"Control+KeyA"`) are one shortcut.
+static SHORTCUTS: Lazy<Mutex<HashMap<u32, Shortcut>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn shortcuts() -> MutexGuard<'static, HashMap<u32, Shortcut>> {
+    SHORTCUTS.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn parse(accelerator: &str) -> PyResult<HotKey> {
+    accelerator.parse().map_err(|e| {
+        pyo3::exceptions::PyValueError::new_err(format!("Invalid accelerator {:?}: {}", accelerator, e))
+    })
+}
+
+/// Calls `callback()` whenever `accelerator` (e.g. `"CmdOrCtrl+Shift+K"`)
+/// is pressed, even while no window of the app has focus. A shortcut the
+/// OS refuses, e.g. because another app holds it, is logged and dropped
+/// from [`list_shortcuts`].
+///
+/// # Errors
+/// - Raises `PyValueError` if `accelerator` cannot be parsed or is
+///   already registered.
+/// - Raises `FrameError` if the event loop is not running.
+#[pyfunction]
+pub fn register_shortcut(accelerator: String, callback: Py<PyAny>) -> PyResult<()> {
+    let hotkey = parse(&accelerator)?;
+    let mut shortcuts = shortcuts();
+    if shortcuts.contains_key(&hotkey.id()) {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "Shortcut {:?} is already registered",
+            accelerator
+        )));
+    }
+    send_runtime_message(RuntimeMessage::RegisterShortcut(hotkey))?;
+    shortcuts.insert(hotkey.id(), Shortcut { accelerator, callback });
+    Ok(())
+}
+
+/// Removes the shortcut registered for `accelerator`.
+///
+/// # Errors
+/// - Raises `PyValueError` if `accelerator` cannot be parsed or is not
+///   registered.
+/// - Raises `FrameError` if the event loop is not running.
+#[pyfunction]
+pub fn unregister_shortcut(accelerator: String) -> PyResult<()> {
+    let hotkey = parse(&accelerator)?;
+    let mut shortcuts = shortcuts();
+    if !shortcuts.contains_key(&hotkey.id()) {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "Shortcut {:?} is not registered",
+            accelerator
+        )));
+    }
+    send_runtime_message(RuntimeMessage::UnregisterShortcut(hotkey))?;
+    shortcuts.remove(&hotkey.id());
+    Ok(())
+}
+
+/// Returns the accelerators of the registered shortcuts, as passed to
+/// [`register_shortcut`].
+#[pyfunction]
+pub fn list_shortcuts() -> Vec<String> {
+    let mut accelerators: Vec<_> = shortcuts().values().map(|shortcut| shortcut.accelerator.clone()).collect();
+    accelerators.sort();
+    accelerators
+}
+
+/// The OS registrations, owned by the event loop: global-hotkey needs the
+/// main thread on macOS, and Windows delivers hotkeys through the thread's
+/// message loop.
+#[derive(Default)]
+pub struct HotKeys {
+    manager: Option<GlobalHotKeyManager>,
+}
+
+impl HotKeys {
+    fn manager(&mut self) -> global_hotkey::Result<&GlobalHotKeyManager> {
+        if self.manager.is_none() {
+            self.manager = Some(GlobalHotKeyManager::new()?);
+        }
+        Ok(self.manager.as_ref().unwrap())
+    }
+
+    pub fn register(&mut self, hotkey: HotKey) {
+        if let Err(e) = self.manager().and_then(|manager| manager.register(hotkey)) {
+            log::error!("Failed to register shortcut {}: {}", hotkey, e);
+            shortcuts().remove(&hotkey.id());
+        }
+    }
+
+    pub fn unregister(&mut self, hotkey: HotKey) {
+        if let Err(e) = self.manager().and_then(|manager| manager.unregister(hotkey)) {
+            log::warn!("Failed to unregister shortcut {}: {}", hotkey, e);
+        }
+    }
+}
+
+/// Calls the callback of the shortcut `id`, outside the registry lock so
+/// it may (un)register shortcuts itself.
+pub fn dispatch(id: u32) {
+    let callback = Python::with_gil(|py| shortcuts().get(&id).map(|shortcut| shortcut.callback.clone_ref(py)));
+    if let Some(callback) = callback {
+        events::notify(&callback, ());
+    }
+}
+
+/// Forwards presses of registered shortcuts to the event loop as
+/// [`RuntimeMessage::ShortcutPressed`].
+pub fn forward_events(proxy: tao::event_loop::EventLoopProxy<RuntimeMessage>) {
+    let proxy = Mutex::new(proxy);
+    GlobalHotKeyEvent::set_event_handler(Some(move |event: GlobalHotKeyEvent| {
+        if event.state == HotKeyState::Pressed {
+            let _ = proxy
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .send_event(RuntimeMessage::ShortcutPressed(event.id));
+        }
+    }));
+}
@@ -0,0 +1,79 @@
+use once_cell::sync::Lazy;
+use pyo3::prelude::*;
+use std::sync::{Mutex, PoisonError};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use crate::{send_runtime_message, RuntimeMessage};
+
+/// Python threads of the running webframe, joined by [`shutdown`].
+static THREADS: Lazy<Mutex<Vec<JoinHandle<()>>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+/// How often [`shutdown`] checks whether the threads finished.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Registers a thread running a Python event loop for [`shutdown`] to join.
+pub fn track(thread: JoinHandle<()>) {
+    THREADS.lock().unwrap_or_else(PoisonError::into_inner).push(thread);
+}
+
+/// Detaches the threads of an earlier webframe that exited without
+/// [`shutdown`], so shutting down the next one does not wait for them.
+pub fn reset() {
+    THREADS.lock().unwrap_or_else(PoisonError::into_inner).clear();
+}
+
+/// Joins the tracked threads that finish before `deadline`, except the
+/// calling one, and keeps the others. Returns whether all were joined.
+fn join_until(deadline: Instant) -> bool {
+    let current = std::thread::current().id();
+    let mut threads = std::mem::take(&mut *THREADS.lock().unwrap_or_else(PoisonError::into_inner));
+    threads.retain(|thread| thread.thread().id() != current);
+    while threads.iter().any(|thread| !thread.is_finished()) && Instant::now() < deadline {
+        std::thread::sleep(POLL_INTERVAL);
+    }
+    let (finished, running): (Vec<_>, Vec<_>) = threads.into_iter().partition(JoinHandle::is_finished);
+    for thread in finished {
+        if thread.join().is_err() {
+            log::error!("Python event loop thread panicked");
+        }
+    }
+    let joined = running.is_empty();
+    THREADS.lock().unwrap_or_else(PoisonError::into_inner).extend(running);
+    joined
+}
+
+/// Tears the webframe down for good, unlike `quit_app()` which only ends
+/// the event loop: every window closes, background tasks are aborted, the
+/// message channels behind `emit_*` are dropped so later emits raise
+/// `ChannelError`, and the asyncio loops running IPC handler coroutines
+/// and the `pyevent_to_rust_queue` thread are stopped instead of
+/// restarted. Waits up to `timeout` seconds for those Python threads to
+/// exit, with the GIL released, so the process can start another
+/// webframe, e.g. with `create_webframe(blocking=False)`, without them
+/// piling up.
+///
+/// Called from the Python thread itself, e.g. in a coroutine, that thread
+/// is not waited for; it exits once control returns to its loop.
+///
+/// # Errors
+/// - Raises `PyValueError` if `timeout` is negative or not finite.
+/// - Raises `FrameError` if no webframe is running.
+/// - Raises `PyTimeoutError` if a Python thread is still running after
+///   `timeout`, e.g. blocked in a synchronous call.
+#[pyfunction]
+#[pyo3(signature = (timeout=5.0))]
+pub fn shutdown(py: Python<'_>, timeout: f64) -> PyResult<()> {
+    let timeout = Duration::try_from_secs_f64(timeout)
+        .map_err(|e| pyo3::exceptions::PyValueError::new_err(format!("invalid timeout: {e}")))?;
+    send_runtime_message(RuntimeMessage::Shutdown)?;
+    let deadline = Instant::now() + timeout;
+    if py.allow_threads(|| join_until(deadline)) {
+        Ok(())
+    } else {
+        Err(pyo3::exceptions::PyTimeoutError::new_err(format!(
+            "Python event loop threads still running after {:.1}s",
+            timeout.as_secs_f64()
+        )))
+    }
+}
@@ -0,0 +1,26 @@
+use crate::{send_runtime_message, tasks, RuntimeMessage};
+
+/// Exit status of a process ended by a second Ctrl+C, as shells report
+/// SIGINT.
+const INTERRUPTED: i32 = 130;
+
+/// Exits the event loop on the first Ctrl+C: SIGINT on Unix, the console's
+/// Ctrl+C event on Windows (only delivered to apps attached to a console).
+/// A second Ctrl+C, e.g. while the loop is stuck in a handler, ends the
+/// process right away.
+pub fn spawn(tasks: &mut tasks::Tasks, runtime: &tokio::runtime::Handle) {
+    tasks.spawn(runtime, "interrupt handler".to_string(), async {
+        tokio::signal::ctrl_c()
+            .await
+            .map_err(|e| format!("Failed to listen for Ctrl+C: {}", e))?;
+        log::info!("Interrupted, exiting the event loop");
+        if send_runtime_message(RuntimeMessage::Interrupt).is_err() {
+            return Ok(());
+        }
+        tokio::signal::ctrl_c()
+            .await
+            .map_err(|e| format!("Failed to listen for Ctrl+C: {}", e))?;
+        log::warn!("Interrupted again, exiting the process");
+        std::process::exit(INTERRUPTED);
+    });
+}
@@ -0,0 +1,61 @@
+use tao::dpi::{LogicalSize, PhysicalPosition};
+use tao::event_loop::EventLoopWindowTarget;
+
+use crate::{errors, RuntimeMessage};
+
+/// Size of the splash window in logical pixels.
+const SIZE: LogicalSize<f64> = LogicalSize::new(480.0, 320.0);
+
+/// A small borderless window shown while the main window loads hidden,
+/// dismissed once the page calls `window.ipcReady()`.
+pub struct Splash {
+    // Dropped before the window hosting it.
+    _webview: wry::WebView,
+    window: tao::window::Window,
+}
+
+impl Splash {
+    /// Shows `html` in a splash window centered on the primary monitor.
+    ///
+    /// # Errors
+    /// Returns `WindowError` or `WebViewError` if the window or its
+    /// WebView cannot be created.
+    pub fn show(target: &EventLoopWindowTarget<RuntimeMessage>, html: &str) -> pyo3::PyResult<Self> {
+        let mut builder = tao::window::WindowBuilder::new()
+            .with_title("")
+            .with_inner_size(SIZE)
+            .with_resizable(false)
+            .with_decorations(false)
+            .with_always_on_top(true);
+        if let Some(monitor) = target.primary_monitor() {
+            let size = SIZE.to_physical::<f64>(monitor.scale_factor());
+            let origin = monitor.position();
+            let x = origin.x as f64 + (monitor.size().width as f64 - size.width) / 2.0;
+            let y = origin.y as f64 + (monitor.size().height as f64 - size.height) / 2.0;
+            builder = builder.with_position(PhysicalPosition::new(x, y));
+        }
+        let window = builder
+            .build(target)
+            .map_err(|e| errors::WindowError::new_err(format!("Failed to create splash window: {}", e)))?;
+        let webview = wry::WebViewBuilder::new()
+            .with_html(html)
+            .build(&window)
+            .map_err(|e| errors::WebViewError::new_err(format!("Failed to create splash WebView: {}", e)))?;
+        Ok(Splash { _webview: webview, window })
+    }
+
+    pub fn window_id(&self) -> tao::window::WindowId {
+        self.window.id()
+    }
+}
+
+/// Closes `splash`, if still shown, and reveals the main `window`.
+pub fn dismiss(splash: &mut Option<Splash>, window: Option<&tao::window::Window>) {
+    if splash.take().is_none() {
+        return;
+    }
+    if let Some(window) = window {
+        window.set_visible(true);
+        window.set_focus();
+    }
+}
@@ -0,0 +1,95 @@
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Message counts of one channel since the webframe started.
+pub struct Counter {
+    sent: AtomicU64,
+    received: AtomicU64,
+    /// Sends that failed: the channel was full or closed.
+    rejected: AtomicU64,
+}
+
+impl Counter {
+    const fn new() -> Self {
+        Counter { sent: AtomicU64::new(0), received: AtomicU64::new(0), rejected: AtomicU64::new(0) }
+    }
+
+    pub fn sent(&self) {
+        self.sent.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn received(&self) {
+        self.received.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn rejected(&self) {
+        self.rejected.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Counts `outcome` of a send as sent or rejected, passing it on.
+    pub fn count<T, E>(&self, outcome: Result<T, E>) -> Result<T, E> {
+        match &outcome {
+            Ok(_) => self.sent(),
+            Err(_) => self.rejected(),
+        }
+        outcome
+    }
+
+    fn reset(&self) {
+        self.sent.store(0, Ordering::Relaxed);
+        self.received.store(0, Ordering::Relaxed);
+        self.rejected.store(0, Ordering::Relaxed);
+    }
+
+    fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        // Read `received` first, so a message taken in between is not
+        // counted as received but never sent.
+        let received = self.received.load(Ordering::Relaxed);
+        let sent = self.sent.load(Ordering::Relaxed);
+        let dict = PyDict::new(py);
+        dict.set_item("sent", sent)?;
+        dict.set_item("received", received)?;
+        dict.set_item("pending", sent.saturating_sub(received))?;
+        dict.set_item("rejected", self.rejected.load(Ordering::Relaxed))?;
+        Ok(dict)
+    }
+}
+
+/// `emit_*` messages to the Rust consumers of every window.
+pub static EMITS: Counter = Counter::new();
+/// Objects the Python thread sends through its `SenderHandle`.
+pub static FROM_PYTHON: Counter = Counter::new();
+/// Replies and pings the Python thread's `ReceiverHandle` gets.
+pub static TO_PYTHON: Counter = Counter::new();
+
+/// Starts counting afresh for a new webframe.
+pub fn reset() {
+    EMITS.reset();
+    FROM_PYTHON.reset();
+    TO_PYTHON.reset();
+}
+
+/// Reports message counts of the webframe's channels, to diagnose
+/// backpressure and lost messages.
+///
+/// Returns a dict with one entry per channel: `"emit"` for [`emit_str`],
+/// `emit_sync` and `emit_async` messages to the Rust consumers (all
+/// windows together), `"from_python"` for objects the
+/// `pyevent_to_rust_queue` coroutine sends through its `SenderHandle`, and
+/// `"to_python"` for the replies and pings its `ReceiverHandle` gets. Each
+/// holds `sent`, `received` (taken by the other side), `pending` (sent but
+/// not taken yet, e.g. a consumer falling behind) and `rejected` (sends
+/// that failed because the channel was full or closed). Counts start at
+/// zero with each `create_webframe`; channels of `create_channel` pairs
+/// are not counted.
+///
+/// [`emit_str`]: crate::emit_str
+#[pyfunction]
+pub fn channel_stats(py: Python<'_>) -> PyResult<Bound<'_, PyDict>> {
+    let stats = PyDict::new(py);
+    stats.set_item("emit", EMITS.to_dict(py)?)?;
+    stats.set_item("from_python", FROM_PYTHON.to_dict(py)?)?;
+    stats.set_item("to_python", TO_PYTHON.to_dict(py)?)?;
+    Ok(stats)
+}
@@ -0,0 +1,48 @@
+use std::any::Any;
+use std::future::Future;
+use tokio::task::AbortHandle;
+
+/// Long-running background tasks of a webframe. A task that fails or
+/// panics is logged rather than dying unnoticed, and all of them are
+/// aborted together on shutdown.
+#[derive(Default)]
+pub struct Tasks {
+    handles: Vec<AbortHandle>,
+}
+
+impl Tasks {
+    /// Runs `task` on `runtime`, logging its error or panic under `name`.
+    pub fn spawn<F>(&mut self, runtime: &tokio::runtime::Handle, name: String, task: F)
+    where
+        F: Future<Output = Result<(), String>> + Send + 'static,
+    {
+        self.handles.retain(|handle| !handle.is_finished());
+        let handle = runtime.spawn(task);
+        self.handles.push(handle.abort_handle());
+        runtime.spawn(async move {
+            match handle.await {
+                Ok(Ok(())) => log::debug!("Task {} finished", name),
+                Ok(Err(e)) => log::error!("Task {} failed: {}", name, e),
+                Err(e) if e.is_panic() => log::error!("Task {} panicked: {}", name, panic_message(&*e.into_panic())),
+                Err(_) => log::debug!("Task {} cancelled", name),
+            }
+        });
+    }
+
+    /// Cancels every task still running.
+    pub fn abort_all(&mut self) {
+        for handle in self.handles.drain(..) {
+            handle.abort();
+        }
+    }
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message
+    } else {
+        "<non-string panic payload>"
+    }
+}
@@ -0,0 +1,98 @@
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::sync::{Mutex, PoisonError};
+use tray_icon::{MouseButton, MouseButtonState, TrayIcon, TrayIconBuilder, TrayIconEvent};
+
+use crate::{icon, menu, RuntimeMessage};
+
+/// The tray icon requested with `create_webframe(tray=...)`.
+pub struct TraySpec {
+    icon: tray_icon::Icon,
+    tooltip: Option<String>,
+    menu: Option<muda::Menu>,
+    /// Show or hide the main window on a left click.
+    pub toggle_window: bool,
+}
+
+impl TraySpec {
+    /// Reads the `tray` dict: `icon` (required, like `create_webframe`'s
+    /// `icon`), `tooltip`, `menu` (entries like the menu bar's) and
+    /// `toggle_window` (default `True`).
+    ///
+    /// # Errors
+    /// Returns `PyValueError` for unknown keys, a missing or invalid icon
+    /// and an invalid menu.
+    pub fn from_py(py: Python<'_>, value: Py<PyAny>) -> PyResult<Self> {
+        let dict = value.bind(py).downcast::<PyDict>()?;
+        for key in dict.keys() {
+            let key: String = key.extract()?;
+            if !matches!(key.as_str(), "icon" | "tooltip" | "menu" | "toggle_window") {
+                return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                    "Unknown tray option: {:?}",
+                    key
+                )));
+            }
+        }
+        let icon = dict
+            .get_item("icon")?
+            .ok_or_else(|| pyo3::exceptions::PyValueError::new_err("tray requires an icon"))?;
+        let (rgba, width, height) = icon::rgba_from_py(py, icon.unbind())?;
+        let icon = tray_icon::Icon::from_rgba(rgba, width, height)
+            .map_err(|e| pyo3::exceptions::PyValueError::new_err(format!("Invalid tray icon: {}", e)))?;
+        let menu = match dict.get_item("menu")? {
+            Some(menu) => Some(menu::build(&menu::parse_entries(py, menu.unbind())?)?),
+            None => None,
+        };
+        Ok(TraySpec {
+            icon,
+            tooltip: dict.get_item("tooltip")?.map(|tooltip| tooltip.extract()).transpose()?,
+            menu,
+            toggle_window: dict
+                .get_item("toggle_window")?
+                .map(|toggle| toggle.extract())
+                .transpose()?
+                .unwrap_or(true),
+        })
+    }
+
+    /// Shows the icon in the system tray. On macOS this must happen once
+    /// the event loop runs.
+    ///
+    /// # Errors
+    /// Returns `PyRuntimeError` if the platform has no tray, e.g. a Linux
+    /// desktop without AppIndicator support.
+    pub fn build(self) -> PyResult<TrayIcon> {
+        let mut builder = TrayIconBuilder::new()
+            .with_icon(self.icon)
+            .with_menu_on_left_click(!self.toggle_window);
+        if let Some(tooltip) = self.tooltip {
+            builder = builder.with_tooltip(tooltip);
+        }
+        if let Some(menu) = self.menu {
+            builder = builder.with_menu(Box::new(menu));
+        }
+        builder
+            .build()
+            .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(format!("System tray unavailable: {}", e)))
+    }
+}
+
+/// Forwards finished clicks on the tray icon to the event loop as
+/// [`RuntimeMessage::TrayClicked`]. Clicks on its menu arrive as menu
+/// events (see [`menu::forward_events`]).
+pub fn forward_events(proxy: tao::event_loop::EventLoopProxy<RuntimeMessage>) {
+    let proxy = Mutex::new(proxy);
+    TrayIconEvent::set_event_handler(Some(move |event: TrayIconEvent| {
+        if let TrayIconEvent::Click { button, button_state: MouseButtonState::Up, .. } = event {
+            let button = match button {
+                MouseButton::Left => "left",
+                MouseButton::Right => "right",
+                MouseButton::Middle => "middle",
+            };
+            let _ = proxy
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .send_event(RuntimeMessage::TrayClicked(button));
+        }
+    }));
+}
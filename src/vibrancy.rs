@@ -0,0 +1,72 @@
+use window_vibrancy::NSVisualEffectMaterial;
+
+/// Background material drawn behind a transparent window.
+///
+/// macOS materials map to `NSVisualEffectView`; `blur`, `acrylic`, `mica`
+/// and `tabbed` are the Windows system backdrops.
+#[derive(Debug, Clone, Copy)]
+pub enum Vibrancy {
+    /// A macOS `NSVisualEffectMaterial`.
+    Material(NSVisualEffectMaterial),
+    Blur,
+    Acrylic,
+    Mica,
+    Tabbed,
+}
+
+impl Vibrancy {
+    /// Parses a material name as passed from Python (e.g. `"sidebar"`, `"mica"`).
+    ///
+    /// # Errors
+    /// Returns `PyValueError` for unknown material names.
+    pub fn parse(name: &str) -> pyo3::PyResult<Self> {
+        let material = match name {
+            "titlebar" => NSVisualEffectMaterial::Titlebar,
+            "selection" => NSVisualEffectMaterial::Selection,
+            "menu" => NSVisualEffectMaterial::Menu,
+            "popover" => NSVisualEffectMaterial::Popover,
+            "sidebar" => NSVisualEffectMaterial::Sidebar,
+            "header_view" => NSVisualEffectMaterial::HeaderView,
+            "sheet" => NSVisualEffectMaterial::Sheet,
+            "window_background" => NSVisualEffectMaterial::WindowBackground,
+            "hud_window" => NSVisualEffectMaterial::HudWindow,
+            "full_screen_ui" => NSVisualEffectMaterial::FullScreenUI,
+            "tooltip" => NSVisualEffectMaterial::Tooltip,
+            "content_background" => NSVisualEffectMaterial::ContentBackground,
+            "under_window_background" => NSVisualEffectMaterial::UnderWindowBackground,
+            "under_page_background" => NSVisualEffectMaterial::UnderPageBackground,
+            "blur" => return Ok(Vibrancy::Blur),
+            "acrylic" => return Ok(Vibrancy::Acrylic),
+            "mica" => return Ok(Vibrancy::Mica),
+            "tabbed" => return Ok(Vibrancy::Tabbed),
+            other => {
+                return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                    "Unknown vibrancy material: {:?}",
+                    other
+                )))
+            }
+        };
+        Ok(Vibrancy::Material(material))
+    }
+}
+
+/// Applies `vibrancy` behind `window`.
+///
+/// The window must have been built transparent for the effect to show.
+/// Materials the current platform does not provide (including every
+/// material on Linux) are logged and otherwise ignored.
+pub fn apply(window: &tao::window::Window, vibrancy: Vibrancy) {
+    let result = match vibrancy {
+        Vibrancy::Material(material) => {
+            window_vibrancy::apply_vibrancy(window, material, None, None)
+        }
+        Vibrancy::Blur => window_vibrancy::apply_blur(window, None),
+        Vibrancy::Acrylic => window_vibrancy::apply_acrylic(window, None),
+        Vibrancy::Mica => window_vibrancy::apply_mica(window, None),
+        Vibrancy::Tabbed => window_vibrancy::apply_tabbed(window, None),
+    };
+
+    if let Err(e) = result {
+        log::warn!("Vibrancy {:?} not applied: {}", vibrancy, e);
+    }
+}
@@ -0,0 +1,140 @@
+use once_cell::sync::Lazy;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use wry::dpi::{LogicalPosition, LogicalSize, PhysicalPosition, PhysicalSize};
+
+/// Ids handed out by `add_child_view`, checked synchronously so Python
+/// gets an error for unknown ids instead of a silently dropped message.
+static VIEW_IDS: Lazy<Mutex<HashSet<u64>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+
+/// Locks the view ids, recovering a poisoned lock: a set of ids cannot be
+/// left half-updated.
+fn view_ids() -> MutexGuard<'static, HashSet<u64>> {
+    VIEW_IDS.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+static NEXT_VIEW_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Reserves the id of a child view about to be created.
+pub fn allocate_id() -> u64 {
+    let id = NEXT_VIEW_ID.fetch_add(1, Ordering::Relaxed);
+    view_ids().insert(id);
+    id
+}
+
+/// # Errors
+/// Returns `PyValueError` if `id` does not name a child view.
+pub fn check_id(id: u64) -> pyo3::PyResult<()> {
+    if view_ids().contains(&id) {
+        Ok(())
+    } else {
+        Err(pyo3::exceptions::PyValueError::new_err(format!("Unknown view id: {}", id)))
+    }
+}
+
+/// Placement of a child view inside the window.
+#[derive(Debug, Clone, Copy)]
+pub enum ViewBounds {
+    /// Fixed position and size in logical pixels.
+    Absolute { x: f64, y: f64, width: f64, height: f64 },
+    /// Fractions (`0.0..=1.0`) of the window's inner size, re-applied
+    /// whenever the window is resized.
+    Relative { x: f64, y: f64, width: f64, height: f64 },
+}
+
+impl ViewBounds {
+    /// Builds bounds from the values passed to Python.
+    ///
+    /// # Errors
+    /// Returns `PyValueError` for negative sizes, or relative values outside
+    /// `0.0..=1.0`.
+    pub fn new(x: f64, y: f64, width: f64, height: f64, relative: bool) -> pyo3::PyResult<Self> {
+        if width < 0.0 || height < 0.0 {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "View width and height must not be negative",
+            ));
+        }
+        if !relative {
+            return Ok(ViewBounds::Absolute { x, y, width, height });
+        }
+        if [x, y, width, height].iter().any(|v| !(0.0..=1.0).contains(v)) {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "Relative view bounds must be between 0.0 and 1.0",
+            ));
+        }
+        Ok(ViewBounds::Relative { x, y, width, height })
+    }
+
+    /// Resolves the bounds against the window's inner size.
+    pub fn to_rect(self, window_size: PhysicalSize<u32>) -> wry::Rect {
+        match self {
+            ViewBounds::Absolute { x, y, width, height } => wry::Rect {
+                position: LogicalPosition::new(x, y).into(),
+                size: LogicalSize::new(width, height).into(),
+            },
+            ViewBounds::Relative { x, y, width, height } => {
+                let (w, h) = (window_size.width as f64, window_size.height as f64);
+                wry::Rect {
+                    position: PhysicalPosition::new((x * w) as i32, (y * h) as i32).into(),
+                    size: PhysicalSize::new((width * w) as u32, (height * h) as u32).into(),
+                }
+            }
+        }
+    }
+}
+
+struct ChildView {
+    webview: wry::WebView,
+    bounds: ViewBounds,
+}
+
+/// Child WebViews of the window, keyed by view id.
+#[derive(Default)]
+pub struct ChildViews {
+    views: HashMap<u64, ChildView>,
+}
+
+impl ChildViews {
+    pub fn insert(&mut self, id: u64, webview: wry::WebView, bounds: ViewBounds) {
+        self.views.insert(id, ChildView { webview, bounds });
+    }
+
+    pub fn webview(&self, id: u64) -> Option<&wry::WebView> {
+        self.views.get(&id).map(|view| &view.webview)
+    }
+
+    /// Moves and resizes the view `id`.
+    pub fn set_bounds(&mut self, id: u64, bounds: ViewBounds, window_size: PhysicalSize<u32>) {
+        let Some(view) = self.views.get_mut(&id) else {
+            log::warn!("Child view {} does not exist", id);
+            return;
+        };
+        view.bounds = bounds;
+        if let Err(e) = view.webview.set_bounds(bounds.to_rect(window_size)) {
+            log::error!("Failed to set bounds of child view {}: {:?}", id, e);
+        }
+    }
+
+    /// Re-applies relative bounds after the window was resized.
+    pub fn reflow(&self, window_size: PhysicalSize<u32>) {
+        for (id, view) in &self.views {
+            if let ViewBounds::Relative { .. } = view.bounds {
+                if let Err(e) = view.webview.set_bounds(view.bounds.to_rect(window_size)) {
+                    log::error!("Failed to reflow child view {}: {:?}", id, e);
+                }
+            }
+        }
+    }
+
+    /// Drops every child view, e.g. when the window closes.
+    pub fn clear(&mut self) {
+        self.views.clear();
+        view_ids().clear();
+    }
+}
+
+/// Forgets an id whose view could not be created.
+pub fn release_id(id: u64) {
+    view_ids().remove(&id);
+}
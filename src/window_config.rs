@@ -0,0 +1,212 @@
+use pyo3::prelude::*;
+use serde::Deserialize;
+use tao::dpi::{LogicalPosition, LogicalSize};
+use tao::window::{Theme, WindowBuilder};
+
+/// Options of the native window, passed to `create_webframe` as a dict.
+///
+/// Missing keys take their default; unknown keys are rejected so typos
+/// do not go unnoticed.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WindowConfig {
+    pub title: String,
+    /// Inner size in logical pixels.
+    pub width: f64,
+    pub height: f64,
+    /// Outer position in logical pixels; `x` and `y` go together. The
+    /// platform places the window when they are omitted.
+    pub x: Option<f64>,
+    pub y: Option<f64>,
+    pub resizable: bool,
+    /// Title bar buttons; Linux cannot hide the minimize and maximize
+    /// buttons on their own.
+    pub closable: bool,
+    pub minimizable: bool,
+    pub maximizable: bool,
+    /// `False` drops the native title bar and borders, for custom chrome.
+    pub decorations: bool,
+    pub always_on_top: bool,
+    /// Clears the window and WebView background so the page can draw
+    /// non-rectangular or translucent chrome. `None` when not given, so
+    /// `create_webframe` can tell an explicit `False` from the default.
+    pub transparent: Option<bool>,
+    /// Bounds of the inner size when resizing, `[width, height]` in
+    /// logical pixels.
+    pub min_size: Option<(f64, f64)>,
+    pub max_size: Option<(f64, f64)>,
+}
+
+impl Default for WindowConfig {
+    fn default() -> Self {
+        WindowConfig {
+            title: "PyFrame".to_string(),
+            width: 800.0,
+            height: 600.0,
+            x: None,
+            y: None,
+            resizable: true,
+            closable: true,
+            minimizable: true,
+            maximizable: true,
+            decorations: true,
+            always_on_top: false,
+            transparent: None,
+            min_size: None,
+            max_size: None,
+        }
+    }
+}
+
+impl WindowConfig {
+    /// Reads the config from the Python value passed as `window`; `None`
+    /// gives the defaults.
+    ///
+    /// # Errors
+    /// Returns `PyValueError` if `value` is not a dict of known options,
+    /// a dimension is not positive, only one of `x` and `y` is given,
+    /// `min_size` exceeds `max_size`, or a non-resizable window has size
+    /// bounds other than its size.
+    pub fn from_py(py: Python<'_>, value: Option<Py<PyAny>>) -> PyResult<Self> {
+        let Some(value) = value else {
+            return Ok(WindowConfig::default());
+        };
+        let json: String = py.import("json")?.call_method1("dumps", (value,))?.extract()?;
+        let config: WindowConfig = serde_json::from_str(&json).map_err(|e| {
+            pyo3::exceptions::PyValueError::new_err(format!("Invalid window config: {}", e))
+        })?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn validate(&self) -> PyResult<()> {
+        let sizes = [Some((self.width, self.height)), self.min_size, self.max_size];
+        if let Some((width, height)) = sizes.into_iter().flatten().find(|&(w, h)| !(w > 0.0 && h > 0.0)) {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Window sizes must be positive, got {}x{}",
+                width, height
+            )));
+        }
+        if let (Some(min), Some(max)) = (self.min_size, self.max_size) {
+            if min.0 > max.0 || min.1 > max.1 {
+                return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                    "Window min_size {}x{} exceeds max_size {}x{}",
+                    min.0, min.1, max.0, max.1
+                )));
+            }
+        }
+        let size = (self.width, self.height);
+        if !self.resizable && [self.min_size, self.max_size].into_iter().flatten().any(|bound| bound != size) {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "A window that is not resizable keeps its size {}x{}, so min_size and max_size cannot differ from it",
+                self.width, self.height
+            )));
+        }
+        if self.x.is_some() != self.y.is_some() {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "Window x and y must be given together",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Applies the options to `builder`.
+    pub fn apply(&self, builder: WindowBuilder) -> WindowBuilder {
+        let mut builder = builder
+            .with_title(&self.title)
+            .with_inner_size(LogicalSize::new(self.width, self.height))
+            .with_resizable(self.resizable)
+            .with_closable(self.closable)
+            .with_minimizable(self.minimizable)
+            .with_maximizable(self.maximizable)
+            .with_decorations(self.decorations)
+            .with_always_on_top(self.always_on_top)
+            .with_transparent(self.transparent.unwrap_or(false));
+        if let Some((width, height)) = self.min_size {
+            builder = builder.with_min_inner_size(LogicalSize::new(width, height));
+        }
+        if let Some((width, height)) = self.max_size {
+            builder = builder.with_max_inner_size(LogicalSize::new(width, height));
+        }
+        match (self.x, self.y) {
+            (Some(x), Some(y)) => builder.with_position(LogicalPosition::new(x, y)),
+            _ => builder,
+        }
+    }
+}
+
+/// Parses a `theme` argument: `"light"`, `"dark"`, or `"system"` (`None`,
+/// following the OS).
+///
+/// # Errors
+/// Returns `PyValueError` for other names.
+pub fn parse_theme(name: &str) -> PyResult<Option<Theme>> {
+    match name {
+        "light" => Ok(Some(Theme::Light)),
+        "dark" => Ok(Some(Theme::Dark)),
+        "system" => Ok(None),
+        _ => Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "Unknown theme {:?} (expected \"light\", \"dark\" or \"system\")",
+            name
+        ))),
+    }
+}
+
+/// A color as passed from Python: a hex string (`"#1e1e1e"`, or
+/// `"#1e1e1e80"` with alpha) or a `(r, g, b)` / `(r, g, b, a)` tuple.
+#[derive(Debug, FromPyObject)]
+pub enum Color {
+    Hex(String),
+    Components(Vec<u8>),
+}
+
+impl Color {
+    /// The color as RGBA, opaque unless alpha is given.
+    ///
+    /// # Errors
+    /// Returns `PyValueError` for malformed hex strings and tuples of
+    /// other lengths.
+    pub fn rgba(&self) -> PyResult<(u8, u8, u8, u8)> {
+        let components = match self {
+            Color::Components(components) => components.clone(),
+            Color::Hex(hex) => hex
+                .strip_prefix('#')
+                .filter(|digits| matches!(digits.len(), 6 | 8) && digits.is_ascii())
+                .and_then(|digits| {
+                    (0..digits.len())
+                        .step_by(2)
+                        .map(|i| u8::from_str_radix(&digits[i..i + 2], 16).ok())
+                        .collect::<Option<Vec<_>>>()
+                })
+                .ok_or_else(|| {
+                    pyo3::exceptions::PyValueError::new_err(format!(
+                        "Invalid color {:?} (expected \"#rrggbb\" or \"#rrggbbaa\")",
+                        hex
+                    ))
+                })?,
+        };
+        match components[..] {
+            [r, g, b] => Ok((r, g, b, 255)),
+            [r, g, b, a] => Ok((r, g, b, a)),
+            _ => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "A color needs 3 or 4 components, got {}",
+                components.len()
+            ))),
+        }
+    }
+}
+
+/// Name of `theme` as reported to Python.
+pub fn theme_name(theme: Theme) -> &'static str {
+    match theme {
+        Theme::Dark => "dark",
+        _ => "light",
+    }
+}
+
+/// Warns where a transparent window may not end up transparent.
+pub fn warn_transparency_caveats(transparent: bool) {
+    if transparent && cfg!(target_os = "linux") {
+        log::warn!("Transparent windows need a compositing window manager on Linux and may render opaque");
+    }
+}
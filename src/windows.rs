@@ -0,0 +1,112 @@
+use once_cell::sync::Lazy;
+use pyo3::prelude::*;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use crate::channel::MessageSender;
+use crate::Emitted;
+
+/// Id of the window built by `create_webframe`. The call never returns
+/// while the window is open, so the id is fixed instead.
+pub const MAIN_WINDOW_ID: u64 = 1;
+
+static NEXT_WINDOW_ID: AtomicU64 = AtomicU64::new(MAIN_WINDOW_ID + 1);
+
+/// Reserves the id of a window about to be opened.
+pub fn allocate_id() -> u64 {
+    NEXT_WINDOW_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+/// A window hosted by the event loop next to the main one.
+pub struct HostedWindow {
+    pub id: u64,
+    pub webview: wry::WebView,
+    /// Dropping it closes the window.
+    pub window: tao::window::Window,
+}
+
+/// Message channels of the open windows, keyed by window id. Ordered so
+/// that the first window is the fallback target of `emit_*`.
+static MESSAGE_CHANNELS: Lazy<Mutex<BTreeMap<u64, MessageSender<Emitted>>>> =
+    Lazy::new(|| Mutex::new(BTreeMap::new()));
+
+/// Locks the channel map. A panic while it was held cannot leave the map
+/// half-updated, so a poisoned lock is recovered instead of taking every
+/// later `emit_*` down with it.
+fn channels() -> MutexGuard<'static, BTreeMap<u64, MessageSender<Emitted>>> {
+    MESSAGE_CHANNELS.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Makes `sender` the message channel of the window `id`.
+pub fn register(id: u64, sender: MessageSender<Emitted>) {
+    channels().insert(id, sender);
+}
+
+/// Drops the message channel of a closed window.
+pub fn unregister(id: u64) {
+    channels().remove(&id);
+}
+
+/// Drops every message channel, e.g. on quit.
+pub fn clear() {
+    channels().clear();
+}
+
+/// # Errors
+/// Returns `PyValueError` if `id` does not name an open window.
+pub fn check_id(id: u64) -> PyResult<()> {
+    if channels().contains_key(&id) {
+        Ok(())
+    } else {
+        Err(pyo3::exceptions::PyValueError::new_err(format!("Unknown window id: {}", id)))
+    }
+}
+
+/// Returns the message channel of `window_id`, or of the first open window
+/// if no id is given.
+///
+/// # Errors
+/// Returns `PyValueError` for an unknown window id and `ChannelError`
+/// if no window is open.
+pub fn sender(window_id: Option<u64>) -> PyResult<MessageSender<Emitted>> {
+    let channels = channels();
+    match window_id {
+        Some(id) => channels.get(&id).cloned().ok_or_else(|| {
+            pyo3::exceptions::PyValueError::new_err(format!("Unknown window id: {}", id))
+        }),
+        None => channels
+            .values()
+            .next()
+            .cloned()
+            .ok_or_else(|| crate::errors::ChannelError::new_err("Channel not initialized")),
+    }
+}
+
+/// A change of a window's state requested from Python.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowAction {
+    Hide,
+    /// Also restores and focuses the window.
+    Show,
+    Minimize,
+    Maximize,
+}
+
+impl WindowAction {
+    /// Applies the action to `window` and returns whether it is visible
+    /// afterwards.
+    pub fn apply(self, window: &tao::window::Window) -> bool {
+        match self {
+            WindowAction::Hide => window.set_visible(false),
+            WindowAction::Show => {
+                window.set_visible(true);
+                window.set_minimized(false);
+                window.set_focus();
+            }
+            WindowAction::Minimize => window.set_minimized(true),
+            WindowAction::Maximize => window.set_maximized(true),
+        }
+        window.is_visible()
+    }
+}